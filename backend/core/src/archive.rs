@@ -0,0 +1,482 @@
+use std::{
+	fs::{self, File},
+	io::{self, Read},
+	path::{Component, Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use rai_pal_proc_macros::{serializable_enum, serializable_struct};
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZReader};
+use tar::EntryType;
+use xz2::read::XzDecoder;
+use zip::{ZipArchive, read::ZipFile};
+
+use crate::{
+	paths,
+	result::{Error, Result},
+};
+
+// Generous enough for any mod we know of, but stops zip bombs from filling up the disk.
+#[cfg(not(test))]
+pub const MAX_TOTAL_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+#[cfg(not(test))]
+pub const MAX_FILE_COUNT: usize = 50_000;
+
+// Tests get smaller limits, so they don't need gigabytes of files to hit them.
+#[cfg(test)]
+pub const MAX_TOTAL_SIZE: u64 = 1024 * 1024;
+#[cfg(test)]
+pub const MAX_FILE_COUNT: usize = 100;
+
+// 7z stores unix file modes in the upper half of the attributes when this flag is set.
+const SEVEN_ZIP_UNIX_EXTENSION: u32 = 0x8000;
+const SEVEN_ZIP_REPARSE_POINT: u32 = 0x400;
+const UNIX_FILE_TYPE_MASK: u32 = 0o170_000;
+const UNIX_SYMLINK: u32 = 0o120_000;
+
+#[serializable_enum]
+pub enum ArchiveFormat {
+	Zip,
+	SevenZip,
+	Tar,
+	TarGz,
+	TarXz,
+	// A lone dll or exe, which gets copied as it is.
+	SingleFile,
+}
+
+#[serializable_struct]
+pub struct ArchiveEntry {
+	// Relative to the extraction target, unless stated otherwise.
+	pub path: PathBuf,
+	pub size: u64,
+	pub is_dir: bool,
+}
+
+fn read_header(reader: impl Read) -> Result<Vec<u8>> {
+	let mut header = Vec::with_capacity(262);
+	reader.take(262).read_to_end(&mut header)?;
+
+	Ok(header)
+}
+
+fn is_tar(header: &[u8]) -> bool {
+	header.get(257..262) == Some(b"ustar")
+}
+
+fn has_single_file_extension(archive_path: &Path) -> bool {
+	archive_path.extension().is_some_and(|extension| {
+		extension.eq_ignore_ascii_case("dll") || extension.eq_ignore_ascii_case("exe")
+	})
+}
+
+fn unsupported_format_error(archive_path: &Path, reason: &str) -> Error {
+	Error::ArchiveFormatUnsupported(archive_path.to_path_buf(), reason.to_string())
+}
+
+// Detected by content, since download urls don't always end with a useful extension.
+pub fn detect_format(archive_path: &Path) -> Result<ArchiveFormat> {
+	let header = read_header(File::open(archive_path)?)?;
+
+	let compressed_format = if header.starts_with(b"\x1F\x8B") {
+		Some(ArchiveFormat::TarGz)
+	} else if header.starts_with(b"\xFD7zXZ\x00") {
+		Some(ArchiveFormat::TarXz)
+	} else {
+		None
+	};
+
+	if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+		Ok(ArchiveFormat::Zip)
+	} else if header.starts_with(b"7z\xBC\xAF\x27\x1C") {
+		Ok(ArchiveFormat::SevenZip)
+	} else if header.starts_with(b"Rar!\x1A\x07") {
+		Err(unsupported_format_error(
+			archive_path,
+			"RAR archives aren't supported",
+		))
+	} else if let Some(format) = compressed_format {
+		// Compressed files can have anything inside, so we check that it's really a tar.
+		if is_tar(&read_header(open_decompressed(archive_path, format)?)?) {
+			Ok(format)
+		} else {
+			Err(unsupported_format_error(
+				archive_path,
+				"only compressed tar archives are supported",
+			))
+		}
+	} else if is_tar(&header) {
+		Ok(ArchiveFormat::Tar)
+	} else if header.starts_with(b"MZ") || has_single_file_extension(archive_path) {
+		Ok(ArchiveFormat::SingleFile)
+	} else {
+		Err(unsupported_format_error(
+			archive_path,
+			"not an archive, dll or executable",
+		))
+	}
+}
+
+fn unsafe_entry_error(archive_path: &Path, entry_name: &str, reason: &str) -> Error {
+	Error::ArchiveEntryUnsafe(
+		entry_name.to_string(),
+		archive_path.to_path_buf(),
+		reason.to_string(),
+	)
+}
+
+// Rejects absolute paths and paths that would escape the target folder (zip-slip).
+fn get_enclosed_path(archive_path: &Path, entry_name: &str) -> Result<PathBuf> {
+	let mut enclosed_path = PathBuf::new();
+
+	for component in Path::new(entry_name).components() {
+		match component {
+			Component::Normal(part) => enclosed_path.push(part),
+			Component::CurDir => {}
+			Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+				return Err(unsafe_entry_error(
+					archive_path,
+					entry_name,
+					"path points outside of the target folder",
+				));
+			}
+		}
+	}
+
+	Ok(enclosed_path)
+}
+
+fn check_file_count(archive_path: &Path, file_count: usize) -> Result {
+	if file_count > MAX_FILE_COUNT {
+		return Err(Error::ArchiveTooLarge(
+			archive_path.to_path_buf(),
+			format!("{file_count} files, the limit is {MAX_FILE_COUNT}"),
+		));
+	}
+
+	Ok(())
+}
+
+fn add_to_total_size(archive_path: &Path, total_size: &mut u64, size: u64) -> Result {
+	*total_size = total_size.saturating_add(size);
+
+	if *total_size > MAX_TOTAL_SIZE {
+		return Err(Error::ArchiveTooLarge(
+			archive_path.to_path_buf(),
+			format!("over {MAX_TOTAL_SIZE} bytes uncompressed"),
+		));
+	}
+
+	Ok(())
+}
+
+// The sizes in the archive headers can lie, so we also count the bytes as they're written.
+fn write_entry(
+	archive_path: &Path,
+	reader: impl Read,
+	output_path: &Path,
+	unix_mode: Option<u32>,
+	total_size: &mut u64,
+) -> Result {
+	fs::create_dir_all(paths::path_parent(output_path)?)?;
+
+	let remaining_size = MAX_TOTAL_SIZE - *total_size;
+	let written_size = io::copy(
+		&mut reader.take(remaining_size + 1),
+		&mut File::create(output_path)?,
+	)?;
+	add_to_total_size(archive_path, total_size, written_size)?;
+
+	// Keeps executables executable.
+	#[cfg(unix)]
+	if let Some(mode) = unix_mode {
+		use std::os::unix::fs::PermissionsExt;
+		fs::set_permissions(output_path, fs::Permissions::from_mode(mode & 0o777))?;
+	}
+
+	#[cfg(not(unix))]
+	let _ = unix_mode;
+
+	Ok(())
+}
+
+fn get_zip_entry(archive_path: &Path, file: &ZipFile) -> Result<ArchiveEntry> {
+	if file.is_symlink() {
+		return Err(unsafe_entry_error(
+			archive_path,
+			file.name(),
+			"symlinks aren't allowed",
+		));
+	}
+
+	let path = file.enclosed_name().ok_or_else(|| {
+		unsafe_entry_error(
+			archive_path,
+			file.name(),
+			"path points outside of the target folder",
+		)
+	})?;
+
+	Ok(ArchiveEntry {
+		path,
+		size: file.size(),
+		is_dir: file.is_dir(),
+	})
+}
+
+fn open_zip(archive_path: &Path) -> Result<ZipArchive<File>> {
+	let archive = ZipArchive::new(File::open(archive_path)?)?;
+	check_file_count(archive_path, archive.len())?;
+
+	Ok(archive)
+}
+
+fn list_zip(archive_path: &Path) -> Result<Vec<ArchiveEntry>> {
+	let mut archive = open_zip(archive_path)?;
+
+	(0..archive.len())
+		.map(|index| get_zip_entry(archive_path, &archive.by_index(index)?))
+		.collect()
+}
+
+fn extract_zip(archive_path: &Path, target_path: &Path, entries: &[ArchiveEntry]) -> Result {
+	let mut archive = open_zip(archive_path)?;
+	let mut total_size = 0;
+
+	for (index, entry) in entries.iter().enumerate() {
+		let output_path = target_path.join(&entry.path);
+
+		if entry.is_dir {
+			fs::create_dir_all(&output_path)?;
+			continue;
+		}
+
+		let file = archive.by_index(index)?;
+		let unix_mode = file.unix_mode();
+		write_entry(archive_path, file, &output_path, unix_mode, &mut total_size)?;
+	}
+
+	Ok(())
+}
+
+fn open_decompressed(archive_path: &Path, format: ArchiveFormat) -> Result<Box<dyn Read>> {
+	let file = File::open(archive_path)?;
+
+	Ok(match format {
+		ArchiveFormat::TarGz => Box::new(GzDecoder::new(file)),
+		ArchiveFormat::TarXz => Box::new(XzDecoder::new(file)),
+		_ => Box::new(file),
+	})
+}
+
+fn open_tar(archive_path: &Path, format: ArchiveFormat) -> Result<tar::Archive<Box<dyn Read>>> {
+	Ok(tar::Archive::new(open_decompressed(archive_path, format)?))
+}
+
+// Global headers only hold metadata for the whole archive, they don't get extracted.
+fn get_tar_entries(
+	archive: &mut tar::Archive<Box<dyn Read>>,
+) -> Result<impl Iterator<Item = io::Result<tar::Entry<'_, Box<dyn Read>>>>> {
+	Ok(archive.entries()?.filter(|tar_entry| {
+		tar_entry.as_ref().map_or(true, |tar_entry| {
+			tar_entry.header().entry_type() != EntryType::XGlobalHeader
+		})
+	}))
+}
+
+fn get_tar_entry(archive_path: &Path, entry: &tar::Entry<impl Read>) -> Result<ArchiveEntry> {
+	let entry_path = entry.path()?;
+	let entry_name = entry_path.to_string_lossy();
+	let entry_type = entry.header().entry_type();
+
+	if !matches!(entry_type, EntryType::Regular | EntryType::Directory) {
+		return Err(unsafe_entry_error(
+			archive_path,
+			&entry_name,
+			"only regular files and folders are allowed",
+		));
+	}
+
+	Ok(ArchiveEntry {
+		path: get_enclosed_path(archive_path, &entry_name)?,
+		size: entry.size(),
+		is_dir: entry_type == EntryType::Directory,
+	})
+}
+
+fn list_tar(archive_path: &Path, format: ArchiveFormat) -> Result<Vec<ArchiveEntry>> {
+	let mut archive = open_tar(archive_path, format)?;
+	let mut entries = Vec::new();
+
+	for tar_entry in get_tar_entries(&mut archive)? {
+		entries.push(get_tar_entry(archive_path, &tar_entry?)?);
+		check_file_count(archive_path, entries.len())?;
+	}
+
+	Ok(entries)
+}
+
+fn extract_tar(
+	archive_path: &Path,
+	format: ArchiveFormat,
+	target_path: &Path,
+	entries: &[ArchiveEntry],
+) -> Result {
+	let mut archive = open_tar(archive_path, format)?;
+	let mut total_size = 0;
+
+	// Tar archives can only be read in order, so we go through them again,
+	// relying on the entries being in the same order as when we listed them.
+	for (tar_entry, entry) in get_tar_entries(&mut archive)?.zip(entries) {
+		let tar_entry = tar_entry?;
+		let output_path = target_path.join(&entry.path);
+
+		if entry.is_dir {
+			fs::create_dir_all(&output_path)?;
+			continue;
+		}
+
+		let unix_mode = tar_entry.header().mode().ok();
+		write_entry(
+			archive_path,
+			tar_entry,
+			&output_path,
+			unix_mode,
+			&mut total_size,
+		)?;
+	}
+
+	Ok(())
+}
+
+const fn get_seven_zip_unix_mode(entry: &SevenZArchiveEntry) -> Option<u32> {
+	if entry.has_windows_attributes && entry.windows_attributes & SEVEN_ZIP_UNIX_EXTENSION != 0 {
+		Some(entry.windows_attributes >> 16)
+	} else {
+		None
+	}
+}
+
+fn get_seven_zip_entry(archive_path: &Path, entry: &SevenZArchiveEntry) -> Result<ArchiveEntry> {
+	let is_symlink = get_seven_zip_unix_mode(entry)
+		.is_some_and(|mode| mode & UNIX_FILE_TYPE_MASK == UNIX_SYMLINK)
+		|| (entry.has_windows_attributes
+			&& entry.windows_attributes & SEVEN_ZIP_REPARSE_POINT != 0);
+
+	if is_symlink {
+		return Err(unsafe_entry_error(
+			archive_path,
+			&entry.name,
+			"symlinks aren't allowed",
+		));
+	}
+
+	Ok(ArchiveEntry {
+		path: get_enclosed_path(archive_path, &entry.name)?,
+		size: entry.size,
+		is_dir: entry.is_directory,
+	})
+}
+
+fn list_seven_zip(archive_path: &Path) -> Result<Vec<ArchiveEntry>> {
+	let reader = SevenZReader::open(archive_path, Password::empty())?;
+	let files = &reader.archive().files;
+	check_file_count(archive_path, files.len())?;
+
+	files
+		.iter()
+		// Anti-items mark files for deletion in incremental backups, they have no contents.
+		.filter(|entry| !entry.is_anti_item)
+		.map(|entry| get_seven_zip_entry(archive_path, entry))
+		.collect()
+}
+
+fn extract_seven_zip(archive_path: &Path, target_path: &Path) -> Result {
+	let mut reader = SevenZReader::open(archive_path, Password::empty())?;
+	let mut total_size = 0;
+	let mut result = Ok(());
+
+	// 7z entries come in whatever order the solid blocks were packed in,
+	// so we validate each one again instead of matching it to the listing.
+	reader.for_each_entries(|seven_zip_entry, entry_reader| {
+		if seven_zip_entry.is_anti_item {
+			return Ok(true);
+		}
+
+		result = get_seven_zip_entry(archive_path, seven_zip_entry).and_then(|entry| {
+			let output_path = target_path.join(&entry.path);
+
+			if entry.is_dir {
+				Ok(fs::create_dir_all(&output_path)?)
+			} else {
+				write_entry(
+					archive_path,
+					entry_reader,
+					&output_path,
+					get_seven_zip_unix_mode(seven_zip_entry),
+					&mut total_size,
+				)
+			}
+		});
+
+		Ok(result.is_ok())
+	})?;
+
+	result
+}
+
+fn get_single_file_entry(archive_path: &Path) -> Result<ArchiveEntry> {
+	Ok(ArchiveEntry {
+		path: PathBuf::from(
+			archive_path
+				.file_name()
+				.ok_or_else(|| Error::PathParseFailure(archive_path.to_path_buf()))?,
+		),
+		size: fs::metadata(archive_path)?.len(),
+		is_dir: false,
+	})
+}
+
+// Dry run of `extract`: validates every entry and returns what would be written, without writing anything.
+// Files that aren't archives are listed as a single entry named like the file itself.
+pub fn list(archive_path: &Path) -> Result<Vec<ArchiveEntry>> {
+	let format = detect_format(archive_path)?;
+
+	let entries = match format {
+		ArchiveFormat::Zip => list_zip(archive_path)?,
+		ArchiveFormat::SevenZip => list_seven_zip(archive_path)?,
+		ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarXz => {
+			list_tar(archive_path, format)?
+		}
+		ArchiveFormat::SingleFile => vec![get_single_file_entry(archive_path)?],
+	};
+
+	let mut total_size = 0;
+	for entry in &entries {
+		add_to_total_size(archive_path, &mut total_size, entry.size)?;
+	}
+
+	Ok(entries)
+}
+
+// Every entry is validated before anything gets written,
+// so a bad archive doesn't leave half its contents behind.
+pub fn extract(archive_path: &Path, target_path: &Path) -> Result<Vec<ArchiveEntry>> {
+	let format = detect_format(archive_path)?;
+	let entries = list(archive_path)?;
+
+	match format {
+		ArchiveFormat::Zip => extract_zip(archive_path, target_path, &entries)?,
+		ArchiveFormat::SevenZip => extract_seven_zip(archive_path, target_path)?,
+		ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::TarXz => {
+			extract_tar(archive_path, format, target_path, &entries)?;
+		}
+		ArchiveFormat::SingleFile => {
+			let entry = get_single_file_entry(archive_path)?;
+			fs::create_dir_all(target_path)?;
+			fs::copy(archive_path, target_path.join(entry.path))?;
+		}
+	}
+
+	Ok(entries)
+}
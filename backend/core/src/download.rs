@@ -0,0 +1,227 @@
+use std::{
+	collections::{HashMap, HashSet},
+	ffi::OsString,
+	fs::{self, OpenOptions},
+	future::{Future, poll_fn},
+	io::Write,
+	path::{Path, PathBuf},
+	pin::pin,
+	sync::Mutex,
+	task::{Poll, Waker},
+	time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use log::error;
+use rai_pal_proc_macros::serializable_struct;
+use reqwest::{StatusCode, header};
+use sha2::{Digest, Sha256};
+
+use crate::{
+	paths,
+	result::{Error, Result},
+};
+
+// Progress is reported at most this often, so we don't flood the frontend with events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+lazy_static! {
+	static ref CANCELLED_DOWNLOADS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+	// Downloads waiting on the network, which need to be woken up when they get cancelled.
+	static ref DOWNLOAD_WAKERS: Mutex<HashMap<String, Waker>> = Mutex::new(HashMap::new());
+}
+
+#[serializable_struct]
+pub struct DownloadProgress {
+	pub id: String,
+	pub downloaded_bytes: u64,
+	pub total_bytes: Option<u64>,
+}
+
+// The download stops right away, even if it's still waiting for the server.
+// The partial file is kept, so the download can be resumed later.
+pub fn cancel(id: &str) {
+	match CANCELLED_DOWNLOADS.lock() {
+		Ok(mut cancelled_downloads) => {
+			cancelled_downloads.insert(id.to_string());
+		}
+		Err(err) => error!("Failed to cancel download `{id}`: {err}"),
+	}
+
+	if let Some(waker) = DOWNLOAD_WAKERS
+		.lock()
+		.ok()
+		.and_then(|mut download_wakers| download_wakers.remove(id))
+	{
+		waker.wake();
+	}
+}
+
+fn take_cancelled(id: &str) -> bool {
+	CANCELLED_DOWNLOADS
+		.lock()
+		.is_ok_and(|mut cancelled_downloads| cancelled_downloads.remove(id))
+}
+
+fn forget_waker(id: &str) {
+	if let Ok(mut download_wakers) = DOWNLOAD_WAKERS.lock() {
+		download_wakers.remove(id);
+	}
+}
+
+// Waits for the given network future, but gives up as soon as the download is cancelled.
+async fn until_cancelled<T, F>(id: &str, future: F) -> Result<T>
+where
+	F: Future<Output = reqwest::Result<T>>,
+{
+	let mut future = pin!(future);
+
+	let result = poll_fn(|context| {
+		// The waker goes in before checking for cancellations, so we can't miss one that happens in between.
+		if let Ok(mut download_wakers) = DOWNLOAD_WAKERS.lock() {
+			download_wakers.insert(id.to_string(), context.waker().clone());
+		}
+
+		if take_cancelled(id) {
+			return Poll::Ready(Err(Error::DownloadCancelled(id.to_string())));
+		}
+
+		future.as_mut().poll(context).map_err(Error::from)
+	})
+	.await;
+
+	forget_waker(id);
+
+	result
+}
+
+// Partial downloads are named after the URL they came from,
+// so we never resume a download using bytes from a different file.
+// The hash needs to stay the same between Rai Pal versions, or we'd lose partial downloads after updating.
+fn get_partial_path(target_path: &Path, url: &str) -> Result<PathBuf> {
+	let url_hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+
+	let mut file_name = OsString::from(
+		target_path
+			.file_name()
+			.ok_or_else(|| Error::PathParseFailure(target_path.to_path_buf()))?,
+	);
+	// Only part of the hash, to keep file names short. Plenty to tell a few URLs apart.
+	file_name.push(format!(".{}.part", &url_hash[..16]));
+
+	Ok(target_path.with_file_name(file_name))
+}
+
+fn remove_stale_partial_files(target_path: &Path, partial_path: &Path) -> Result {
+	let file_name = target_path
+		.file_name()
+		.ok_or_else(|| Error::PathParseFailure(target_path.to_path_buf()))?
+		.to_string_lossy();
+	let parent = paths::path_parent(target_path)?;
+
+	for stale_path in paths::glob_path(&parent.join(format!("{file_name}.*.part"))) {
+		if stale_path != partial_path {
+			fs::remove_file(stale_path)?;
+		}
+	}
+
+	Ok(())
+}
+
+// Used to name the downloaded file, which matters for downloads that aren't archives (like a lone dll).
+pub fn get_url_file_name(url: &str) -> String {
+	reqwest::Url::parse(url)
+		.ok()
+		.and_then(|parsed_url| {
+			parsed_url
+				.path_segments()?
+				.filter(|segment| !segment.is_empty())
+				.next_back()
+				.map(ToString::to_string)
+		})
+		.filter(|file_name| {
+			Path::new(file_name).file_name() == Some(OsString::from(file_name).as_os_str())
+		})
+		.unwrap_or_else(|| "download".to_string())
+}
+
+async fn request(id: &str, url: &str, resume_from: u64) -> Result<reqwest::Response> {
+	let client = reqwest::Client::new();
+	let mut request = client.get(url);
+
+	if resume_from > 0 {
+		request = request.header(header::RANGE, format!("bytes={resume_from}-"));
+	}
+
+	until_cancelled(id, request.send()).await
+}
+
+// Streams the file to disk, resuming from a previous partial download if there is one.
+// The target path only gets written once the whole file has been downloaded.
+pub async fn download_file<F>(id: &str, url: &str, target_path: &Path, on_progress: F) -> Result
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+{
+	let partial_path = get_partial_path(target_path, url)?;
+	remove_stale_partial_files(target_path, &partial_path)?;
+
+	// Forget about any cancellations that happened before this download started.
+	take_cancelled(id);
+
+	let mut downloaded_bytes = fs::metadata(&partial_path).map_or(0, |metadata| metadata.len());
+	let mut response = request(id, url, downloaded_bytes).await?;
+
+	if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+		// The partial file is probably bigger than it should be, so we start over.
+		downloaded_bytes = 0;
+		response = request(id, url, downloaded_bytes).await?;
+	}
+
+	let mut response = response.error_for_status()?;
+
+	let is_resuming = response.status() == StatusCode::PARTIAL_CONTENT;
+	if !is_resuming {
+		// Server doesn't support ranges (or we didn't ask for one), so we get the whole file.
+		downloaded_bytes = 0;
+	}
+
+	let total_bytes = response
+		.content_length()
+		.map(|content_length| content_length + downloaded_bytes);
+
+	let mut file = OpenOptions::new()
+		.create(true)
+		.write(true)
+		.append(is_resuming)
+		.truncate(!is_resuming)
+		.open(&partial_path)?;
+
+	let mut last_progress_time = Instant::now();
+
+	while let Some(chunk) = until_cancelled(id, response.chunk()).await? {
+		file.write_all(&chunk)?;
+		downloaded_bytes += u64::try_from(chunk.len())?;
+
+		if last_progress_time.elapsed() >= PROGRESS_INTERVAL {
+			last_progress_time = Instant::now();
+			on_progress(DownloadProgress {
+				id: id.to_string(),
+				downloaded_bytes,
+				total_bytes,
+			});
+		}
+	}
+
+	file.flush()?;
+	drop(file);
+
+	on_progress(DownloadProgress {
+		id: id.to_string(),
+		downloaded_bytes,
+		total_bytes,
+	});
+
+	fs::rename(partial_path, target_path)?;
+
+	Ok(())
+}
@@ -65,6 +65,15 @@ impl Game {
 		}
 	}
 
+	// Lets the installed game know which provider game it belongs to.
+	pub fn link_installed_game(&mut self) -> &mut Self {
+		if let Some(installed_game) = &mut self.installed_game {
+			installed_game.provider_id = Some(self.id.provider_id);
+			installed_game.external_id = Some(self.external_id.clone());
+		}
+		self
+	}
+
 	pub fn add_tag(&mut self, tag: GameTag) -> &mut Self {
 		self.tags.insert(tag);
 		self
@@ -84,7 +84,7 @@ fn get_version(game_exe_path: &Path) -> Option<EngineVersion> {
 	None
 }
 
-fn get_unity_data_path(game_exe_path: &Path) -> Result<PathBuf> {
+pub fn get_unity_data_path(game_exe_path: &Path) -> Result<PathBuf> {
 	let parent = paths::path_parent(game_exe_path)?;
 	let file_stem = paths::file_name_without_extension(game_exe_path)?;
 
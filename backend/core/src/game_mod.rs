@@ -1,8 +1,14 @@
+use std::fmt::{self, Display};
+
 use rai_pal_proc_macros::serializable_struct;
 
-use crate::game_engines::{
-	game_engine::{EngineBrand, EngineVersionNumbers},
-	unity::UnityScriptingBackend,
+use crate::{
+	game_engines::{
+		game_engine::{EngineBrand, EngineVersionNumbers},
+		unity::UnityScriptingBackend,
+	},
+	game_executable::Architecture,
+	mod_version_requirements,
 };
 
 #[serializable_struct]
@@ -17,5 +23,32 @@ pub struct CommonModData {
 	pub engine: Option<EngineBrand>,
 	pub unity_backend: Option<UnityScriptingBackend>,
 	pub engine_version_range: Option<EngineVersionRange>,
+	// For mods with native files that only work on one architecture. Works with any if missing.
+	pub architecture: Option<Architecture>,
 	pub loader_id: String,
 }
+
+#[serializable_struct]
+pub struct ModDependency {
+	pub id: String,
+	// Version requirement, like ">=1.2.0" or "^2". Any version is accepted if missing.
+	pub version: Option<String>,
+}
+
+impl ModDependency {
+	// If we don't know the version, we can only be sure it's satisfied if any version is accepted.
+	pub fn is_satisfied_by(&self, version: Option<&str>) -> bool {
+		version.map_or(self.version.is_none(), |version| {
+			mod_version_requirements::matches_requirement(version, self.version.as_deref())
+		})
+	}
+}
+
+impl Display for ModDependency {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match &self.version {
+			Some(version) => write!(f, "{} ({version})", self.id),
+			None => write!(f, "{}", self.id),
+		}
+	}
+}
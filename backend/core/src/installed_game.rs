@@ -1,5 +1,5 @@
 use std::{
-	collections::HashMap,
+	collections::{HashMap, HashSet},
 	fs::{self},
 	path::{Path, PathBuf},
 };
@@ -9,9 +9,9 @@ use rai_pal_proc_macros::serializable_struct;
 
 use crate::{
 	game_executable::GameExecutable,
-	mod_manifest,
+	mod_manifest, mod_pins,
 	paths::{self, glob_path, hash_path},
-	providers::provider_command::ProviderCommand,
+	providers::{provider::ProviderId, provider_command::ProviderCommand},
 	result::{Error, Result},
 };
 
@@ -20,8 +20,13 @@ pub struct InstalledGame {
 	pub id: String,
 	pub executable: GameExecutable,
 	pub installed_mod_versions: InstalledModVersions,
+	pub disabled_mods: HashSet<String>,
+	pub pinned_mod_versions: InstalledModVersions,
 	pub discriminator: Option<String>,
 	pub start_command: Option<ProviderCommand>,
+	// Copied from the game this belongs to, since mod loaders only get to see the installed game.
+	pub provider_id: Option<ProviderId>,
+	pub external_id: Option<String>,
 }
 
 type InstalledModVersions = HashMap<String, String>;
@@ -56,9 +61,13 @@ impl InstalledGame {
 		let mut installed_game = Self {
 			id: game_id,
 			installed_mod_versions: HashMap::default(),
+			disabled_mods: HashSet::default(),
+			pinned_mod_versions: HashMap::default(),
 			executable: GameExecutable::new(path)?,
 			discriminator: None,
 			start_command: None,
+			provider_id: None,
+			external_id: None,
 		};
 
 		installed_game.refresh_installed_mods();
@@ -93,6 +102,8 @@ impl InstalledGame {
 
 	pub fn refresh_installed_mods(&mut self) {
 		self.installed_mod_versions = self.get_available_mods();
+		self.disabled_mods = self.get_disabled_mods();
+		self.pinned_mod_versions = mod_pins::read(self).versions;
 	}
 
 	pub fn open_game_folder(&self) -> Result {
@@ -149,6 +160,30 @@ impl InstalledGame {
 			.collect()
 	}
 
+	// Disabled mods are still installed, but their files are parked outside of the mod loader folders.
+	pub fn get_disabled_mods(&self) -> HashSet<String> {
+		match self.get_parked_mods_folder() {
+			Ok(parked_mods_folder) => {
+				if !parked_mods_folder.is_dir() {
+					return HashSet::default();
+				}
+				glob_path(&parked_mods_folder.join("*"))
+					.iter()
+					.filter_map(|parked_mod_path| {
+						Some(parked_mod_path.file_name()?.to_str()?.to_string())
+					})
+					.collect()
+			}
+			Err(err) => {
+				error!(
+					"Failed to get parked mods folder for game {}. Error: {}",
+					self.id, err
+				);
+				HashSet::default()
+			}
+		}
+	}
+
 	pub fn get_installed_mod_manifest_path(&self, mod_id: &str) -> Result<PathBuf> {
 		Ok(self
 			.get_installed_mods_folder()?
@@ -156,6 +191,10 @@ impl InstalledGame {
 			.join(format!("{mod_id}.json")))
 	}
 
+	pub fn get_parked_mods_folder(&self) -> Result<PathBuf> {
+		Ok(self.get_installed_mods_folder()?.join("parked"))
+	}
+
 	pub fn get_installed_mods_folder(&self) -> Result<PathBuf> {
 		let installed_mods_folder = paths::app_data_path()?
 			.join("installed-mods")
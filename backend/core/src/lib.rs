@@ -1,7 +1,9 @@
 #![allow(async_fn_in_trait)]
 
 pub mod analytics;
+pub mod archive;
 pub mod debug;
+pub mod download;
 pub mod files;
 pub mod game;
 pub mod game_engines;
@@ -14,15 +16,27 @@ pub mod games_query;
 pub mod installed_game;
 pub mod local_mod;
 pub mod maps;
+pub mod mod_backups;
+pub mod mod_compatibility;
 pub mod mod_loaders;
 pub mod mod_manifest;
+pub mod mod_pins;
+pub mod mod_presets;
+pub mod mod_profiles;
+pub mod mod_updates;
+pub mod mod_version_requirements;
+pub mod mod_versions;
 pub mod paths;
 pub mod providers;
+pub mod remote_database;
 pub mod remote_game;
 pub mod remote_mod;
 pub mod result;
+pub mod session_diagnostics;
 pub mod string_includes;
+pub mod timestamps;
 pub mod windows;
+pub mod wine;
 
 #[cfg(test)]
 mod tests;
@@ -7,7 +7,8 @@ use rai_pal_proc_macros::{serializable_enum, serializable_struct};
 
 use crate::{
 	game_engines::{game_engine::EngineBrand, unity::UnityScriptingBackend},
-	game_mod::CommonModData,
+	game_mod::{CommonModData, ModDependency},
+	mod_loaders::bepinex_builds::BepInExBuild,
 	mod_manifest::{self, Manifest},
 	paths::{self, open_folder_or_parent},
 	result::Result,
@@ -52,6 +53,7 @@ impl LocalMod {
 					.as_ref()
 					.and_then(|m| m.engine_version_range.clone()),
 				unity_backend,
+				architecture: manifest.as_ref().and_then(|m| m.architecture),
 				loader_id: loader_id.to_string(),
 			},
 			data: LocalModData {
@@ -61,6 +63,38 @@ impl LocalMod {
 		})
 	}
 
+	pub fn get_version(&self) -> Option<&str> {
+		self.data
+			.manifest
+			.as_ref()
+			.map(|manifest| manifest.version.as_str())
+	}
+
+	pub fn get_dependencies(&self) -> &[ModDependency] {
+		self.data
+			.manifest
+			.as_ref()
+			.and_then(|manifest| manifest.dependencies.as_deref())
+			.unwrap_or_default()
+	}
+
+	pub fn get_incompatibilities(&self) -> &[ModDependency] {
+		self.data
+			.manifest
+			.as_ref()
+			.and_then(|manifest| manifest.incompatibilities.as_deref())
+			.unwrap_or_default()
+	}
+
+	// Empty means the mod works with any BepInEx build.
+	pub fn get_bepinex_builds(&self) -> &[BepInExBuild] {
+		self.data
+			.manifest
+			.as_ref()
+			.and_then(|manifest| manifest.bepinex_builds.as_deref())
+			.unwrap_or_default()
+	}
+
 	pub fn open_folder(&self) -> Result {
 		open_folder_or_parent(&self.data.path)
 	}
@@ -0,0 +1,170 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use log::{error, warn};
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	files,
+	installed_game::InstalledGame,
+	mod_loaders::mod_loader::{self, ModLoaderActions},
+	paths,
+	result::Result,
+};
+
+const GAME_FILES_FILE_NAME: &str = "game-files.json";
+
+// A file or folder mod loaders write to the game folder, and whether it was there before the backup.
+#[serializable_struct]
+struct GameFileBackup {
+	path: PathBuf,
+	existed: bool,
+}
+
+fn get_path(game: &InstalledGame) -> Result<PathBuf> {
+	Ok(paths::app_data_path()?.join("mod-backups").join(&game.id))
+}
+
+fn get_game_file_backup_path(backup_path: &Path, index: usize) -> PathBuf {
+	backup_path.join("game-files").join(index.to_string())
+}
+
+// Anything mod loaders would write outside of the installed mods folder, like proxy dlls and doorstop_config.ini.
+fn get_game_file_paths(
+	game: &InstalledGame,
+	mod_loaders: &mod_loader::Map,
+) -> Result<Vec<PathBuf>> {
+	let installed_mods_folder = game.get_installed_mods_folder()?;
+
+	let mut game_file_paths: Vec<PathBuf> = mod_loaders
+		.values()
+		.flat_map(|mod_loader| {
+			mod_loader.get_install_preview(game).unwrap_or_else(|err| {
+				warn!(
+					"Failed to get install preview for mod loader `{}`, so its game files won't be backed up: {err}",
+					mod_loader.get_data().id
+				);
+				Vec::new()
+			})
+		})
+		.map(|entry| entry.path)
+		.filter(|path| !path.starts_with(&installed_mods_folder))
+		.collect();
+
+	game_file_paths.sort();
+	game_file_paths.dedup();
+
+	Ok(game_file_paths)
+}
+
+fn copy_path(source_path: &Path, target_path: &Path) -> Result {
+	if source_path.is_dir() {
+		files::copy_dir_all(source_path, target_path)?;
+	} else {
+		fs::create_dir_all(paths::path_parent(target_path)?)?;
+		fs::copy(source_path, target_path)?;
+	}
+
+	Ok(())
+}
+
+fn remove_path(path: &Path) -> Result {
+	if path.is_dir() {
+		fs::remove_dir_all(path)?;
+	} else if path.exists() {
+		fs::remove_file(path)?;
+	}
+
+	Ok(())
+}
+
+// Copies the game's installed mods, and the game files mod loaders would touch,
+// so a failed batch of changes (like installing a modpack) can be undone.
+// Replaces any previous backup for this game.
+pub fn create(game: &InstalledGame, mod_loaders: &mod_loader::Map) -> Result {
+	let backup_path = get_path(game)?;
+	remove_path(&backup_path)?;
+
+	files::copy_dir_all(game.get_installed_mods_folder()?, backup_path.join("mods"))?;
+
+	let game_files: Vec<GameFileBackup> = get_game_file_paths(game, mod_loaders)?
+		.into_iter()
+		.map(|path| GameFileBackup {
+			existed: path.exists(),
+			path,
+		})
+		.collect();
+
+	for (index, game_file) in game_files.iter().enumerate() {
+		if game_file.existed {
+			copy_path(
+				&game_file.path,
+				&get_game_file_backup_path(&backup_path, index),
+			)?;
+		}
+	}
+
+	fs::write(
+		backup_path.join(GAME_FILES_FILE_NAME),
+		serde_json::to_string_pretty(&game_files)?,
+	)?;
+
+	Ok(())
+}
+
+// Puts everything back the way it was when the backup was created, and removes the backup.
+// Game files that didn't exist back then get deleted.
+pub fn restore(game: &InstalledGame) -> Result {
+	let backup_path = get_path(game)?;
+	let installed_mods_folder = game.get_installed_mods_folder()?;
+
+	let game_files: Vec<GameFileBackup> =
+		serde_json::from_str(&fs::read_to_string(backup_path.join(GAME_FILES_FILE_NAME))?)?;
+
+	for (index, game_file) in game_files.iter().enumerate() {
+		remove_path(&game_file.path)?;
+		if game_file.existed {
+			copy_path(
+				&get_game_file_backup_path(&backup_path, index),
+				&game_file.path,
+			)?;
+		}
+	}
+
+	fs::remove_dir_all(&installed_mods_folder)?;
+	fs::rename(backup_path.join("mods"), &installed_mods_folder)?;
+
+	remove(game)
+}
+
+pub fn remove(game: &InstalledGame) -> Result {
+	remove_path(&get_path(game)?)
+}
+
+// Runs a batch of changes to the game's mods, restoring the backup if anything fails.
+// Restore failures are only logged, since the original error is the one worth showing.
+pub fn restore_on_error(game: &InstalledGame, result: Result) -> Result {
+	match result {
+		Ok(()) => {
+			if let Err(err) = remove(game) {
+				error!("Failed to remove mod backup for game {}: {err}", game.id);
+			}
+			Ok(())
+		}
+		Err(err) => {
+			error!(
+				"Failed to change mods for game {}, restoring backup. Error: {err}",
+				game.id
+			);
+			if let Err(restore_err) = restore(game) {
+				error!(
+					"Failed to restore mod backup for game {}: {restore_err}",
+					game.id
+				);
+			}
+			Err(err)
+		}
+	}
+}
@@ -0,0 +1,325 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use rai_pal_proc_macros::{serializable_enum, serializable_struct};
+
+use crate::{
+	game::Game,
+	game_engines::{
+		game_engine::{EngineVersionNumbers, GameEngine},
+		unity::UnityScriptingBackend,
+	},
+	game_executable::Architecture,
+	game_mod::CommonModData,
+	installed_game::InstalledGame,
+	local_mod, remote_mod,
+};
+
+#[serializable_enum]
+pub enum CompatibilityIssue {
+	EngineUnknown,
+	EngineMismatch,
+	EngineVersionUnknown,
+	EngineVersionTooOld,
+	EngineVersionTooNew,
+	UnityBackendUnknown,
+	UnityBackendMismatch,
+	ArchitectureUnknown,
+	ArchitectureMismatch,
+}
+
+#[serializable_enum]
+pub enum CompatibilityVerdict {
+	Compatible,
+	// We don't know enough about the game to be sure, but nothing says it's incompatible.
+	Unknown,
+	Incompatible,
+}
+
+#[serializable_struct]
+pub struct ModCompatibility {
+	pub verdict: CompatibilityVerdict,
+	pub issues: Vec<CompatibilityIssue>,
+}
+
+impl CompatibilityIssue {
+	// Blocking issues are the ones where we know for sure the mod won't work.
+	// The others only mean that some of the game's information is missing.
+	pub const fn is_blocking(self) -> bool {
+		matches!(
+			self,
+			Self::EngineMismatch
+				| Self::EngineVersionTooOld
+				| Self::EngineVersionTooNew
+				| Self::UnityBackendMismatch
+				| Self::ArchitectureMismatch
+		)
+	}
+}
+
+impl ModCompatibility {
+	fn from_issues(issues: Vec<CompatibilityIssue>) -> Self {
+		let verdict = if issues.iter().any(|issue| issue.is_blocking()) {
+			CompatibilityVerdict::Incompatible
+		} else if issues.is_empty() {
+			CompatibilityVerdict::Compatible
+		} else {
+			CompatibilityVerdict::Unknown
+		};
+
+		Self { verdict, issues }
+	}
+
+	pub fn is_incompatible(&self) -> bool {
+		self.verdict == CompatibilityVerdict::Incompatible
+	}
+}
+
+// Version range bounds can leave out the minor and patch numbers (like "Unity 5"),
+// in which case we only compare the parts that are present in the bound.
+fn compare_to_bound(version: &EngineVersionNumbers, bound: &EngineVersionNumbers) -> Ordering {
+	version
+		.major
+		.cmp(&bound.major)
+		.then_with(|| match (version.minor, bound.minor) {
+			(Some(minor), Some(bound_minor)) => minor.cmp(&bound_minor),
+			_ => Ordering::Equal,
+		})
+		.then_with(|| match (version.patch, bound.patch) {
+			(Some(patch), Some(bound_patch)) if version.minor == bound.minor => {
+				patch.cmp(&bound_patch)
+			}
+			_ => Ordering::Equal,
+		})
+}
+
+pub struct CompatibilityTarget<'a> {
+	pub engine: Option<&'a GameEngine>,
+	pub unity_backend: Option<UnityScriptingBackend>,
+	pub architecture: Option<Architecture>,
+}
+
+impl<'a> From<&'a InstalledGame> for CompatibilityTarget<'a> {
+	fn from(game: &'a InstalledGame) -> Self {
+		Self {
+			engine: game.executable.engine.as_ref(),
+			unity_backend: game.executable.scripting_backend,
+			architecture: game.executable.architecture,
+		}
+	}
+}
+
+// Games that aren't installed can still be evaluated using the remote game database,
+// but then we only know the engine.
+impl<'a> From<&'a Game> for CompatibilityTarget<'a> {
+	fn from(game: &'a Game) -> Self {
+		Self {
+			engine: game.get_engine(),
+			unity_backend: game
+				.installed_game
+				.as_ref()
+				.and_then(|installed_game| installed_game.executable.scripting_backend),
+			architecture: game
+				.installed_game
+				.as_ref()
+				.and_then(|installed_game| installed_game.executable.architecture),
+		}
+	}
+}
+
+pub fn evaluate_target(mod_data: &CommonModData, target: &CompatibilityTarget) -> ModCompatibility {
+	let mut issues = Vec::new();
+
+	if let Some(mod_engine) = mod_data.engine {
+		match target.engine {
+			Some(engine) if engine.brand != mod_engine => {
+				issues.push(CompatibilityIssue::EngineMismatch);
+			}
+			Some(engine) => {
+				if let Some(range) = &mod_data.engine_version_range {
+					match &engine.version {
+						Some(version) => {
+							if range.minimum.as_ref().is_some_and(|minimum| {
+								compare_to_bound(&version.numbers, minimum) == Ordering::Less
+							}) {
+								issues.push(CompatibilityIssue::EngineVersionTooOld);
+							}
+
+							if range.maximum.as_ref().is_some_and(|maximum| {
+								compare_to_bound(&version.numbers, maximum) == Ordering::Greater
+							}) {
+								issues.push(CompatibilityIssue::EngineVersionTooNew);
+							}
+						}
+						None => issues.push(CompatibilityIssue::EngineVersionUnknown),
+					}
+				}
+			}
+			None => issues.push(CompatibilityIssue::EngineUnknown),
+		}
+	}
+
+	if let Some(mod_unity_backend) = mod_data.unity_backend {
+		match target.unity_backend {
+			Some(unity_backend) if unity_backend != mod_unity_backend => {
+				issues.push(CompatibilityIssue::UnityBackendMismatch);
+			}
+			Some(_) => {}
+			None => issues.push(CompatibilityIssue::UnityBackendUnknown),
+		}
+
+		// Mods that depend on a scripting backend get installed with a loader that's built per architecture.
+		if mod_data.architecture.is_none() && target.architecture.is_none() {
+			issues.push(CompatibilityIssue::ArchitectureUnknown);
+		}
+	}
+
+	if let Some(mod_architecture) = mod_data.architecture {
+		match target.architecture {
+			Some(architecture) if architecture != mod_architecture => {
+				issues.push(CompatibilityIssue::ArchitectureMismatch);
+			}
+			Some(_) => {}
+			None => issues.push(CompatibilityIssue::ArchitectureUnknown),
+		}
+	}
+
+	ModCompatibility::from_issues(issues)
+}
+
+pub fn evaluate(mod_data: &CommonModData, game: &InstalledGame) -> ModCompatibility {
+	evaluate_target(mod_data, &CompatibilityTarget::from(game))
+}
+
+#[serializable_enum]
+pub enum ModExclusionReason {
+	Incompatible,
+	Deprecated,
+}
+
+#[serializable_struct]
+pub struct CompatibleMod {
+	pub mod_id: String,
+	pub compatibility: ModCompatibility,
+	pub is_downloaded: bool,
+	pub is_installed: bool,
+}
+
+#[serializable_struct]
+pub struct ExcludedMod {
+	pub mod_id: String,
+	pub reason: ModExclusionReason,
+	pub issues: Vec<CompatibilityIssue>,
+}
+
+#[serializable_struct]
+pub struct CompatibleMods {
+	// Sorted from best to worst match.
+	pub compatible: Vec<CompatibleMod>,
+	pub excluded: Vec<ExcludedMod>,
+}
+
+// Mods that declare more about what they need are more likely to be made for this specific game.
+fn get_specificity(mod_data: &CommonModData) -> usize {
+	[
+		mod_data.engine.is_some(),
+		mod_data.unity_backend.is_some(),
+		mod_data.engine_version_range.is_some(),
+		mod_data.architecture.is_some(),
+	]
+	.into_iter()
+	.filter(|is_specified| *is_specified)
+	.count()
+}
+
+const fn get_verdict_rank(verdict: CompatibilityVerdict) -> u8 {
+	match verdict {
+		CompatibilityVerdict::Compatible => 0,
+		CompatibilityVerdict::Unknown => 1,
+		CompatibilityVerdict::Incompatible => 2,
+	}
+}
+
+pub fn get_compatible_mods(
+	game: &Game,
+	local_mods: &local_mod::Map,
+	remote_mods: &remote_mod::Map,
+) -> CompatibleMods {
+	let target = CompatibilityTarget::from(game);
+	let installed_mod_versions = game
+		.installed_game
+		.as_ref()
+		.map(|installed_game| &installed_game.installed_mod_versions);
+
+	let mod_ids: HashSet<&String> = local_mods.keys().chain(remote_mods.keys()).collect();
+
+	let mut compatible = Vec::new();
+	let mut excluded = Vec::new();
+	let mut specificities = Vec::new();
+
+	for mod_id in mod_ids {
+		let local_mod = local_mods.get(mod_id);
+		let remote_mod = remote_mods.get(mod_id);
+
+		let Some(mod_data) = local_mod
+			.map(|local_mod| &local_mod.common)
+			.or_else(|| remote_mod.map(|remote_mod| &remote_mod.common))
+		else {
+			continue;
+		};
+
+		let is_installed =
+			installed_mod_versions.is_some_and(|versions| versions.contains_key(mod_id));
+		let compatibility = evaluate_target(mod_data, &target);
+
+		if compatibility.is_incompatible() {
+			excluded.push(ExcludedMod {
+				mod_id: mod_id.clone(),
+				reason: ModExclusionReason::Incompatible,
+				issues: compatibility.issues,
+			});
+			continue;
+		}
+
+		// Deprecated mods are only shown to people who already have them installed.
+		if !is_installed && remote_mod.is_some_and(|remote_mod| remote_mod.data.deprecated) {
+			excluded.push(ExcludedMod {
+				mod_id: mod_id.clone(),
+				reason: ModExclusionReason::Deprecated,
+				issues: compatibility.issues,
+			});
+			continue;
+		}
+
+		specificities.push(get_specificity(mod_data));
+		compatible.push(CompatibleMod {
+			mod_id: mod_id.clone(),
+			compatibility,
+			is_downloaded: local_mod.is_some(),
+			is_installed,
+		});
+	}
+
+	let mut ranked: Vec<_> = compatible.into_iter().zip(specificities).collect();
+	ranked.sort_by(|(mod_a, specificity_a), (mod_b, specificity_b)| {
+		mod_b
+			.is_installed
+			.cmp(&mod_a.is_installed)
+			.then_with(|| {
+				get_verdict_rank(mod_a.compatibility.verdict)
+					.cmp(&get_verdict_rank(mod_b.compatibility.verdict))
+			})
+			.then_with(|| specificity_b.cmp(specificity_a))
+			.then_with(|| mod_b.is_downloaded.cmp(&mod_a.is_downloaded))
+			.then_with(|| mod_a.mod_id.cmp(&mod_b.mod_id))
+	});
+
+	excluded.sort_by(|mod_a, mod_b| mod_a.mod_id.cmp(&mod_b.mod_id));
+
+	CompatibleMods {
+		compatible: ranked
+			.into_iter()
+			.map(|(compatible_mod, _)| compatible_mod)
+			.collect(),
+		excluded,
+	}
+}
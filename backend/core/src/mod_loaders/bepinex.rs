@@ -1,14 +1,21 @@
 use std::{
 	collections::HashMap,
-	fs::{self, File},
+	fs,
 	path::{Path, PathBuf},
 };
 
+use log::info;
+use pelite::pe32::{Pe, PeFile};
 use rai_pal_proc_macros::serializable_struct;
-use zip::ZipArchive;
 
-use super::mod_loader::ModLoaderStatic;
+use super::{
+	bepinex_builds::{self, BepInExBuild, BepInExGameBuild, BuildResources, PayloadChoice},
+	bepinex_config, loader_payloads, mod_integrity,
+	mod_loader::{self, ModLoader, ModLoaderStatic},
+};
 use crate::{
+	archive::{self, ArchiveEntry},
+	download::DownloadProgress,
 	files::copy_dir_all,
 	game_engines::{
 		game_engine::{EngineBrand, GameEngine},
@@ -17,17 +24,104 @@ use crate::{
 	game_mod::CommonModData,
 	installed_game::InstalledGame,
 	local_mod::{LocalMod, ModKind},
+	maps::TryGettable,
 	mod_loaders::mod_loader::{ModLoaderActions, ModLoaderData},
-	paths,
+	mod_manifest, mod_versions, paths,
 	result::{Error, Result},
 };
 
+// Folders inside BepInEx where each mod gets its own subfolder.
+const MOD_FOLDER_NAMES: [&str; 2] = ["plugins", "patchers"];
+
+// Folders BepInEx fills in by itself, with files that only work with the build that made them.
+const GENERATED_FOLDER_NAMES: [&str; 4] = ["core", "interop", "unhollowed", "cache"];
+
+// BepInEx 6 renamed its main assembly.
+const CORE_ASSEMBLY_NAMES: [&str; 2] = ["BepInEx.dll", "BepInEx.Core.dll"];
+
 #[serializable_struct]
 pub struct BepInEx {
 	pub data: ModLoaderData,
 	pub id: &'static str,
 }
 
+#[serializable_struct]
+pub struct BepInExBuildOptions {
+	pub selected: Option<BepInExBuild>,
+	pub deployed: Option<BepInExBuild>,
+	// Builds that can be deployed to the game.
+	pub available: Vec<BepInExBuild>,
+}
+
+pub fn get(mod_loaders: &mod_loader::Map) -> Result<&BepInEx> {
+	match mod_loaders.try_get(BepInEx::ID)? {
+		ModLoader::BepInEx(bepinex) => Ok(bepinex),
+		_ => Err(Error::DataEntryNotFound(BepInEx::ID.to_string())),
+	}
+}
+
+// Mods packaged for manual installs (like Thunderstore ones) often have everything inside a BepInEx folder.
+fn get_mod_content_path(mod_path: &Path) -> PathBuf {
+	let bepinex_path = mod_path.join("BepInEx");
+	if bepinex_path.is_dir() {
+		bepinex_path
+	} else {
+		mod_path.to_path_buf()
+	}
+}
+
+fn copy_root_assemblies(source_path: &Path, target_path: &Path) -> Result {
+	for entry in fs::read_dir(source_path)? {
+		let entry = entry?;
+		let entry_path = entry.path();
+		if entry_path.is_file()
+			&& entry_path
+				.extension()
+				.is_some_and(|extension| extension == "dll")
+		{
+			fs::create_dir_all(target_path)?;
+			fs::copy(&entry_path, target_path.join(entry.file_name()))?;
+		}
+	}
+
+	Ok(())
+}
+
+// Configs are shared between all mods, and users edit them, so we only copy the ones that aren't there yet.
+fn copy_default_configs(source_path: &Path, target_path: &Path) -> Result {
+	fs::create_dir_all(target_path)?;
+
+	for entry in fs::read_dir(source_path)? {
+		let entry = entry?;
+		let entry_path = entry.path();
+		let entry_target_path = target_path.join(entry.file_name());
+		if entry_path.is_dir() {
+			copy_default_configs(&entry_path, &entry_target_path)?;
+		} else if !entry_target_path.exists() {
+			fs::copy(&entry_path, entry_target_path)?;
+		}
+	}
+
+	Ok(())
+}
+
+// .NET assemblies are always 32 bit PE files, even the ones that run in 64 bit games.
+fn get_assembly_version(assembly_path: &Path) -> Option<String> {
+	let assembly_bytes = fs::read(assembly_path).ok()?;
+	let version = PeFile::from_bytes(&assembly_bytes)
+		.ok()?
+		.resources()
+		.ok()?
+		.version_info()
+		.ok()?
+		.fixed()?;
+
+	Some(format!(
+		"{}.{}.{}",
+		version.dwFileVersion.Major, version.dwFileVersion.Minor, version.dwFileVersion.Patch
+	))
+}
+
 impl ModLoaderStatic for BepInEx {
 	const ID: &'static str = "bepinex";
 
@@ -43,24 +137,87 @@ impl ModLoaderStatic for BepInEx {
 	}
 }
 
-impl ModLoaderActions for BepInEx {
-	fn get_data(&self) -> &ModLoaderData {
-		&self.data
+impl BepInEx {
+	fn get_scripting_backend(game: &InstalledGame) -> Result<UnityScriptingBackend> {
+		game.executable.scripting_backend.ok_or_else(|| {
+			Error::ModInstallInfoInsufficient(
+				"scripting_backend".to_string(),
+				game.executable.path.clone(),
+			)
+		})
 	}
 
-	fn install(&self, game: &InstalledGame) -> Result {
-		let scripting_backend_path = &self.data.path.join(
-			game.executable
-				.scripting_backend
-				.ok_or_else(|| {
-					Error::ModInstallInfoInsufficient(
-						"scripting_backend".to_string(),
-						game.executable.path.clone(),
-					)
-				})?
-				.to_string(),
-		);
-		let architecture_path = scripting_backend_path
+	// Games that got BepInEx before we kept track of builds have whatever was bundled at the time.
+	fn get_deployed_build(&self, game: &InstalledGame) -> Option<BepInExBuild> {
+		bepinex_builds::read(game).deployed.or_else(|| {
+			self.get_installed_version(game)?;
+			Some(BepInExBuild::get_default(
+				game.executable.scripting_backend?,
+			))
+		})
+	}
+
+	// The build the user picked wins, then whatever is already deployed, then whatever the mod needs.
+	pub fn get_target_build(
+		&self,
+		game: &InstalledGame,
+		local_mod: Option<&LocalMod>,
+	) -> Result<BepInExBuild> {
+		let scripting_backend = Self::get_scripting_backend(game)?;
+		let mod_builds = local_mod
+			.map(LocalMod::get_bepinex_builds)
+			.unwrap_or_default();
+
+		let build = bepinex_builds::read(game)
+			.selected
+			.or_else(|| self.get_deployed_build(game))
+			.or_else(|| {
+				mod_builds
+					.iter()
+					.copied()
+					.find(|build| build.supports(scripting_backend))
+			})
+			.unwrap_or_else(|| BepInExBuild::get_default(scripting_backend));
+
+		if let Some(local_mod) = local_mod {
+			if !mod_builds.is_empty() && !mod_builds.contains(&build) {
+				return Err(Error::BepInExBuildMismatch(
+					local_mod.common.id.clone(),
+					build.to_string(),
+				));
+			}
+		}
+
+		Ok(build)
+	}
+
+	// Games keep the payload they already have, unless they're getting a build they don't have yet.
+	fn get_payload_choice(&self, game: &InstalledGame, build: BepInExBuild) -> PayloadChoice {
+		if self.get_deployed_build(game) == Some(build) {
+			PayloadChoice::Version(bepinex_builds::read(game).deployed_payload)
+		} else {
+			PayloadChoice::Latest
+		}
+	}
+
+	fn get_build_resources(
+		&self,
+		game: &InstalledGame,
+		build: BepInExBuild,
+		payload_choice: &PayloadChoice,
+	) -> Result<BuildResources> {
+		build.get_resources(
+			&self.data.path,
+			Self::get_scripting_backend(game)?,
+			payload_choice,
+		)
+	}
+
+	fn get_architecture_path(
+		game: &InstalledGame,
+		scripting_backend_path: &Path,
+	) -> Result<PathBuf> {
+		Ok(scripting_backend_path
 			.join(
 				// Hardcoded windows platform since that's all we support for now.
 				"Windows",
@@ -75,31 +232,77 @@ impl ModLoaderActions for BepInEx {
 						)
 					})?
 					.to_string(),
-			);
+			))
+	}
+
+	fn get_config_origin_path(&self, game: &InstalledGame) -> PathBuf {
+		let is_legacy = game.executable.engine.as_ref().is_some_and(is_legacy);
+
+		self.data.path.join("config").join(if is_legacy {
+			"BepInEx-legacy.cfg"
+		} else {
+			"BepInEx.cfg"
+		})
+	}
+
+	// Scripting backend and architecture folders for the build,
+	// making sure everything `install_build` needs is there before anything gets touched.
+	fn get_build_paths(
+		&self,
+		game: &InstalledGame,
+		build: BepInExBuild,
+		payload_choice: &PayloadChoice,
+	) -> Result<(BuildResources, PathBuf)> {
+		let resources = self.get_build_resources(game, build, payload_choice)?;
+		let scripting_backend_path = &resources.path;
+		let architecture_path = Self::get_architecture_path(game, scripting_backend_path)?;
+
+		if let Some(missing_path) = [
+			architecture_path.join("mod-loader.zip"),
+			architecture_path.join("copy-to-game"),
+			scripting_backend_path.join("doorstop_config.ini"),
+			self.get_config_origin_path(game),
+		]
+		.into_iter()
+		.find(|path| !path.exists())
+		{
+			return Err(Error::BepInExBuildIncomplete(
+				build.to_string(),
+				missing_path,
+			));
+		}
+
+		Ok((resources, architecture_path))
+	}
+
+	fn install_build(
+		&self,
+		game: &InstalledGame,
+		build: BepInExBuild,
+		payload_choice: &PayloadChoice,
+	) -> Result {
+		let (resources, architecture_path) = self.get_build_paths(game, build, payload_choice)?;
+		let scripting_backend_path = &resources.path;
 
 		let mod_loader_archive = architecture_path.join("mod-loader.zip");
 		let folder_to_copy_to_game = architecture_path.join("copy-to-game");
 		let game_data_folder = &game.get_installed_mods_folder()?;
 
-		ZipArchive::new(File::open(mod_loader_archive)?)?.extract(game_data_folder)?;
+		archive::extract(&mod_loader_archive, game_data_folder)?;
 
 		let game_folder = paths::path_parent(&game.executable.path)?;
 
 		copy_dir_all(folder_to_copy_to_game, game_folder)?;
 
-		let is_legacy = game.executable.engine.as_ref().is_some_and(is_legacy);
-
-		let config_origin_path = &self.data.path.join("config").join(if is_legacy {
-			"BepInEx-legacy.cfg"
-		} else {
-			"BepInEx.cfg"
-		});
-
 		let config_target_folder = game_data_folder.join("BepInEx").join("config");
 
 		fs::create_dir_all(&config_target_folder)?;
 
-		fs::copy(config_origin_path, config_target_folder.join("BepInEx.cfg"))?;
+		// Users can edit this one, so it's only the default until the game has its own.
+		let config_target_path = config_target_folder.join("BepInEx.cfg");
+		if !config_target_path.exists() {
+			fs::copy(self.get_config_origin_path(game), config_target_path)?;
+		}
 
 		let doorstop_config =
 			fs::read_to_string(scripting_backend_path.join("doorstop_config.ini"))?;
@@ -112,28 +315,263 @@ impl ModLoaderActions for BepInEx {
 			),
 		)?;
 
+		bepinex_builds::write(
+			game,
+			&BepInExGameBuild {
+				deployed: Some(build),
+				deployed_payload: resources.payload_version,
+				..bepinex_builds::read(game)
+			},
+		)
+	}
+
+	pub fn get_build_options(&self, game: &InstalledGame) -> Result<BepInExBuildOptions> {
+		Ok(BepInExBuildOptions {
+			selected: bepinex_builds::read(game).selected,
+			deployed: self.get_deployed_build(game),
+			available: BepInExBuild::get_available(
+				&self.data.path,
+				Self::get_scripting_backend(game)?,
+			),
+		})
+	}
+
+	// Picks the build to use next time BepInEx gets deployed, without touching what's deployed now.
+	pub fn select_build(game: &InstalledGame, build: Option<BepInExBuild>) -> Result {
+		bepinex_builds::write(
+			game,
+			&BepInExGameBuild {
+				selected: build,
+				..bepinex_builds::read(game)
+			},
+		)
+	}
+
+	// Default configs the mod comes with, where `install_mod_inner` copies them to.
+	fn get_deployed_config_paths(
+		&self,
+		game: &InstalledGame,
+		mod_id: &str,
+	) -> Result<Vec<PathBuf>> {
+		let Some(local_mod) = self.get_local_mods()?.remove(mod_id) else {
+			return Ok(Vec::new());
+		};
+		let local_mod = mod_versions::get_pinned_local_mod(game, &local_mod)?;
+		let mod_config_path = get_mod_content_path(&local_mod.data.path).join("config");
+		let config_folder = bepinex_config::get_config_folder(game)?;
+
+		Ok(paths::glob_path(&mod_config_path.join("**").join("*"))
+			.into_iter()
+			.filter(|config_path| config_path.is_file())
+			.filter_map(|config_path| {
+				Some(config_folder.join(config_path.strip_prefix(&mod_config_path).ok()?))
+			})
+			.collect())
+	}
+
+	// Only downloaded builds say which BepInEx version they have, so those are the only ones we can look for.
+	pub fn get_build_with_version(
+		game: &InstalledGame,
+		version: &str,
+	) -> Result<Option<BepInExBuild>> {
+		let scripting_backend = Self::get_scripting_backend(game)?;
+
+		Ok(BepInExBuild::ALL.into_iter().find(|build| {
+			build.supports(scripting_backend)
+				&& loader_payloads::get_downloaded_path(
+					Self::ID,
+					&build.to_string(),
+					scripting_backend,
+					version,
+				)
+				.is_some()
+		}))
+	}
+
+	// Downloads whichever build has this BepInEx version in the loader index, unless we already have one.
+	pub async fn download_version<F>(game: &InstalledGame, version: &str, on_progress: &F) -> Result
+	where
+		F: Fn(DownloadProgress) + Send + Sync,
+	{
+		let scripting_backend = Self::get_scripting_backend(game)?;
+		let builds: Vec<String> = BepInExBuild::ALL
+			.into_iter()
+			.filter(|build| build.supports(scripting_backend))
+			.map(|build| build.to_string())
+			.collect();
+
+		loader_payloads::download_version(
+			Self::ID,
+			&builds,
+			scripting_backend,
+			version,
+			on_progress,
+		)
+		.await
+	}
+
+	// Replaces the deployed BepInEx with the given build, usually with the latest payload we have for it.
+	// Mods and their configs stay where they are, and so do the BepInEx settings.
+	pub fn upgrade(
+		&self,
+		game: &InstalledGame,
+		build: BepInExBuild,
+		payload_choice: &PayloadChoice,
+	) -> Result {
+		// Checked first, since the current build gets deleted before the new one is deployed.
+		self.get_build_paths(game, build, payload_choice)?;
+
+		for manifest_path in game.get_manifest_paths() {
+			let Some(manifest) = mod_manifest::get(&manifest_path) else {
+				continue;
+			};
+			let mod_builds = manifest.bepinex_builds.unwrap_or_default();
+			if !mod_builds.is_empty() && !mod_builds.contains(&build) {
+				return Err(Error::BepInExBuildMismatch(
+					paths::file_name_without_extension(&manifest_path)?.to_string(),
+					build.to_string(),
+				));
+			}
+		}
+
+		// Files from the previous build could clash with the new one, so they go first.
+		if let Some(loader_manifest) = mod_integrity::get_loader_manifest(game, Self::ID)? {
+			for file in loader_manifest.files {
+				if file.path.is_file() {
+					fs::remove_file(file.path)?;
+				}
+			}
+		}
+		let bepinex_folder = game.get_installed_mods_folder()?.join("BepInEx");
+		for folder_name in GENERATED_FOLDER_NAMES {
+			let folder_path = bepinex_folder.join(folder_name);
+			if folder_path.is_dir() {
+				fs::remove_dir_all(folder_path)?;
+			}
+		}
+
+		Self::select_build(game, Some(build))?;
+		self.install_build(game, build, payload_choice)?;
+
+		mod_integrity::write_loader_manifest(self, game)?;
+
+		info!("Deployed BepInEx build {build} to game {}", game.id);
+
 		Ok(())
 	}
+}
+
+impl ModLoaderActions for BepInEx {
+	fn get_data(&self) -> &ModLoaderData {
+		&self.data
+	}
+
+	fn install(&self, game: &InstalledGame) -> Result {
+		let build = self.get_target_build(game, None)?;
+		self.install_build(game, build, &self.get_payload_choice(game, build))
+	}
+
+	fn get_deployed_mod_paths(&self, game: &InstalledGame, mod_id: &str) -> Result<Vec<PathBuf>> {
+		let bepinex_folder = game.get_installed_mods_folder()?.join("BepInEx");
+
+		// Mods with their dlls at the root also end up in the plugins folder.
+		let mut deployed_paths: Vec<PathBuf> = MOD_FOLDER_NAMES
+			.iter()
+			.map(|folder_name| bepinex_folder.join(folder_name).join(mod_id))
+			.collect();
+		deployed_paths.extend(self.get_deployed_config_paths(game, mod_id)?);
+
+		Ok(deployed_paths)
+	}
+
+	fn get_installed_version(&self, game: &InstalledGame) -> Option<String> {
+		let core_folder = game
+			.get_installed_mods_folder()
+			.ok()?
+			.join("BepInEx")
+			.join("core");
+
+		CORE_ASSEMBLY_NAMES
+			.iter()
+			.find_map(|assembly_name| get_assembly_version(&core_folder.join(assembly_name)))
+	}
+
+	fn get_install_preview(&self, game: &InstalledGame) -> Result<Vec<ArchiveEntry>> {
+		let build = self.get_target_build(game, None)?;
+		let scripting_backend_path = self
+			.get_build_resources(game, build, &self.get_payload_choice(game, build))?
+			.path;
+		let architecture_path = Self::get_architecture_path(game, &scripting_backend_path)?;
+		let game_data_folder = game.get_installed_mods_folder()?;
+		let game_folder = paths::path_parent(&game.executable.path)?;
+
+		let mut entries: Vec<ArchiveEntry> =
+			archive::list(&architecture_path.join("mod-loader.zip"))?
+				.into_iter()
+				.map(|entry| ArchiveEntry {
+					path: game_data_folder.join(entry.path),
+					..entry
+				})
+				.collect();
+
+		let folder_to_copy_to_game = architecture_path.join("copy-to-game");
+		if folder_to_copy_to_game.is_dir() {
+			for entry in fs::read_dir(folder_to_copy_to_game)? {
+				let entry = entry?;
+				let metadata = entry.metadata()?;
+				entries.push(ArchiveEntry {
+					path: game_folder.join(entry.file_name()),
+					size: metadata.len(),
+					is_dir: metadata.is_dir(),
+				});
+			}
+		}
+
+		entries.push(ArchiveEntry {
+			path: game_folder.join("doorstop_config.ini"),
+			size: fs::metadata(scripting_backend_path.join("doorstop_config.ini"))?.len(),
+			is_dir: false,
+		});
+
+		entries.push(ArchiveEntry {
+			path: bepinex_config::get_config_folder(game)?.join("BepInEx.cfg"),
+			size: fs::metadata(self.get_config_origin_path(game))?.len(),
+			is_dir: false,
+		});
+
+		Ok(entries)
+	}
 
 	async fn install_mod_inner(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
-		self.install(game)?;
+		let build = self.get_target_build(game, Some(local_mod))?;
+		self.install_build(game, build, &self.get_payload_choice(game, build))?;
 
 		let bepinex_folder = game.get_installed_mods_folder()?.join("BepInEx");
+		let mod_content_path = get_mod_content_path(&local_mod.data.path);
+
+		let mut has_mod_folders = false;
+		for folder_name in MOD_FOLDER_NAMES {
+			let mod_folder_path = mod_content_path.join(folder_name);
+			if mod_folder_path.is_dir() {
+				copy_dir_all(
+					mod_folder_path,
+					bepinex_folder.join(folder_name).join(&local_mod.common.id),
+				)?;
+				has_mod_folders = true;
+			}
+		}
 
-		let mod_plugin_path = local_mod.data.path.join("plugins");
-		if mod_plugin_path.is_dir() {
-			copy_dir_all(
-				mod_plugin_path,
-				bepinex_folder.join("plugins").join(&local_mod.common.id),
+		// Some mods (lots of Thunderstore packages, for instance) just have their dlls at the root.
+		if !has_mod_folders {
+			copy_root_assemblies(
+				&mod_content_path,
+				&bepinex_folder.join("plugins").join(&local_mod.common.id),
 			)?;
 		}
 
-		let mod_patch_path = local_mod.data.path.join("patchers");
-		if mod_patch_path.is_dir() {
-			copy_dir_all(
-				mod_patch_path,
-				bepinex_folder.join("patchers").join(&local_mod.common.id),
-			)?;
+		let mod_config_path = mod_content_path.join("config");
+		if mod_config_path.is_dir() {
+			copy_default_configs(&mod_config_path, &bepinex_folder.join("config"))?;
 		}
 
 		Ok(())
@@ -153,6 +591,11 @@ impl ModLoaderActions for BepInEx {
 			fs::remove_dir_all(patchers_folder)?;
 		}
 
+		let parked_folder = game.get_parked_mods_folder()?.join(&local_mod.common.id);
+		if parked_folder.is_dir() {
+			fs::remove_dir_all(parked_folder)?;
+		}
+
 		let manifest_path = game.get_installed_mod_manifest_path(&local_mod.common.id)?;
 		if manifest_path.is_file() {
 			fs::remove_file(manifest_path)?;
@@ -161,13 +604,48 @@ impl ModLoaderActions for BepInEx {
 		Ok(())
 	}
 
-	fn configure_mod(&self, game: &InstalledGame, _local_mod: &LocalMod) -> Result {
-		let game_data_folder = game.get_installed_mods_folder()?;
-		let mod_config_path = game_data_folder.join("BepInEx").join("config");
+	fn set_mod_enabled(&self, game: &InstalledGame, local_mod: &LocalMod, enabled: bool) -> Result {
+		let bepinex_folder = game.get_installed_mods_folder()?.join("BepInEx");
+		let parked_folder = game.get_parked_mods_folder()?.join(&local_mod.common.id);
+
+		// Disabled mods get moved out of the BepInEx folders instead of deleted,
+		// so they can be re-deployed without downloading or copying anything again.
+		for mod_folder_name in MOD_FOLDER_NAMES {
+			let deployed_path = bepinex_folder
+				.join(mod_folder_name)
+				.join(&local_mod.common.id);
+			let parked_path = parked_folder.join(mod_folder_name);
+
+			let (from_path, to_path) = if enabled {
+				(parked_path, deployed_path)
+			} else {
+				(deployed_path, parked_path)
+			};
+
+			if !from_path.is_dir() {
+				continue;
+			}
+
+			if to_path.is_dir() {
+				fs::remove_dir_all(&to_path)?;
+			}
+			fs::create_dir_all(paths::path_parent(&to_path)?)?;
+			fs::rename(from_path, to_path)?;
+		}
+
+		if enabled && parked_folder.is_dir() {
+			fs::remove_dir_all(parked_folder)?;
+		}
+
+		Ok(())
+	}
 
-		// TODO: actually open the specific config file somehow. Probably needs to be in the remote mod manifest.
+	fn configure_mod(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
+		// Falls back to the config folder, so people can at least find it once the mod makes its config.
+		let config_path = bepinex_config::get_mod_config_path(game, local_mod)?
+			.map_or_else(|| bepinex_config::get_config_folder(game), Ok)?;
 
-		Ok(open::that_detached(mod_config_path)?)
+		Ok(open::that_detached(config_path)?)
 	}
 
 	async fn run_without_game(&self, local_mod: &LocalMod) -> Result {
@@ -0,0 +1,182 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use log::{error, warn};
+use rai_pal_proc_macros::{serializable_enum, serializable_struct};
+
+use super::{bepinex::BepInEx, loader_payloads, mod_loader::ModLoaderStatic};
+use crate::{
+	game_engines::unity::UnityScriptingBackend,
+	installed_game::InstalledGame,
+	paths,
+	result::{Error, Result},
+};
+
+#[serializable_enum]
+pub enum BepInExBuild {
+	// BepInEx 5.x, the one most Mono mods are made for.
+	Stable5,
+	// BepInEx 6.x pre-releases.
+	Stable6,
+	// BepInEx 6 bleeding edge builds, which is what most IL2CPP mods need.
+	BleedingEdge,
+}
+
+#[serializable_struct]
+#[derive(Default)]
+pub struct BepInExGameBuild {
+	// Build the user picked for this game. If missing, we pick one based on the game and its mods.
+	pub selected: Option<BepInExBuild>,
+	// Build that's currently deployed to the game.
+	pub deployed: Option<BepInExBuild>,
+	// Version of the downloaded payload deployed to the game. Missing if it got the bundled files.
+	pub deployed_payload: Option<String>,
+}
+
+// Which files to deploy for a build. Newer downloaded payloads only get used on a fresh deploy or an upgrade,
+// so redeploying BepInEx (which happens every time a mod gets installed) doesn't quietly update it.
+pub enum PayloadChoice {
+	// Newest downloaded payload, or the bundled files if there isn't one.
+	Latest,
+	// The downloaded payload with this version, or the bundled files if missing.
+	Version(Option<String>),
+}
+
+pub struct BuildResources {
+	// Scripting backend folder, with the platform and architecture folders inside.
+	pub path: PathBuf,
+	// Missing if these are the bundled files.
+	pub payload_version: Option<String>,
+}
+
+const FILE_NAME: &str = "bepinex-build.json";
+
+impl BepInExBuild {
+	pub const ALL: [Self; 3] = [Self::Stable5, Self::Stable6, Self::BleedingEdge];
+
+	// BepInEx 5 was never made for IL2CPP.
+	pub const fn supports(self, scripting_backend: UnityScriptingBackend) -> bool {
+		!matches!(
+			(self, scripting_backend),
+			(Self::Stable5, UnityScriptingBackend::Il2Cpp)
+		)
+	}
+
+	// The build we bundle with Rai Pal for each scripting backend.
+	pub const fn get_default(scripting_backend: UnityScriptingBackend) -> Self {
+		match scripting_backend {
+			UnityScriptingBackend::Mono => Self::Stable5,
+			UnityScriptingBackend::Il2Cpp => Self::BleedingEdge,
+		}
+	}
+
+	// Downloaded payloads are used when chosen, with the bundled resources as the fallback.
+	// Default builds are at the root of the bundled BepInEx resources, any others go in their own folder.
+	// Either way, the folder has the same layout: scripting backend, then platform, then architecture.
+	pub fn get_resources(
+		self,
+		bepinex_resources_path: &Path,
+		scripting_backend: UnityScriptingBackend,
+		payload_choice: &PayloadChoice,
+	) -> Result<BuildResources> {
+		if !self.supports(scripting_backend) {
+			return Err(Error::BepInExBuildUnsupported(
+				self.to_string(),
+				scripting_backend.to_string(),
+			));
+		}
+
+		let build_name = self.to_string();
+		let payload_version = match payload_choice {
+			PayloadChoice::Latest => {
+				loader_payloads::get_latest_version(BepInEx::ID, &build_name, scripting_backend)
+			}
+			PayloadChoice::Version(version) => version.clone(),
+		};
+
+		if let Some(payload_version) = payload_version {
+			if let Some(downloaded_path) = loader_payloads::get_downloaded_path(
+				BepInEx::ID,
+				&build_name,
+				scripting_backend,
+				&payload_version,
+			) {
+				return Ok(BuildResources {
+					path: downloaded_path,
+					payload_version: Some(payload_version),
+				});
+			}
+
+			warn!(
+				"BepInEx build {build_name} payload version {payload_version} isn't downloaded, using the bundled files instead."
+			);
+		}
+
+		let build_path = if self == Self::get_default(scripting_backend) {
+			bepinex_resources_path.to_path_buf()
+		} else {
+			bepinex_resources_path.join("builds").join(self.to_string())
+		};
+
+		let scripting_backend_path = build_path.join(scripting_backend.to_string());
+		if !scripting_backend_path.is_dir() {
+			return Err(Error::BepInExBuildNotAvailable(
+				self.to_string(),
+				scripting_backend.to_string(),
+			));
+		}
+
+		Ok(BuildResources {
+			path: scripting_backend_path,
+			payload_version: None,
+		})
+	}
+
+	// Builds we have the files for, and that work with the given scripting backend.
+	pub fn get_available(
+		bepinex_resources_path: &Path,
+		scripting_backend: UnityScriptingBackend,
+	) -> Vec<Self> {
+		Self::ALL
+			.into_iter()
+			.filter(|build| {
+				build
+					.get_resources(
+						bepinex_resources_path,
+						scripting_backend,
+						&PayloadChoice::Latest,
+					)
+					.is_ok()
+			})
+			.collect()
+	}
+}
+
+fn get_path(game: &InstalledGame) -> Result<PathBuf> {
+	Ok(game.get_installed_mods_folder()?.join(FILE_NAME))
+}
+
+fn try_read(game: &InstalledGame) -> Result<BepInExGameBuild> {
+	let path = get_path(game)?;
+	if !path.is_file() {
+		return Ok(BepInExGameBuild::default());
+	}
+
+	Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn read(game: &InstalledGame) -> BepInExGameBuild {
+	try_read(game).unwrap_or_else(|err| {
+		error!("Failed to read BepInEx build for game {}: {err}", game.id);
+		BepInExGameBuild::default()
+	})
+}
+
+pub fn write(game: &InstalledGame, game_build: &BepInExGameBuild) -> Result {
+	let path = get_path(game)?;
+	fs::create_dir_all(paths::path_parent(&path)?)?;
+
+	Ok(fs::write(path, serde_json::to_string_pretty(game_build)?)?)
+}
@@ -0,0 +1,494 @@
+use std::{
+	collections::HashMap,
+	ffi::OsStr,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use log::error;
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	installed_game::InstalledGame,
+	local_mod::LocalMod,
+	paths,
+	result::{Error, Result},
+};
+
+const PLUGIN_NAME_PREFIX: &str = "Settings file was created by plugin ";
+const PLUGIN_GUID_PREFIX: &str = "Plugin GUID: ";
+const SETTING_TYPE_PREFIX: &str = "Setting type: ";
+const DEFAULT_VALUE_PREFIX: &str = "Default value: ";
+const ACCEPTABLE_VALUES_PREFIX: &str = "Acceptable values: ";
+const ACCEPTABLE_RANGE_PREFIX: &str = "Acceptable value range: From ";
+const MULTIPLE_VALUES_PREFIX: &str = "Multiple values can be set at the same time";
+
+#[serializable_struct]
+pub struct BepInExConfigRange {
+	pub from: String,
+	pub to: String,
+}
+
+#[serializable_struct]
+pub struct BepInExConfigEntry {
+	pub section: String,
+	pub key: String,
+	pub value: String,
+	pub description: Option<String>,
+	pub setting_type: Option<String>,
+	pub default_value: Option<String>,
+	pub acceptable_values: Option<Vec<String>>,
+	pub acceptable_range: Option<BepInExConfigRange>,
+	// Flag enums take a comma separated list of the acceptable values.
+	pub allows_multiple_values: bool,
+}
+
+#[serializable_struct]
+pub struct BepInExConfig {
+	// Relative to the BepInEx config folder.
+	pub file_name: String,
+	pub plugin_name: Option<String>,
+	pub plugin_guid: Option<String>,
+	pub entries: Vec<BepInExConfigEntry>,
+}
+
+// Keeps every line of the original file around, so writing it back only changes the values we touched.
+pub struct BepInExConfigDocument {
+	lines: Vec<String>,
+	line_ending: &'static str,
+	plugin_name: Option<String>,
+	plugin_guid: Option<String>,
+	// Each entry with the index of the line its value is in.
+	entries: Vec<(usize, BepInExConfigEntry)>,
+}
+
+#[derive(Default)]
+struct PendingMetadata {
+	description: Vec<String>,
+	setting_type: Option<String>,
+	default_value: Option<String>,
+	acceptable_values: Option<Vec<String>>,
+	acceptable_range: Option<BepInExConfigRange>,
+	allows_multiple_values: bool,
+}
+
+impl PendingMetadata {
+	fn read_comment(&mut self, comment: &str) {
+		if let Some(setting_type) = comment.strip_prefix(SETTING_TYPE_PREFIX) {
+			self.setting_type = Some(setting_type.to_string());
+		} else if let Some(default_value) = comment.strip_prefix(DEFAULT_VALUE_PREFIX) {
+			self.default_value = Some(default_value.to_string());
+		} else if let Some(acceptable_values) = comment.strip_prefix(ACCEPTABLE_VALUES_PREFIX) {
+			self.acceptable_values = Some(
+				acceptable_values
+					.split(',')
+					.map(|value| value.trim().to_string())
+					.collect(),
+			);
+		} else if let Some((from, to)) = comment
+			.strip_prefix(ACCEPTABLE_RANGE_PREFIX)
+			.and_then(|range| range.split_once(" to "))
+		{
+			self.acceptable_range = Some(BepInExConfigRange {
+				from: from.trim().to_string(),
+				to: to.trim().to_string(),
+			});
+		} else if comment.starts_with(MULTIPLE_VALUES_PREFIX) {
+			self.allows_multiple_values = true;
+		}
+	}
+}
+
+impl BepInExConfigEntry {
+	// Checks the value against whatever the plugin told us about the setting.
+	// Anything we don't know about is up to BepInEx to deal with.
+	pub fn get_value_issue(&self, value: &str) -> Option<String> {
+		let value = value.trim();
+
+		if let Some(acceptable_values) = &self.acceptable_values {
+			let values = if self.allows_multiple_values {
+				value.split(',').map(str::trim).collect()
+			} else {
+				vec![value]
+			};
+
+			// BepInEx ignores the case when parsing enums.
+			if let Some(invalid_value) = values.iter().find(|part| {
+				!acceptable_values
+					.iter()
+					.any(|acceptable_value| acceptable_value.eq_ignore_ascii_case(part))
+			}) {
+				return Some(format!(
+					"`{invalid_value}` isn't one of the acceptable values ({}).",
+					acceptable_values.join(", ")
+				));
+			}
+		}
+
+		if let Some(range) = &self.acceptable_range {
+			// Ranges with bounds we can't read as numbers aren't worth checking.
+			if let (Ok(from), Ok(to)) = (range.from.parse::<f64>(), range.to.parse::<f64>()) {
+				match value.parse::<f64>() {
+					Ok(number) if number < from || number > to => {
+						return Some(format!(
+							"`{value}` isn't between {} and {}.",
+							range.from, range.to
+						));
+					}
+					Ok(_) => {}
+					Err(_) => return Some(format!("`{value}` isn't a number.")),
+				}
+			}
+		}
+
+		None
+	}
+}
+
+// Values end up in a single line, so line breaks would turn the rest of the value into garbage entries.
+fn validate_value(
+	file_name: &str,
+	section: &str,
+	key: &str,
+	value: &str,
+	entry: Option<&BepInExConfigEntry>,
+) -> Result {
+	let issue = if value.contains(char::is_control) {
+		Some("Values can't contain line breaks or other control characters.".to_string())
+	} else {
+		entry.and_then(|entry| entry.get_value_issue(value))
+	};
+
+	issue.map_or(Ok(()), |issue| {
+		Err(Error::ModConfigValueInvalid(
+			file_name.to_string(),
+			format!("{section}.{key}"),
+			issue,
+		))
+	})
+}
+
+impl BepInExConfigDocument {
+	pub fn parse(contents: &str) -> Self {
+		let line_ending = if contents.contains("\r\n") {
+			"\r\n"
+		} else {
+			"\n"
+		};
+
+		let lines: Vec<String> = contents.lines().map(ToString::to_string).collect();
+
+		let mut plugin_name = None;
+		let mut plugin_guid = None;
+		let mut section: Option<String> = None;
+		let mut metadata = PendingMetadata::default();
+		let mut entries = Vec::new();
+
+		for (index, line) in lines.iter().enumerate() {
+			let line = line.trim();
+
+			if line.is_empty() {
+				continue;
+			}
+
+			if let Some(description) = line.strip_prefix("##") {
+				let description = description.trim();
+
+				// Comments before the first section are about the whole file.
+				if section.is_none() {
+					if let Some(name) = description.strip_prefix(PLUGIN_NAME_PREFIX) {
+						plugin_name = Some(name.to_string());
+					} else if let Some(guid) = description.strip_prefix(PLUGIN_GUID_PREFIX) {
+						plugin_guid = Some(guid.to_string());
+					}
+				} else {
+					metadata.description.push(description.to_string());
+				}
+			} else if let Some(comment) = line.strip_prefix('#') {
+				metadata.read_comment(comment.trim());
+			} else if let Some(section_name) = line
+				.strip_prefix('[')
+				.and_then(|line| line.strip_suffix(']'))
+			{
+				section = Some(section_name.to_string());
+				metadata = PendingMetadata::default();
+			} else if let Some((key, value)) = line.split_once('=') {
+				let metadata = std::mem::take(&mut metadata);
+
+				entries.push((
+					index,
+					BepInExConfigEntry {
+						section: section.clone().unwrap_or_default(),
+						key: key.trim().to_string(),
+						value: value.trim().to_string(),
+						description: if metadata.description.is_empty() {
+							None
+						} else {
+							Some(metadata.description.join("\n"))
+						},
+						setting_type: metadata.setting_type,
+						default_value: metadata.default_value,
+						acceptable_values: metadata.acceptable_values,
+						acceptable_range: metadata.acceptable_range,
+						allows_multiple_values: metadata.allows_multiple_values,
+					},
+				));
+			}
+		}
+
+		Self {
+			lines,
+			line_ending,
+			plugin_name,
+			plugin_guid,
+			entries,
+		}
+	}
+
+	pub fn get_config(&self, file_name: &str) -> BepInExConfig {
+		BepInExConfig {
+			file_name: file_name.to_string(),
+			plugin_name: self.plugin_name.clone(),
+			plugin_guid: self.plugin_guid.clone(),
+			entries: self
+				.entries
+				.iter()
+				.map(|(_, entry)| entry.clone())
+				.collect(),
+		}
+	}
+
+	pub fn get_entry(&self, section: &str, key: &str) -> Option<&BepInExConfigEntry> {
+		self.entries
+			.iter()
+			.map(|(_, entry)| entry)
+			.find(|entry| entry.section == section && entry.key == key)
+	}
+
+	pub fn set_value(&mut self, section: &str, key: &str, value: &str) -> bool {
+		let Some((line_index, entry)) = self
+			.entries
+			.iter_mut()
+			.find(|(_, entry)| entry.section == section && entry.key == key)
+		else {
+			return false;
+		};
+
+		entry.value = value.trim().to_string();
+		self.lines[*line_index] = format!("{} = {}", entry.key, entry.value);
+
+		true
+	}
+
+	// Adds the entry if it isn't there yet. BepInEx keeps entries that plugins haven't registered yet,
+	// and uses them once the plugin does.
+	pub fn set_or_add_value(&mut self, section: &str, key: &str, value: &str) {
+		if self.set_value(section, key, value) {
+			return;
+		}
+
+		let entry_line = format!("{key} = {}", value.trim());
+		let section_header = format!("[{section}]");
+
+		if let Some(section_index) = self
+			.lines
+			.iter()
+			.position(|line| line.trim() == section_header)
+		{
+			let last_section_entry_index = self
+				.entries
+				.iter()
+				.filter(|(_, entry)| entry.section == section)
+				.map(|(line_index, _)| *line_index)
+				.max()
+				.unwrap_or(section_index);
+			self.lines.insert(last_section_entry_index + 1, entry_line);
+		} else {
+			if self
+				.lines
+				.last()
+				.is_some_and(|line| !line.trim().is_empty())
+			{
+				self.lines.push(String::new());
+			}
+			self.lines.push(section_header);
+			self.lines.push(entry_line);
+		}
+
+		// Line indices might have moved, so it's easier to start over.
+		*self = Self::parse(&self.write());
+	}
+
+	pub fn write(&self) -> String {
+		let mut contents = self.lines.join(self.line_ending);
+		contents.push_str(self.line_ending);
+		contents
+	}
+}
+
+pub fn get_config_folder(game: &InstalledGame) -> Result<PathBuf> {
+	Ok(game
+		.get_installed_mods_folder()?
+		.join("BepInEx")
+		.join("config"))
+}
+
+// Config file names come from outside (the frontend, or presets from other people),
+// so we make sure they can't point outside the config folder.
+fn validate_file_name(file_name: &str) -> Result {
+	let is_valid = Path::new(file_name).file_name() == Some(OsStr::new(file_name))
+		&& Path::new(file_name)
+			.extension()
+			.is_some_and(|extension| extension == "cfg");
+
+	if is_valid {
+		Ok(())
+	} else {
+		Err(Error::ModConfigNotFound(file_name.to_string()))
+	}
+}
+
+fn get_config_path(game: &InstalledGame, file_name: &str) -> Result<PathBuf> {
+	validate_file_name(file_name)?;
+
+	let config_path = get_config_folder(game)?.join(file_name);
+	if !config_path.is_file() {
+		return Err(Error::ModConfigNotFound(file_name.to_string()));
+	}
+
+	Ok(config_path)
+}
+
+pub fn get_configs(game: &InstalledGame) -> Result<Vec<BepInExConfig>> {
+	let mut configs: Vec<_> = paths::glob_path(&get_config_folder(game)?.join("*.cfg"))
+		.iter()
+		.filter_map(|config_path| {
+			let file_name = config_path.file_name()?.to_str()?;
+
+			match fs::read_to_string(config_path) {
+				Ok(contents) => Some(BepInExConfigDocument::parse(&contents).get_config(file_name)),
+				Err(err) => {
+					error!("Failed to read config {}: {err}", config_path.display());
+					None
+				}
+			}
+		})
+		.collect();
+
+	configs.sort_by(|config_a, config_b| config_a.file_name.cmp(&config_b.file_name));
+
+	Ok(configs)
+}
+
+pub fn set_value(
+	game: &InstalledGame,
+	file_name: &str,
+	section: &str,
+	key: &str,
+	value: &str,
+) -> Result<BepInExConfig> {
+	let config_path = get_config_path(game, file_name)?;
+	let mut document = BepInExConfigDocument::parse(&fs::read_to_string(&config_path)?);
+
+	let entry = document.get_entry(section, key).ok_or_else(|| {
+		Error::ModConfigEntryNotFound(file_name.to_string(), format!("{section}.{key}"))
+	})?;
+	validate_value(file_name, section, key, value, Some(entry))?;
+	document.set_value(section, key, value);
+
+	fs::write(config_path, document.write())?;
+
+	Ok(document.get_config(file_name))
+}
+
+// Raw contents of every config file, by file name.
+pub fn read_files(game: &InstalledGame) -> Result<HashMap<String, String>> {
+	paths::glob_path(&get_config_folder(game)?.join("*.cfg"))
+		.iter()
+		.filter_map(|config_path| {
+			Some((config_path.file_name()?.to_str()?.to_string(), config_path))
+		})
+		.map(|(file_name, config_path)| Ok((file_name, fs::read_to_string(config_path)?)))
+		.collect()
+}
+
+// Replaces the whole config file, creating it if needed.
+pub fn write_file(game: &InstalledGame, file_name: &str, contents: &str) -> Result {
+	validate_file_name(file_name)?;
+
+	let config_folder = get_config_folder(game)?;
+	fs::create_dir_all(&config_folder)?;
+
+	Ok(fs::write(config_folder.join(file_name), contents)?)
+}
+
+// Same as `set_value`, but creates the file and the entry if they don't exist yet.
+pub fn override_value(
+	game: &InstalledGame,
+	file_name: &str,
+	section: &str,
+	key: &str,
+	value: &str,
+) -> Result {
+	validate_file_name(file_name)?;
+
+	let config_path = get_config_folder(game)?.join(file_name);
+	let contents = if config_path.is_file() {
+		fs::read_to_string(&config_path)?
+	} else {
+		String::new()
+	};
+
+	let mut document = BepInExConfigDocument::parse(&contents);
+	validate_value(
+		file_name,
+		section,
+		key,
+		value,
+		document.get_entry(section, key),
+	)?;
+	document.set_or_add_value(section, key, value);
+
+	fs::create_dir_all(paths::path_parent(&config_path)?)?;
+	Ok(fs::write(config_path, document.write())?)
+}
+
+// Mods can say which config file is theirs. Otherwise, we look for a config named after the mod,
+// since BepInEx names config files after the plugin GUID.
+// Missing if the mod hasn't made its config yet, which usually happens the first time the game runs with it.
+pub fn get_mod_config_path(game: &InstalledGame, local_mod: &LocalMod) -> Result<Option<PathBuf>> {
+	if let Some(config_file) = local_mod
+		.data
+		.manifest
+		.as_ref()
+		.and_then(|manifest| manifest.config_file.as_ref())
+	{
+		validate_file_name(config_file)?;
+		let config_path = get_config_folder(game)?.join(config_file);
+		return Ok(config_path.is_file().then_some(config_path));
+	}
+
+	let simplify = |name: &str| {
+		name.chars()
+			.filter(char::is_ascii_alphanumeric)
+			.collect::<String>()
+			.to_ascii_lowercase()
+	};
+	let mod_name = simplify(&local_mod.common.id);
+	if mod_name.is_empty() {
+		return Ok(None);
+	}
+
+	let Some(config) = get_configs(game)?.into_iter().find(|config| {
+		config
+			.plugin_guid
+			.as_deref()
+			.into_iter()
+			.chain([config.file_name.trim_end_matches(".cfg")])
+			.any(|name| simplify(name).contains(&mod_name))
+	}) else {
+		return Ok(None);
+	};
+
+	Ok(Some(get_config_folder(game)?.join(config.file_name)))
+}
@@ -0,0 +1,356 @@
+use std::{
+	collections::HashMap,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use log::error;
+use rai_pal_proc_macros::{serializable_enum, serializable_struct};
+
+use super::mod_loader::{ModLoaderActions, ModLoaderData};
+use crate::{
+	archive::{self, ArchiveEntry},
+	files::copy_dir_all,
+	game_engines::{game_engine::EngineBrand, unity::UnityScriptingBackend},
+	game_executable::Architecture,
+	game_mod::CommonModData,
+	installed_game::InstalledGame,
+	local_mod::{LocalMod, ModKind},
+	paths,
+	result::{Error, Result},
+};
+
+// Any folder in the resources folder with one of these is treated as a mod loader.
+// There's an example in test-data/sample-loader.
+pub const DESCRIPTOR_FILE_NAME: &str = "loader.json";
+
+const MOD_FILES_PATH_TOKEN: &str = "{{MOD_FILES_PATH}}";
+const GAME_FOLDER_PATH_TOKEN: &str = "{{GAME_FOLDER_PATH}}";
+const MOD_ID_TOKEN: &str = "{{MOD_ID}}";
+
+#[serializable_enum]
+pub enum DescriptorPlatform {
+	Windows,
+	Linux,
+}
+
+#[serializable_enum]
+pub enum DescriptorRoot {
+	// Folder with the game's executable.
+	GameFolder,
+	// Folder Rai Pal keeps for this game's mods, outside the game folder.
+	ModFilesFolder,
+}
+
+#[serializable_struct]
+pub struct DescriptorPath {
+	pub root: DescriptorRoot,
+	// Relative to the root. Can use {{MOD_ID}} where it makes sense.
+	pub path: String,
+}
+
+#[serializable_struct]
+pub struct DescriptorFile {
+	// Relative to the mod loader's resources folder. Can be a file, a folder, or an archive.
+	pub source: String,
+	pub destination: DescriptorPath,
+	// If true, the source is an archive that gets extracted into the destination.
+	pub extract: Option<bool>,
+	// Files are only used for games that match all the conditions that are set.
+	pub platform: Option<DescriptorPlatform>,
+	pub architecture: Option<Architecture>,
+	pub unity_backend: Option<UnityScriptingBackend>,
+}
+
+#[serializable_struct]
+pub struct LoaderDescriptor {
+	pub engine: Option<EngineBrand>,
+	// Copied to the game when the mod loader is installed.
+	pub files: Vec<DescriptorFile>,
+	// Text files written to the game when the mod loader is installed, after replacing
+	// {{MOD_FILES_PATH}} and {{GAME_FOLDER_PATH}} with the actual paths.
+	pub templates: Option<Vec<DescriptorFile>>,
+	// Where each mod's files get copied to when installed.
+	pub mods_folder: DescriptorPath,
+	// Anything else that should be deleted when uninstalling a mod.
+	pub uninstall_paths: Option<Vec<DescriptorPath>>,
+}
+
+// Mod loader defined by a json descriptor in the resources folder, instead of by code.
+// Good enough for loaders that just need some files dropped in the game folder.
+#[serializable_struct]
+pub struct DescriptorLoader {
+	pub data: ModLoaderData,
+	pub descriptor: LoaderDescriptor,
+}
+
+impl DescriptorLoader {
+	pub fn new(loader_path: &Path) -> Result<Self> {
+		let id = loader_path
+			.file_name()
+			.and_then(|file_name| file_name.to_str())
+			.ok_or_else(|| Error::PathParseFailure(loader_path.to_path_buf()))?
+			.to_string();
+
+		Ok(Self {
+			descriptor: serde_json::from_str(&fs::read_to_string(
+				loader_path.join(DESCRIPTOR_FILE_NAME),
+			)?)?,
+			data: ModLoaderData {
+				id,
+				path: loader_path.to_path_buf(),
+				kind: ModKind::Installable,
+			},
+		})
+	}
+
+	fn get_installed_mods_path(&self) -> Result<PathBuf> {
+		Ok(paths::installed_mods_path()?
+			.join(&self.data.id)
+			.join("mods"))
+	}
+
+	fn get_files(&self, game: &InstalledGame) -> Vec<&DescriptorFile> {
+		let platform = get_platform(game);
+
+		self.descriptor
+			.files
+			.iter()
+			.filter(|file| file.matches(game, platform))
+			.collect()
+	}
+
+	fn get_templates(&self, game: &InstalledGame) -> Vec<&DescriptorFile> {
+		let platform = get_platform(game);
+
+		self.descriptor
+			.templates
+			.iter()
+			.flatten()
+			.filter(|template| template.matches(game, platform))
+			.collect()
+	}
+
+	fn get_installed_mod_path(&self, game: &InstalledGame, mod_id: &str) -> Result<PathBuf> {
+		self.descriptor.mods_folder.resolve(game, Some(mod_id))
+	}
+}
+
+// We can only really tell the platform from the executable.
+// Windows executables on Linux run through Wine or Proton, so they still need the Windows files.
+fn get_platform(game: &InstalledGame) -> DescriptorPlatform {
+	if game
+		.executable
+		.path
+		.extension()
+		.is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+	{
+		DescriptorPlatform::Windows
+	} else {
+		DescriptorPlatform::Linux
+	}
+}
+
+fn replace_tokens(text: &str, game: &InstalledGame) -> Result<String> {
+	Ok(text
+		.replace(
+			MOD_FILES_PATH_TOKEN,
+			game.get_installed_mods_folder()?.to_string_lossy().as_ref(),
+		)
+		.replace(
+			GAME_FOLDER_PATH_TOKEN,
+			paths::path_parent(&game.executable.path)?
+				.to_string_lossy()
+				.as_ref(),
+		))
+}
+
+impl DescriptorPath {
+	fn resolve(&self, game: &InstalledGame, mod_id: Option<&str>) -> Result<PathBuf> {
+		let root = match self.root {
+			DescriptorRoot::GameFolder => paths::path_parent(&game.executable.path)?.to_path_buf(),
+			DescriptorRoot::ModFilesFolder => game.get_installed_mods_folder()?,
+		};
+
+		let path = mod_id.map_or_else(
+			|| self.path.clone(),
+			|mod_id| self.path.replace(MOD_ID_TOKEN, mod_id),
+		);
+
+		Ok(root.join(path))
+	}
+}
+
+impl DescriptorFile {
+	fn matches(&self, game: &InstalledGame, platform: DescriptorPlatform) -> bool {
+		self.platform
+			.is_none_or(|file_platform| file_platform == platform)
+			&& self
+				.architecture
+				.is_none_or(|architecture| game.executable.architecture == Some(architecture))
+			&& self.unity_backend.is_none_or(|unity_backend| {
+				game.executable.scripting_backend == Some(unity_backend)
+			})
+	}
+}
+
+impl ModLoaderActions for DescriptorLoader {
+	fn get_data(&self) -> &ModLoaderData {
+		&self.data
+	}
+
+	fn install(&self, game: &InstalledGame) -> Result {
+		for file in self.get_files(game) {
+			let source_path = self.data.path.join(&file.source);
+			let destination_path = file.destination.resolve(game, None)?;
+
+			if file.extract.unwrap_or(false) {
+				archive::extract(&source_path, &destination_path)?;
+			} else if source_path.is_dir() {
+				copy_dir_all(&source_path, &destination_path)?;
+			} else {
+				fs::create_dir_all(paths::path_parent(&destination_path)?)?;
+				fs::copy(&source_path, &destination_path)?;
+			}
+		}
+
+		for template in self.get_templates(game) {
+			let destination_path = template.destination.resolve(game, None)?;
+			let contents = fs::read_to_string(self.data.path.join(&template.source))?;
+
+			fs::create_dir_all(paths::path_parent(&destination_path)?)?;
+			fs::write(destination_path, replace_tokens(&contents, game)?)?;
+		}
+
+		Ok(())
+	}
+
+	fn get_install_preview(&self, game: &InstalledGame) -> Result<Vec<ArchiveEntry>> {
+		let mut entries = Vec::new();
+
+		for file in self.get_files(game) {
+			let source_path = self.data.path.join(&file.source);
+			let destination_path = file.destination.resolve(game, None)?;
+
+			if file.extract.unwrap_or(false) {
+				entries.extend(archive::list(&source_path)?.into_iter().map(|entry| {
+					ArchiveEntry {
+						path: destination_path.join(entry.path),
+						..entry
+					}
+				}));
+			} else {
+				entries.push(ArchiveEntry {
+					size: fs::metadata(&source_path)?.len(),
+					is_dir: source_path.is_dir(),
+					path: destination_path,
+				});
+			}
+		}
+
+		for template in self.get_templates(game) {
+			entries.push(ArchiveEntry {
+				size: fs::metadata(self.data.path.join(&template.source))?.len(),
+				is_dir: false,
+				path: template.destination.resolve(game, None)?,
+			});
+		}
+
+		Ok(entries)
+	}
+
+	fn has_official_database(&self) -> bool {
+		false
+	}
+
+	fn get_deployed_mod_paths(&self, game: &InstalledGame, mod_id: &str) -> Result<Vec<PathBuf>> {
+		Ok(vec![self.get_installed_mod_path(game, mod_id)?])
+	}
+
+	async fn install_mod_inner(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
+		self.install(game)?;
+
+		copy_dir_all(
+			&local_mod.data.path,
+			self.get_installed_mod_path(game, &local_mod.common.id)?,
+		)
+	}
+
+	async fn uninstall_mod(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
+		let mod_id = &local_mod.common.id;
+
+		let mut paths_to_remove = vec![
+			self.get_installed_mod_path(game, mod_id)?,
+			game.get_parked_mods_folder()?.join(mod_id),
+			game.get_installed_mod_manifest_path(mod_id)?,
+		];
+		for uninstall_path in self.descriptor.uninstall_paths.iter().flatten() {
+			paths_to_remove.push(uninstall_path.resolve(game, Some(mod_id))?);
+		}
+
+		for path in paths_to_remove {
+			if path.is_dir() {
+				fs::remove_dir_all(path)?;
+			} else if path.is_file() {
+				fs::remove_file(path)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn set_mod_enabled(&self, game: &InstalledGame, local_mod: &LocalMod, enabled: bool) -> Result {
+		let deployed_path = self.get_installed_mod_path(game, &local_mod.common.id)?;
+		let parked_path = game.get_parked_mods_folder()?.join(&local_mod.common.id);
+
+		let (from_path, to_path) = if enabled {
+			(parked_path, deployed_path)
+		} else {
+			(deployed_path, parked_path)
+		};
+
+		if !from_path.is_dir() {
+			return Ok(());
+		}
+
+		if to_path.is_dir() {
+			fs::remove_dir_all(&to_path)?;
+		}
+		fs::create_dir_all(paths::path_parent(&to_path)?)?;
+		fs::rename(from_path, to_path)?;
+
+		Ok(())
+	}
+
+	fn configure_mod(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
+		self.open_installed_mod_folder(game, local_mod)
+	}
+
+	async fn run_without_game(&self, local_mod: &LocalMod) -> Result {
+		Err(Error::CantRunNonRunnable(local_mod.common.id.clone()))
+	}
+
+	fn open_installed_mod_folder(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
+		Ok(open::that_detached(
+			self.get_installed_mod_path(game, &local_mod.common.id)?,
+		)?)
+	}
+
+	fn get_mod_path(&self, mod_data: &CommonModData) -> Result<PathBuf> {
+		Ok(self.get_installed_mods_path()?.join(&mod_data.id))
+	}
+
+	fn get_local_mods(&self) -> Result<HashMap<String, LocalMod>> {
+		Ok(paths::glob_path(&self.get_installed_mods_path()?.join("*"))
+			.iter()
+			.filter_map(|mod_path| {
+				LocalMod::new(&self.data.id, mod_path, self.descriptor.engine, None)
+					.inspect_err(|err| {
+						error!("Failed to read mod at {}: {err}", mod_path.display());
+					})
+					.ok()
+			})
+			.map(|local_mod| (local_mod.common.id.clone(), local_mod))
+			.collect())
+	}
+}
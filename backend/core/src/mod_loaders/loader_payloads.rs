@@ -0,0 +1,370 @@
+use std::{
+	cmp::Ordering,
+	collections::{HashMap, HashSet},
+	ffi::OsStr,
+	fs,
+	path::{Path, PathBuf},
+	sync::{
+		Mutex,
+		atomic::{self, AtomicBool},
+	},
+};
+
+use lazy_static::lazy_static;
+use log::info;
+use rai_pal_proc_macros::serializable_struct;
+
+use super::{mod_database::ModDownload, mod_verification};
+use crate::{
+	archive,
+	download::{self, DownloadProgress},
+	game_engines::unity::UnityScriptingBackend,
+	mod_version_requirements, paths, remote_database,
+	result::{Error, Result},
+};
+
+// Same idea as the mod database version: bump this, and add a matching folder to rai-pal-db,
+// when the index changes in a way older versions of Rai Pal can't handle.
+const INDEX_VERSION: i32 = 0;
+
+const PAYLOAD_FILE_NAME: &str = "rai-pal-loader-payload.json";
+
+// Refreshing mods starts an update every time, so we make sure they don't pile up.
+static IS_UPDATING: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+	// Payloads being downloaded right now, since two downloads of the same payload would share a staging folder.
+	static ref ACTIVE_DOWNLOADS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+// Files a mod loader copies to games, for one of its builds.
+// The archive has the same layout as the bundled resources for that build.
+// Every version we download is kept, so games keep getting the version they already have
+// until someone asks for an upgrade.
+#[serializable_struct]
+pub struct LoaderPayload {
+	pub build: String,
+	pub scripting_backend: UnityScriptingBackend,
+	// The download ID is the mod loader version. Needs a sha256 or a minisign signature.
+	pub download: ModDownload,
+}
+
+#[serializable_struct]
+pub struct LoaderIndex {
+	pub payloads: Vec<LoaderPayload>,
+}
+
+// Keeps a payload in the active downloads until it's dropped, however the download ends.
+struct ActiveDownload(String);
+
+impl ActiveDownload {
+	fn start(download_id: &str) -> Result<Self> {
+		let is_new = ACTIVE_DOWNLOADS
+			.lock()
+			.is_ok_and(|mut active_downloads| active_downloads.insert(download_id.to_string()));
+
+		if is_new {
+			Ok(Self(download_id.to_string()))
+		} else {
+			Err(Error::LoaderPayloadBusy(download_id.to_string()))
+		}
+	}
+}
+
+impl Drop for ActiveDownload {
+	fn drop(&mut self) {
+		if let Ok(mut active_downloads) = ACTIVE_DOWNLOADS.lock() {
+			active_downloads.remove(&self.0);
+		}
+	}
+}
+
+pub async fn get_index(loader_id: &str) -> Result<LoaderIndex> {
+	remote_database::get(&format!("loader-db/{INDEX_VERSION}/{loader_id}.json")).await
+}
+
+fn get_download_id(loader_id: &str, payload: &LoaderPayload) -> String {
+	format!(
+		"{loader_id}/{}/{}/{}",
+		payload.build, payload.scripting_backend, payload.download.id
+	)
+}
+
+// Newest first. Versions that look the same to `mod_version_requirements` still get a stable order.
+fn compare_versions(version_a: &str, version_b: &str) -> Ordering {
+	match mod_version_requirements::compare(version_b, version_a) {
+		Ordering::Equal => version_b.cmp(version_a),
+		ordering => ordering,
+	}
+}
+
+fn get_versions_path(
+	loader_id: &str,
+	build: &str,
+	scripting_backend: UnityScriptingBackend,
+) -> Result<PathBuf> {
+	Ok(paths::installed_mods_path()?
+		.join(loader_id)
+		.join("loader")
+		.join(build)
+		.join(scripting_backend.to_string()))
+}
+
+pub fn get_payload_path(
+	loader_id: &str,
+	build: &str,
+	scripting_backend: UnityScriptingBackend,
+	version: &str,
+) -> Result<PathBuf> {
+	Ok(get_versions_path(loader_id, build, scripting_backend)?.join(version))
+}
+
+fn read_payload(payload_path: &Path) -> Option<LoaderPayload> {
+	serde_json::from_str(&fs::read_to_string(payload_path.join(PAYLOAD_FILE_NAME)).ok()?).ok()
+}
+
+// Index entries come from outside, so we make sure we can check what we download,
+// and that the version can be used as a folder name.
+pub fn validate(loader_id: &str, payload: &LoaderPayload) -> Result {
+	let payload_id = get_download_id(loader_id, payload);
+	let invalid = |reason: &str| {
+		Err(Error::LoaderPayloadInvalid(
+			payload_id.clone(),
+			reason.to_string(),
+		))
+	};
+
+	if payload.download.sha256.is_none() && payload.download.minisign.is_none() {
+		return invalid("it has no sha256 or minisign signature to verify it with");
+	}
+
+	for folder_name in [payload.build.as_str(), payload.download.id.as_str()] {
+		if Path::new(folder_name).file_name() != Some(OsStr::new(folder_name)) {
+			return invalid("its build and version can't be used as folder names");
+		}
+	}
+
+	Ok(())
+}
+
+// Folder with the downloaded files for this version of the build, if they've been downloaded.
+pub fn get_downloaded_path(
+	loader_id: &str,
+	build: &str,
+	scripting_backend: UnityScriptingBackend,
+	version: &str,
+) -> Option<PathBuf> {
+	let payload_path = get_payload_path(loader_id, build, scripting_backend, version).ok()?;
+
+	read_payload(&payload_path).map(|_| payload_path)
+}
+
+// Versions of the build we have the files for, newest first.
+pub fn get_downloaded_versions(
+	loader_id: &str,
+	build: &str,
+	scripting_backend: UnityScriptingBackend,
+) -> Vec<String> {
+	let Ok(versions_path) = get_versions_path(loader_id, build, scripting_backend) else {
+		return Vec::new();
+	};
+
+	let mut versions: Vec<String> = paths::glob_path(&versions_path.join("*"))
+		.iter()
+		.filter_map(|payload_path| read_payload(payload_path))
+		.map(|payload| payload.download.id)
+		.collect();
+
+	versions.sort_by(|version_a, version_b| compare_versions(version_a, version_b));
+
+	versions
+}
+
+pub fn get_latest_version(
+	loader_id: &str,
+	build: &str,
+	scripting_backend: UnityScriptingBackend,
+) -> Option<String> {
+	get_downloaded_versions(loader_id, build, scripting_backend)
+		.into_iter()
+		.next()
+}
+
+async fn download<F>(loader_id: &str, payload: &LoaderPayload, on_progress: &F) -> Result
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+{
+	validate(loader_id, payload)?;
+
+	let download_id = get_download_id(loader_id, payload);
+	let _active_download = ActiveDownload::start(&download_id)?;
+
+	let payload_path = get_payload_path(
+		loader_id,
+		&payload.build,
+		payload.scripting_backend,
+		&payload.download.id,
+	)?;
+
+	// Each payload gets its own folder, so downloads of other payloads can't clash with this one.
+	let downloads_path = paths::installed_mods_path()?
+		.join(loader_id)
+		.join("downloads")
+		.join("loader")
+		.join(&payload.build)
+		.join(payload.scripting_backend.to_string())
+		.join(&payload.download.id);
+	fs::create_dir_all(&downloads_path)?;
+
+	let download_path = downloads_path.join(download::get_url_file_name(&payload.download.url));
+	download::download_file(
+		&download_id,
+		&payload.download.url,
+		&download_path,
+		on_progress,
+	)
+	.await?;
+
+	if let Err(err) = mod_verification::verify(loader_id, &download_path, &payload.download).await {
+		fs::remove_file(&download_path)?;
+		return Err(err);
+	}
+
+	// Extracted to a temporary folder first, so a failed extraction doesn't break the payload we already had.
+	let unzip_path = downloads_path.join("extracted");
+	if unzip_path.exists() {
+		fs::remove_dir_all(&unzip_path)?;
+	}
+	archive::extract(&download_path, &unzip_path)?;
+
+	let staged_path = payload
+		.download
+		.root
+		.as_ref()
+		.map_or_else(|| unzip_path.clone(), |root| unzip_path.join(root));
+
+	// Written last, so its presence means the payload is complete.
+	fs::write(
+		staged_path.join(PAYLOAD_FILE_NAME),
+		serde_json::to_string_pretty(payload)?,
+	)?;
+
+	if payload_path.exists() {
+		fs::remove_dir_all(&payload_path)?;
+	}
+	fs::create_dir_all(paths::path_parent(&payload_path)?)?;
+	fs::rename(&staged_path, &payload_path)?;
+
+	fs::remove_dir_all(&downloads_path)?;
+
+	info!("Downloaded {loader_id} payload `{download_id}`");
+
+	Ok(())
+}
+
+// Newest version in the index for each build and scripting backend.
+pub fn get_latest_payloads(index: &LoaderIndex) -> Vec<&LoaderPayload> {
+	let mut latest_payloads: HashMap<(&str, UnityScriptingBackend), &LoaderPayload> =
+		HashMap::new();
+
+	for payload in &index.payloads {
+		latest_payloads
+			.entry((payload.build.as_str(), payload.scripting_backend))
+			.and_modify(|latest_payload| {
+				if compare_versions(&payload.download.id, &latest_payload.download.id)
+					== Ordering::Less
+				{
+					*latest_payload = payload;
+				}
+			})
+			.or_insert(payload);
+	}
+
+	latest_payloads.into_values().collect()
+}
+
+// Gets a specific version from the index, for when a game needs one we don't have yet.
+// Any of the given builds will do, as long as it has that version.
+pub async fn download_version<F>(
+	loader_id: &str,
+	builds: &[String],
+	scripting_backend: UnityScriptingBackend,
+	version: &str,
+	on_progress: &F,
+) -> Result
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+{
+	if builds
+		.iter()
+		.any(|build| get_downloaded_path(loader_id, build, scripting_backend, version).is_some())
+	{
+		return Ok(());
+	}
+
+	let index = get_index(loader_id).await?;
+	let payload = index
+		.payloads
+		.iter()
+		.find(|payload| {
+			builds.contains(&payload.build)
+				&& payload.scripting_backend == scripting_backend
+				&& payload.download.id == version
+		})
+		.ok_or_else(|| {
+			Error::ModLoaderVersionNotAvailable(loader_id.to_string(), version.to_string())
+		})?;
+
+	download(loader_id, payload, on_progress).await
+}
+
+// Downloads the newest payload version in the index for each build, if we don't have it yet.
+// Older versions are only downloaded when something asks for them, with `download_version`.
+// Nothing gets deployed here, games only get the new files on a fresh deploy or an upgrade.
+// Payloads that fail to download keep whatever we had before, and the bundled resources are always there too.
+pub async fn update<F, E>(loader_id: &str, on_progress: &F, error_handler: E)
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+	E: Fn(Error) + Send,
+{
+	if IS_UPDATING.swap(true, atomic::Ordering::SeqCst) {
+		info!("Already updating {loader_id} payloads.");
+		return;
+	}
+
+	update_latest(loader_id, on_progress, error_handler).await;
+
+	IS_UPDATING.store(false, atomic::Ordering::SeqCst);
+}
+
+async fn update_latest<F, E>(loader_id: &str, on_progress: &F, error_handler: E)
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+	E: Fn(Error) + Send,
+{
+	let index = match get_index(loader_id).await {
+		Ok(index) => index,
+		Err(err) => {
+			error_handler(err);
+			return;
+		}
+	};
+
+	for payload in get_latest_payloads(&index) {
+		let is_downloaded = get_downloaded_path(
+			loader_id,
+			&payload.build,
+			payload.scripting_backend,
+			&payload.download.id,
+		)
+		.is_some();
+
+		if is_downloaded {
+			continue;
+		}
+
+		if let Err(err) = download(loader_id, payload, on_progress).await {
+			error_handler(err);
+		}
+	}
+}
@@ -1,4 +1,16 @@
 pub mod bepinex;
+pub mod bepinex_builds;
+pub mod bepinex_config;
+pub mod descriptor_loader;
+pub mod loader_payloads;
 pub mod mod_database;
+pub mod mod_dependencies;
+pub mod mod_import;
+pub mod mod_integrity;
 pub mod mod_loader;
+pub mod mod_sources;
+pub mod mod_verification;
+pub mod modpacks;
 pub mod runnable_loader;
+pub mod runnable_processes;
+pub mod thunderstore;
@@ -1,19 +1,19 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use lazy_regex::regex_captures;
 use log::error;
 use rai_pal_proc_macros::serializable_struct;
-use reqwest::redirect::Policy;
+use reqwest::{header, redirect::Policy};
 
+use super::{bepinex_builds::BepInExBuild, mod_verification::ModSignature};
 use crate::{
 	game_engines::{game_engine::EngineBrand, unity::UnityScriptingBackend},
-	game_mod::EngineVersionRange,
+	game_executable::Architecture,
+	game_mod::{EngineVersionRange, ModDependency},
+	remote_database,
 	result::Result,
 };
 
-// TODO: fall back to github if this one fails.
-const URL_BASE: &str = "https://raicuparta.github.io/rai-pal-db/mod-db";
-
 // The repository over at github.com/Raicuparta/rai-pal-db can have multiple versions of the database.
 // This way we prevent old versions of Rai Pal from breaking unless we want them to.
 // So when you need to change the database in a backwards-incompatible way,
@@ -31,9 +31,19 @@ pub struct DatabaseEntry {
 	pub engine: Option<EngineBrand>,
 	pub engine_version_range: Option<EngineVersionRange>,
 	pub unity_backend: Option<UnityScriptingBackend>,
+	// Only needed for mods with native files, like proxy DLLs. Works with any architecture if missing.
+	pub architecture: Option<Architecture>,
 	pub github: Option<ModGithubInfo>,
 	pub redownload_id: Option<i32>,
 	pub deprecated: Option<bool>,
+	pub dependencies: Option<Vec<ModDependency>>,
+	pub incompatibilities: Option<Vec<ModDependency>>,
+	// BepInEx builds the mod works with. Any build is fine if missing.
+	pub bepinex_builds: Option<Vec<BepInExBuild>>,
+	// BepInEx config file the mod keeps its settings in, like "com.someone.mod.cfg".
+	pub config_file: Option<String>,
+	// Older versions people can roll back to. Doesn't need to include the latest version.
+	pub versions: Option<Vec<ModDownload>>,
 }
 
 #[serializable_struct]
@@ -42,9 +52,41 @@ pub struct RunnableModData {
 	pub args: Vec<String>,
 }
 
+// A set of mods from the same database that are meant to be installed together.
+#[serializable_struct]
+pub struct ModpackEntry {
+	pub id: String,
+	pub title: String,
+	pub author: String,
+	pub description: String,
+	pub engine: Option<EngineBrand>,
+	pub engine_version_range: Option<EngineVersionRange>,
+	pub unity_backend: Option<UnityScriptingBackend>,
+	// Installed in this order.
+	pub mods: Vec<ModpackMod>,
+	pub config_overrides: Option<Vec<ModpackConfigOverride>>,
+}
+
+#[serializable_struct]
+pub struct ModpackMod {
+	pub id: String,
+	// Version ID to install. Uses the latest version if missing.
+	pub version: Option<String>,
+}
+
+#[serializable_struct]
+pub struct ModpackConfigOverride {
+	// Name of the BepInEx config file, like "com.someone.mod.cfg".
+	pub file_name: String,
+	pub section: String,
+	pub key: String,
+	pub value: String,
+}
+
 #[serializable_struct]
 pub struct ModDatabase {
 	pub mods: Vec<DatabaseEntry>,
+	pub modpacks: Option<Vec<ModpackEntry>>,
 }
 
 #[serializable_struct]
@@ -53,6 +95,21 @@ pub struct ModDownload {
 	pub url: String,
 	pub root: Option<PathBuf>,
 	pub runnable: Option<RunnableModData>,
+	pub sha256: Option<String>,
+	pub minisign: Option<ModSignature>,
+}
+
+#[derive(serde::Deserialize)]
+struct GithubRelease {
+	tag_name: String,
+	draft: bool,
+	assets: Vec<GithubReleaseAsset>,
+}
+
+#[derive(serde::Deserialize)]
+struct GithubReleaseAsset {
+	name: String,
+	browser_download_url: String,
 }
 
 #[serializable_struct]
@@ -62,15 +119,14 @@ pub struct ModGithubInfo {
 	pub asset_name: String,
 	pub root: Option<PathBuf>,
 	pub runnable: Option<RunnableModData>,
+	// SHA-256 of the asset for each release tag.
+	// If there are any, releases missing from here are only downloaded if they can be checked with minisign.
+	pub sha256: Option<HashMap<String, String>>,
+	pub minisign: Option<ModSignature>,
 }
 
 pub async fn get(mod_loader_id: &str) -> Result<ModDatabase> {
-	Ok(reqwest::get(format!(
-		"{URL_BASE}/{DATABASE_VERSION}/{mod_loader_id}.json"
-	))
-	.await?
-	.json::<ModDatabase>()
-	.await?)
+	remote_database::get(&format!("mod-db/{DATABASE_VERSION}/{mod_loader_id}.json")).await
 }
 
 impl DatabaseEntry {
@@ -152,19 +208,75 @@ impl ModGithubInfo {
 	}
 
 	pub async fn get_download(&self) -> Option<ModDownload> {
-		self.get_latest_tag().await.map(|latest_tag| ModDownload {
-			id: latest_tag.clone(),
+		let latest_tag = self.get_latest_tag().await?;
+		let url = format!(
+			"{}/download/{}/{}",
+			self.get_releases_url(),
+			latest_tag,
+			self.asset_name
+		);
+
+		self.get_release_download(latest_tag, url)
+	}
+
+	// Missing if the database has checksums for this mod but not for this release,
+	// since we'd have nothing to check the download against.
+	pub fn get_release_download(&self, tag: String, url: String) -> Option<ModDownload> {
+		let sha256 = match &self.sha256 {
+			Some(checksums) => match checksums.get(&tag) {
+				Some(sha256) => Some(sha256.clone()),
+				None if self.minisign.is_none() => {
+					error!(
+						"Refusing to download release `{tag}` of {}/{}, since the database has no checksum for it.",
+						self.user, self.repo
+					);
+					return None;
+				}
+				None => None,
+			},
+			None => None,
+		};
+
+		Some(ModDownload {
+			id: tag,
+			url,
 			root: self.root.clone(),
 			runnable: self.runnable.clone(),
-			url: format!(
-				"{}/download/{}/{}",
-				self.get_releases_url(),
-				latest_tag,
-				self.asset_name
-			),
+			sha256,
+			minisign: self.minisign.clone(),
 		})
 	}
 
+	// Unlike `get_latest_tag`, this goes through the GitHub API, which is rate limited.
+	// So we only do it when the user actually asks for the version list.
+	pub async fn get_versions(&self) -> Result<Vec<ModDownload>> {
+		let releases = reqwest::Client::new()
+			.get(format!(
+				"https://api.github.com/repos/{}/{}/releases",
+				self.user, self.repo
+			))
+			// GitHub's API rejects requests without a user agent.
+			.header(header::USER_AGENT, "rai-pal")
+			.send()
+			.await?
+			.error_for_status()?
+			.json::<Vec<GithubRelease>>()
+			.await?;
+
+		Ok(releases
+			.into_iter()
+			.filter(|release| !release.draft)
+			.filter_map(|release| {
+				let asset = release
+					.assets
+					.into_iter()
+					.find(|asset| asset.name == self.asset_name)?;
+
+				self.get_release_download(release.tag_name, asset.browser_download_url)
+			})
+			.collect())
+	}
+
 	fn get_releases_url(&self) -> String {
 		format!("https://github.com/{}/{}/releases", self.user, self.repo)
 	}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::{
+	installed_game::InstalledGame,
+	local_mod::{self, LocalMod},
+	mod_manifest::{self, Manifest},
+	result::{Error, Result},
+};
+
+fn get_installed_manifests(game: &InstalledGame) -> HashMap<String, Manifest> {
+	game.get_manifest_paths()
+		.iter()
+		.filter_map(|manifest_path| {
+			Some((
+				manifest_path.file_stem()?.to_str()?.to_string(),
+				mod_manifest::get(manifest_path)?,
+			))
+		})
+		.collect()
+}
+
+// Fails if any of the mods about to be installed is incompatible with another one,
+// or with a mod that's already installed in the game. Incompatibilities are checked both ways,
+// since it's enough for one of the mods to declare it.
+pub fn check_conflicts(game: &InstalledGame, mods_to_install: &local_mod::Map) -> Result {
+	let installed_manifests: HashMap<_, _> = get_installed_manifests(game)
+		.into_iter()
+		// Mods that are about to be installed will replace the installed version.
+		.filter(|(mod_id, _)| !mods_to_install.contains_key(mod_id))
+		.collect();
+
+	for (mod_id, local_mod) in mods_to_install {
+		for incompatibility in local_mod.get_incompatibilities() {
+			let is_installed_conflict = installed_manifests
+				.get(&incompatibility.id)
+				.is_some_and(|manifest| incompatibility.is_satisfied_by(Some(&manifest.version)));
+
+			let is_pending_conflict = mods_to_install
+				.get(&incompatibility.id)
+				.is_some_and(|other_mod| incompatibility.is_satisfied_by(other_mod.get_version()));
+
+			if is_installed_conflict || is_pending_conflict {
+				return Err(Error::ModConflict(
+					mod_id.clone(),
+					incompatibility.id.clone(),
+				));
+			}
+		}
+
+		for (installed_mod_id, installed_manifest) in &installed_manifests {
+			let is_conflict =
+				installed_manifest
+					.incompatibilities
+					.iter()
+					.flatten()
+					.any(|incompatibility| {
+						incompatibility.id == *mod_id
+							&& incompatibility.is_satisfied_by(local_mod.get_version())
+					});
+
+			if is_conflict {
+				return Err(Error::ModConflict(mod_id.clone(), installed_mod_id.clone()));
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn visit(
+	local_mod: &LocalMod,
+	mods_to_install: &local_mod::Map,
+	visiting: &mut Vec<String>,
+	install_order: &mut Vec<LocalMod>,
+) -> Result {
+	let mod_id = &local_mod.common.id;
+
+	if install_order
+		.iter()
+		.any(|ordered_mod| ordered_mod.common.id == *mod_id)
+	{
+		return Ok(());
+	}
+
+	if visiting.contains(mod_id) {
+		visiting.push(mod_id.clone());
+		return Err(Error::ModDependencyCycle(visiting.join(" -> ")));
+	}
+
+	visiting.push(mod_id.clone());
+
+	for dependency in local_mod.get_dependencies() {
+		// Dependencies that aren't in the install list are already satisfied by the game.
+		if let Some(dependency_mod) = mods_to_install.get(&dependency.id) {
+			visit(dependency_mod, mods_to_install, visiting, install_order)?;
+		}
+	}
+
+	visiting.pop();
+	install_order.push(local_mod.clone());
+
+	Ok(())
+}
+
+// Sorts the mods so that each mod comes after all of its dependencies.
+pub fn get_install_order(
+	root_mod: &LocalMod,
+	mods_to_install: &local_mod::Map,
+) -> Result<Vec<LocalMod>> {
+	let mut install_order = Vec::new();
+
+	visit(
+		root_mod,
+		mods_to_install,
+		&mut Vec::new(),
+		&mut install_order,
+	)?;
+
+	Ok(install_order)
+}
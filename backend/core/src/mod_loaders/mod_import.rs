@@ -0,0 +1,366 @@
+use std::{
+	ffi::OsStr,
+	fs,
+	path::{Component, Path, PathBuf},
+};
+
+use log::error;
+use rai_pal_proc_macros::{serializable_enum, serializable_struct};
+
+use super::{
+	bepinex::BepInEx,
+	mod_database::RunnableModData,
+	mod_loader::{self, ModLoaderActions, ModLoaderStatic},
+	runnable_loader::RunnableLoader,
+};
+use crate::{
+	archive,
+	files::copy_dir_all,
+	game_engines::{game_engine::EngineBrand, unity::UnityScriptingBackend},
+	game_mod::CommonModData,
+	local_mod::{self, LocalMod},
+	maps::TryGettable,
+	mod_manifest::Manifest,
+	paths,
+	result::{Error, Result},
+};
+
+// Folders BepInEx loads mods from. Anything else in an imported mod gets ignored by the BepInEx loader.
+const BEPINEX_FOLDER_NAMES: [&str; 2] = ["plugins", "patchers"];
+
+// Assemblies referenced by IL2CPP plugins, but never by Mono ones.
+const IL2CPP_MARKERS: [&str; 3] = ["Il2CppInterop", "UnhollowerBaseLib", "BepInEx.Unity.IL2CPP"];
+
+#[serializable_enum]
+pub enum ModImportLayout {
+	// Already has plugins and/or patchers folders.
+	BepInExFolders,
+	// Just a bunch of dlls, which we treat as plugins.
+	LoosePlugins,
+	// Has executables, but no dlls. Mods with both are only runnable if the user says so.
+	Runnable,
+}
+
+#[serializable_struct]
+pub struct ModImportPreview {
+	pub id: String,
+	pub loader_id: String,
+	pub layout: ModImportLayout,
+	pub unity_backend: Option<UnityScriptingBackend>,
+	// Relative to the mod folder. Runnable mods need one of these picked as the runnable path.
+	pub executables: Vec<String>,
+}
+
+#[serializable_struct]
+pub struct ModImportOptions {
+	// Archive or folder with the mod files.
+	pub source_path: PathBuf,
+	// Anything left empty gets inferred from the mod files, like in the preview.
+	pub id: Option<String>,
+	pub title: Option<String>,
+	pub version: Option<String>,
+	pub layout: Option<ModImportLayout>,
+	pub unity_backend: Option<UnityScriptingBackend>,
+	pub runnable: Option<RunnableModData>,
+}
+
+// Removes the staged files once we're done with them, whatever happened in between.
+struct StagingFolder {
+	path: PathBuf,
+}
+
+impl StagingFolder {
+	fn new(id: &str) -> Result<Self> {
+		Ok(Self {
+			path: paths::installed_mods_path()?.join("imports").join(id),
+		})
+	}
+}
+
+impl Drop for StagingFolder {
+	fn drop(&mut self) {
+		if self.path.exists() {
+			if let Err(err) = fs::remove_dir_all(&self.path) {
+				error!(
+					"Failed to remove import staging folder `{}`: {err}",
+					self.path.display()
+				);
+			}
+		}
+	}
+}
+
+fn get_default_id(source_path: &Path) -> Result<String> {
+	Ok(paths::file_name_without_extension(source_path)?.to_string())
+}
+
+fn stage(source_path: &Path, staging_path: &Path) -> Result {
+	if staging_path.exists() {
+		fs::remove_dir_all(staging_path)?;
+	}
+
+	if source_path.is_dir() {
+		copy_dir_all(source_path, staging_path)?;
+	} else {
+		archive::extract(source_path, staging_path)?;
+	}
+
+	Ok(())
+}
+
+fn find_files(folder_path: &Path, extension: &str) -> Vec<PathBuf> {
+	paths::glob_path(&folder_path.join("**").join(format!("*.{extension}")))
+}
+
+// Archives often wrap everything in a single folder (or in a BepInEx folder),
+// so we look for the folder where the actual mod starts.
+fn find_mod_root(staging_path: &Path) -> Result<PathBuf> {
+	let mut mod_root = staging_path.to_path_buf();
+
+	loop {
+		if BEPINEX_FOLDER_NAMES
+			.iter()
+			.any(|folder_name| mod_root.join(folder_name).is_dir())
+		{
+			return Ok(mod_root);
+		}
+
+		let bepinex_folder = mod_root.join("BepInEx");
+		if bepinex_folder.is_dir() {
+			mod_root = bepinex_folder;
+			continue;
+		}
+
+		let children = fs::read_dir(&mod_root)?.collect::<std::io::Result<Vec<_>>>()?;
+		match children.as_slice() {
+			[child] if child.file_type()?.is_dir() => mod_root = child.path(),
+			_ => return Ok(mod_root),
+		}
+	}
+}
+
+fn get_layout(mod_root: &Path) -> Option<ModImportLayout> {
+	if BEPINEX_FOLDER_NAMES
+		.iter()
+		.any(|folder_name| mod_root.join(folder_name).is_dir())
+	{
+		Some(ModImportLayout::BepInExFolders)
+	} else if !find_files(mod_root, "dll").is_empty() {
+		Some(ModImportLayout::LoosePlugins)
+	} else if !find_files(mod_root, "exe").is_empty() {
+		Some(ModImportLayout::Runnable)
+	} else {
+		None
+	}
+}
+
+fn infer_unity_backend(mod_root: &Path) -> Option<UnityScriptingBackend> {
+	let assemblies = find_files(mod_root, "dll");
+	if assemblies.is_empty() {
+		return None;
+	}
+
+	// Plugin dlls contain the names of the assemblies they reference,
+	// so a plain text search is enough to tell the two apart.
+	let is_il2cpp = assemblies.iter().any(|assembly_path| {
+		fs::read(assembly_path).is_ok_and(|bytes| {
+			let contents = String::from_utf8_lossy(&bytes);
+			IL2CPP_MARKERS
+				.iter()
+				.any(|marker| contents.contains(marker))
+		})
+	});
+
+	Some(if is_il2cpp {
+		UnityScriptingBackend::Il2Cpp
+	} else {
+		UnityScriptingBackend::Mono
+	})
+}
+
+fn get_executables(mod_root: &Path) -> Vec<String> {
+	find_files(mod_root, "exe")
+		.iter()
+		.filter_map(|executable_path| {
+			Some(
+				executable_path
+					.strip_prefix(mod_root)
+					.ok()?
+					.to_string_lossy()
+					.replace('\\', "/"),
+			)
+		})
+		.collect()
+}
+
+const fn get_loader_id(layout: ModImportLayout) -> &'static str {
+	match layout {
+		ModImportLayout::BepInExFolders | ModImportLayout::LoosePlugins => BepInEx::ID,
+		ModImportLayout::Runnable => RunnableLoader::ID,
+	}
+}
+
+fn analyze(id: &str, mod_root: &Path) -> Result<ModImportPreview> {
+	let layout =
+		get_layout(mod_root).ok_or_else(|| Error::ModImportLayoutUnknown(id.to_string()))?;
+
+	Ok(ModImportPreview {
+		id: id.to_string(),
+		loader_id: get_loader_id(layout).to_string(),
+		layout,
+		unity_backend: if layout == ModImportLayout::Runnable {
+			None
+		} else {
+			infer_unity_backend(mod_root)
+		},
+		executables: get_executables(mod_root),
+	})
+}
+
+// Tells the user what we figured out about the mod, so they can correct it before importing.
+pub fn preview(source_path: &Path) -> Result<ModImportPreview> {
+	let id = get_default_id(source_path)?;
+	let staging_folder = StagingFolder::new(&id)?;
+
+	stage(source_path, &staging_folder.path)?;
+	analyze(&id, &find_mod_root(&staging_folder.path)?)
+}
+
+// Moves the files into the folder structure the BepInEx loader expects.
+fn arrange_bepinex_files(mod_root: &Path, layout: ModImportLayout, target_path: &Path) -> Result {
+	fs::create_dir_all(target_path)?;
+
+	if layout == ModImportLayout::LoosePlugins {
+		return copy_dir_all(mod_root, target_path.join("plugins"));
+	}
+
+	for folder_name in BEPINEX_FOLDER_NAMES {
+		let folder_path = mod_root.join(folder_name);
+		if folder_path.is_dir() {
+			copy_dir_all(folder_path, target_path.join(folder_name))?;
+		}
+	}
+
+	Ok(())
+}
+
+fn import_staged(
+	options: &ModImportOptions,
+	id: &str,
+	staging_path: &Path,
+	mod_loaders: &mod_loader::Map,
+) -> Result<LocalMod> {
+	let mod_root = find_mod_root(staging_path)?;
+	let preview = analyze(id, &mod_root)?;
+	// Picking an executable means the user wants a runnable mod, even if it also has dlls.
+	let layout = options.layout.unwrap_or_else(|| {
+		if options.runnable.is_some() && !preview.executables.is_empty() {
+			ModImportLayout::Runnable
+		} else {
+			preview.layout
+		}
+	});
+	let loader_id = get_loader_id(layout);
+	let is_runnable = layout == ModImportLayout::Runnable;
+
+	let runnable = if is_runnable {
+		let runnable = options
+			.runnable
+			.clone()
+			.ok_or_else(|| Error::ModImportExecutableMissing(id.to_string()))?;
+
+		// Runnable paths get joined to the mod folder when starting the mod, so they can't point outside of it.
+		if !Path::new(&runnable.path)
+			.components()
+			.all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+		{
+			return Err(Error::ModImportRunnablePathInvalid(
+				id.to_string(),
+				runnable.path,
+			));
+		}
+
+		if !mod_root.join(&runnable.path).is_file() {
+			return Err(Error::ModImportExecutableMissing(id.to_string()));
+		}
+
+		Some(runnable)
+	} else {
+		None
+	};
+
+	let unity_backend = if is_runnable {
+		None
+	} else {
+		Some(
+			options
+				.unity_backend
+				.or(preview.unity_backend)
+				.ok_or_else(|| Error::UnityBackendUnknown(id.to_string()))?,
+		)
+	};
+
+	let engine = unity_backend.map(|_| EngineBrand::Unity);
+
+	let mod_loader = mod_loaders.try_get(loader_id)?;
+	let target_path = mod_loader.get_mod_path(&CommonModData {
+		id: id.to_string(),
+		engine,
+		engine_version_range: None,
+		unity_backend,
+		architecture: None,
+		loader_id: loader_id.to_string(),
+	})?;
+
+	if target_path.exists() {
+		return Err(Error::ModAlreadyExists(id.to_string()));
+	}
+
+	if is_runnable {
+		copy_dir_all(&mod_root, &target_path)?;
+	} else {
+		arrange_bepinex_files(&mod_root, layout, &target_path)?;
+	}
+
+	fs::write(
+		local_mod::get_manifest_path(&target_path),
+		serde_json::to_string_pretty(&Manifest {
+			title: options.title.clone(),
+			// Imported mods don't come from anywhere with versions, so we can't update them anyway.
+			version: options
+				.version
+				.clone()
+				.unwrap_or_else(|| "0.0.0".to_string()),
+			runnable,
+			engine,
+			engine_version_range: None,
+			unity_backend,
+			architecture: None,
+			dependencies: None,
+			incompatibilities: None,
+			bepinex_builds: None,
+			config_file: None,
+			files: None,
+		})?,
+	)?;
+
+	LocalMod::new(loader_id, &target_path, engine, unity_backend)
+}
+
+// Copies a mod that isn't in the database into the mod loader folders, as if it had been downloaded.
+pub fn import(options: &ModImportOptions, mod_loaders: &mod_loader::Map) -> Result<LocalMod> {
+	let id = match &options.id {
+		Some(id) => id.clone(),
+		None => get_default_id(&options.source_path)?,
+	};
+
+	// The id ends up being a folder name, so it can't be allowed to point anywhere else.
+	if Path::new(&id).file_name() != Some(OsStr::new(&id)) {
+		return Err(Error::ModImportInvalidId(id));
+	}
+
+	let staging_folder = StagingFolder::new(&id)?;
+
+	stage(&options.source_path, &staging_folder.path)?;
+	import_staged(options, &id, &staging_folder.path, mod_loaders)
+}
@@ -0,0 +1,290 @@
+use std::{
+	fs,
+	path::{Component, Path, PathBuf},
+};
+
+use log::{error, info};
+use rai_pal_proc_macros::{serializable_enum, serializable_struct};
+
+use super::{
+	mod_loader::{self, ModLoaderActions},
+	mod_verification,
+};
+use crate::{
+	installed_game::InstalledGame, local_mod, maps::TryGettable, mod_manifest, mod_versions, paths,
+	result::Result,
+};
+
+#[serializable_struct]
+pub struct InstalledFile {
+	pub path: PathBuf,
+	pub sha256: String,
+}
+
+// Written to the game's mods folder every time a mod loader gets deployed to it.
+#[serializable_struct]
+pub struct LoaderManifest {
+	pub version: Option<String>,
+	pub files: Vec<InstalledFile>,
+}
+
+#[serializable_enum]
+pub enum FileIssueKind {
+	Missing,
+	Modified,
+}
+
+#[serializable_struct]
+pub struct FileIssue {
+	pub path: PathBuf,
+	pub kind: FileIssueKind,
+}
+
+#[serializable_struct]
+pub struct LoaderIntegrity {
+	pub loader_id: String,
+	// Version the manifest says was deployed, and the version that's there now.
+	pub expected_version: Option<String>,
+	pub installed_version: Option<String>,
+	pub issues: Vec<FileIssue>,
+}
+
+#[serializable_struct]
+pub struct ModIntegrity {
+	pub mod_id: String,
+	pub issues: Vec<FileIssue>,
+}
+
+// Only has the mod loaders and mods that have something wrong with them.
+#[serializable_struct]
+pub struct GameIntegrity {
+	pub loaders: Vec<LoaderIntegrity>,
+	pub mods: Vec<ModIntegrity>,
+}
+
+impl LoaderIntegrity {
+	fn is_broken(&self) -> bool {
+		!self.issues.is_empty()
+			|| (self.expected_version.is_some() && self.installed_version != self.expected_version)
+	}
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result {
+	if path.is_dir() {
+		for entry in fs::read_dir(path)? {
+			collect_files(&entry?.path(), files)?;
+		}
+	} else if path.is_file() {
+		files.push(path.to_path_buf());
+	}
+
+	Ok(())
+}
+
+// Config files are meant to be edited by users, and can be shared by several mods,
+// so we don't consider changes to them a problem. BepInEx keeps them all in its config folder, whatever the format.
+fn is_config(path: &Path) -> bool {
+	let components: Vec<_> = path.components().map(Component::as_os_str).collect();
+
+	path.extension().is_some_and(|extension| extension == "cfg")
+		|| components
+			.windows(2)
+			.any(|pair| pair[0] == "BepInEx" && pair[1] == "config")
+}
+
+// Hashes every file in the given paths, going into folders.
+pub fn get_installed_files(paths: &[PathBuf]) -> Result<Vec<InstalledFile>> {
+	let mut file_paths = Vec::new();
+	for path in paths {
+		collect_files(path, &mut file_paths)?;
+	}
+
+	file_paths
+		.into_iter()
+		.filter(|file_path| !is_config(file_path))
+		.map(|file_path| {
+			Ok(InstalledFile {
+				sha256: mod_verification::get_sha256(&file_path)?,
+				path: file_path,
+			})
+		})
+		.collect()
+}
+
+fn get_file_issues(files: &[InstalledFile]) -> Vec<FileIssue> {
+	files
+		.iter()
+		.filter_map(|file| {
+			let kind = if !file.path.is_file() {
+				FileIssueKind::Missing
+			} else if !mod_verification::get_sha256(&file.path)
+				.is_ok_and(|sha256| sha256 == file.sha256)
+			{
+				FileIssueKind::Modified
+			} else {
+				return None;
+			};
+
+			Some(FileIssue {
+				path: file.path.clone(),
+				kind,
+			})
+		})
+		.collect()
+}
+
+fn get_loader_manifest_path(game: &InstalledGame, loader_id: &str) -> Result<PathBuf> {
+	Ok(game
+		.get_installed_mods_folder()?
+		.join("loader-manifests")
+		.join(format!("{loader_id}.json")))
+}
+
+pub fn write_loader_manifest<TModLoader: ModLoaderActions + ?Sized>(
+	mod_loader: &TModLoader,
+	game: &InstalledGame,
+) -> Result {
+	let deployed_paths: Vec<PathBuf> = mod_loader
+		.get_install_preview(game)?
+		.into_iter()
+		.map(|entry| entry.path)
+		.collect();
+
+	let manifest = LoaderManifest {
+		version: mod_loader.get_installed_version(game),
+		files: get_installed_files(&deployed_paths)?,
+	};
+
+	let manifest_path = get_loader_manifest_path(game, &mod_loader.get_data().id)?;
+	fs::create_dir_all(paths::path_parent(&manifest_path)?)?;
+
+	Ok(fs::write(
+		manifest_path,
+		serde_json::to_string_pretty(&manifest)?,
+	)?)
+}
+
+fn read_loader_manifest(manifest_path: &Path) -> Result<LoaderManifest> {
+	Ok(serde_json::from_str(&fs::read_to_string(manifest_path)?)?)
+}
+
+// Files recorded the last time this mod loader was deployed to the game, if it ever was.
+pub fn get_loader_manifest(
+	game: &InstalledGame,
+	loader_id: &str,
+) -> Result<Option<LoaderManifest>> {
+	let manifest_path = get_loader_manifest_path(game, loader_id)?;
+	if !manifest_path.is_file() {
+		return Ok(None);
+	}
+
+	Ok(Some(read_loader_manifest(&manifest_path)?))
+}
+
+fn verify_loaders(
+	game: &InstalledGame,
+	mod_loaders: &mod_loader::Map,
+) -> Result<Vec<LoaderIntegrity>> {
+	let mut loaders = Vec::new();
+
+	for manifest_path in paths::glob_path(&get_loader_manifest_path(game, "*")?) {
+		let Some(loader_id) = manifest_path
+			.file_stem()
+			.and_then(|file_stem| file_stem.to_str())
+		else {
+			continue;
+		};
+
+		let manifest = match read_loader_manifest(&manifest_path) {
+			Ok(manifest) => manifest,
+			Err(err) => {
+				error!(
+					"Failed to read loader manifest {}: {err}",
+					manifest_path.display()
+				);
+				continue;
+			}
+		};
+
+		let loader_integrity = LoaderIntegrity {
+			loader_id: loader_id.to_string(),
+			installed_version: mod_loaders
+				.get(loader_id)
+				.and_then(|mod_loader| mod_loader.get_installed_version(game)),
+			expected_version: manifest.version,
+			issues: get_file_issues(&manifest.files),
+		};
+
+		if loader_integrity.is_broken() {
+			loaders.push(loader_integrity);
+		}
+	}
+
+	Ok(loaders)
+}
+
+fn verify_mods(game: &InstalledGame) -> Vec<ModIntegrity> {
+	// Disabled mods have their files parked somewhere else, so they'd always look like they're missing.
+	let disabled_mods = game.get_disabled_mods();
+
+	game.get_manifest_paths()
+		.iter()
+		.filter_map(|manifest_path| {
+			let mod_id = manifest_path.file_stem()?.to_str()?.to_string();
+			if disabled_mods.contains(&mod_id) {
+				return None;
+			}
+
+			let manifest = mod_manifest::get(manifest_path)?;
+			let issues = get_file_issues(&manifest.files.unwrap_or_default());
+
+			if issues.is_empty() {
+				None
+			} else {
+				Some(ModIntegrity { mod_id, issues })
+			}
+		})
+		.collect()
+}
+
+// Compares the files deployed to the game with the hashes we recorded when deploying them.
+// Mods installed before hashes were recorded are always considered intact.
+pub fn verify(game: &InstalledGame, mod_loaders: &mod_loader::Map) -> Result<GameIntegrity> {
+	Ok(GameIntegrity {
+		loaders: verify_loaders(game, mod_loaders)?,
+		mods: verify_mods(game),
+	})
+}
+
+// Redeploys only the mod loaders and mods that have something wrong with them.
+pub async fn repair(
+	game: &InstalledGame,
+	mod_loaders: &mod_loader::Map,
+	local_mods: &local_mod::Map,
+) -> Result<GameIntegrity> {
+	let integrity = verify(game, mod_loaders)?;
+
+	for loader_integrity in &integrity.loaders {
+		info!(
+			"Repairing mod loader {} in game {}",
+			loader_integrity.loader_id, game.id
+		);
+
+		let mod_loader = mod_loaders.try_get(&loader_integrity.loader_id)?;
+		mod_loader.install(game)?;
+		write_loader_manifest(mod_loader, game)?;
+	}
+
+	for mod_integrity in &integrity.mods {
+		info!("Repairing mod {} in game {}", mod_integrity.mod_id, game.id);
+
+		let local_mod =
+			mod_versions::get_pinned_local_mod(game, local_mods.try_get(&mod_integrity.mod_id)?)?;
+		let mod_loader = mod_loaders.try_get(&local_mod.common.loader_id)?;
+
+		mod_loader.uninstall_mod(game, &local_mod).await?;
+		mod_loader.install_single_mod(game, &local_mod).await?;
+	}
+
+	verify(game, mod_loaders)
+}
@@ -1,24 +1,31 @@
 use std::{
 	collections::HashMap,
-	fs::{self, File},
+	fs,
 	path::{Path, PathBuf},
 };
 
 use enum_dispatch::enum_dispatch;
 use log::error;
 use rai_pal_proc_macros::serializable_struct;
-use zip::ZipArchive;
 
-use super::{bepinex::BepInEx, mod_database, runnable_loader::RunnableLoader};
+use super::{
+	bepinex::BepInEx,
+	descriptor_loader::{self, DescriptorLoader},
+	mod_dependencies, mod_integrity, mod_sources, mod_verification,
+	runnable_loader::RunnableLoader,
+};
 use crate::{
-	files,
+	archive::{self, ArchiveEntry},
+	download::{self, DownloadProgress},
+	game::Game,
 	game_mod::CommonModData,
 	installed_game::InstalledGame,
 	local_mod::{self, LocalMod, ModKind},
-	mod_loaders::mod_database::ModDatabase,
-	mod_manifest,
+	mod_compatibility,
+	mod_loaders::mod_database::ModDownload,
+	mod_manifest, mod_pins, mod_profiles, mod_versions,
 	paths::{self, open_folder_or_parent},
-	remote_mod::{RemoteMod, RemoteModData},
+	remote_mod::{self, RemoteMod, RemoteModData},
 	result::{Error, Result},
 };
 
@@ -34,6 +41,7 @@ pub struct ModLoaderData {
 pub enum ModLoader {
 	BepInEx,
 	RunnableLoader,
+	DescriptorLoader,
 }
 
 #[enum_dispatch(ModLoader)]
@@ -41,6 +49,7 @@ pub trait ModLoaderActions {
 	fn install(&self, game: &InstalledGame) -> Result;
 	async fn install_mod_inner(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result;
 	async fn uninstall_mod(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result;
+	fn set_mod_enabled(&self, game: &InstalledGame, local_mod: &LocalMod, enabled: bool) -> Result;
 	async fn run_without_game(&self, local_mod: &LocalMod) -> Result;
 	fn configure_mod(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result;
 	fn open_installed_mod_folder(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result;
@@ -48,25 +57,192 @@ pub trait ModLoaderActions {
 	fn get_mod_path(&self, mod_data: &CommonModData) -> Result<PathBuf>;
 	fn get_local_mods(&self) -> Result<HashMap<String, LocalMod>>;
 
+	// Lists the files that installing the mod loader itself would write to the game,
+	// with absolute paths, so the user can see what's going to happen before it happens.
+	fn get_install_preview(&self, _game: &InstalledGame) -> Result<Vec<ArchiveEntry>> {
+		Ok(Vec::new())
+	}
+
+	// Files and folders in the game where this mod's files end up, so we can check if they're still intact.
+	fn get_deployed_mod_paths(&self, _game: &InstalledGame, _mod_id: &str) -> Result<Vec<PathBuf>> {
+		Ok(Vec::new())
+	}
+
+	// Mod loaders added with a descriptor aren't in the official database, so there's no point asking it.
+	fn has_official_database(&self) -> bool {
+		true
+	}
+
+	// Version of the mod loader deployed to the game, if it's installed and we know how to tell.
+	fn get_installed_version(&self, _game: &InstalledGame) -> Option<String> {
+		None
+	}
+
 	fn open_folder(&self) -> Result {
 		open_folder_or_parent(&self.get_data().path)
 	}
 
-	async fn install_mod(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
+	async fn install_mod<F>(
+		&self,
+		game: &InstalledGame,
+		local_mod: &LocalMod,
+		remote_mods: &remote_mod::Map,
+		on_progress: &F,
+	) -> Result
+	where
+		F: Fn(DownloadProgress) + Send + Sync,
+	{
+		// Pinned versions can have different dependencies and requirements than the latest one,
+		// so everything below works with the version that's actually going to be installed.
+		let local_mod = mod_versions::get_pinned_local_mod(game, local_mod)?;
+
+		let mods_to_install = self
+			.resolve_dependencies(game, &local_mod, remote_mods, on_progress)
+			.await?;
+
+		for mod_to_install in &mods_to_install {
+			let compatibility = mod_compatibility::evaluate(&mod_to_install.common, game);
+			if compatibility.is_incompatible() {
+				return Err(Error::ModIncompatibleWithGame(
+					mod_to_install.common.id.clone(),
+					compatibility
+						.issues
+						.iter()
+						.map(ToString::to_string)
+						.collect::<Vec<_>>()
+						.join(", "),
+				));
+			}
+		}
+
+		for mod_to_install in mods_to_install {
+			// Uninstall mod if it already exists, in case there are conflicting leftover files when updating.
+			self.uninstall_mod(game, &mod_to_install).await?;
+
+			self.install_single_mod(game, &mod_to_install).await?;
+		}
+
+		Ok(())
+	}
+
+	async fn install_single_mod(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
 		self.install_mod_inner(game, local_mod).await?;
 
 		if self.get_data().kind != ModKind::Runnable {
+			// Installing a mod also deploys the mod loader, so both get their files recorded.
+			mod_integrity::write_loader_manifest(self, game)?;
+
 			if let Some(manifest) = &local_mod.data.manifest {
 				let manifest_path = game.get_installed_mod_manifest_path(&local_mod.common.id)?;
 				fs::create_dir_all(paths::path_parent(&manifest_path)?)?;
-				let manifest_contents = serde_json::to_string_pretty(manifest)?;
+				let manifest = mod_manifest::Manifest {
+					files: Some(mod_integrity::get_installed_files(
+						&self.get_deployed_mod_paths(game, &local_mod.common.id)?,
+					)?),
+					..manifest.clone()
+				};
+				let manifest_contents = serde_json::to_string_pretty(&manifest)?;
 				fs::write(manifest_path, manifest_contents)?;
 			}
+
+			// Freshly installed mods are enabled, so the active profile should reflect that.
+			let mut profiles = mod_profiles::read(game);
+			if profiles.set_enabled_in_active_profile(&local_mod.common.id, true) {
+				mod_profiles::write(game, &profiles)?;
+			}
 		}
 
 		Ok(())
 	}
 
+	// Returns the mods that need to be installed for this mod to work, including the mod itself.
+	// Dependencies that are missing locally get downloaded from this mod loader's database.
+	// Dependencies the game has pinned are resolved to their pinned version.
+	// The result is sorted so that dependencies come before the mods that need them.
+	async fn resolve_dependencies<F>(
+		&self,
+		game: &InstalledGame,
+		local_mod: &LocalMod,
+		remote_mods: &remote_mod::Map,
+		on_progress: &F,
+	) -> Result<Vec<LocalMod>>
+	where
+		F: Fn(DownloadProgress) + Send + Sync,
+	{
+		let installed_mods = game.get_available_mods();
+		let mut local_mods = self.get_local_mods()?;
+		let mut mods_to_install = HashMap::from([(local_mod.common.id.clone(), local_mod.clone())]);
+		let mut pending_mods = vec![local_mod.clone()];
+
+		while let Some(pending_mod) = pending_mods.pop() {
+			for dependency in pending_mod.get_dependencies() {
+				if mods_to_install.contains_key(&dependency.id) {
+					continue;
+				}
+
+				if installed_mods
+					.get(&dependency.id)
+					.is_some_and(|version| dependency.is_satisfied_by(Some(version)))
+				{
+					continue;
+				}
+
+				let get_pinned_dependency = |available_mods: &HashMap<String, LocalMod>| {
+					available_mods
+						.get(&dependency.id)
+						.map(|dependency_mod| {
+							mod_versions::get_pinned_local_mod(game, dependency_mod)
+						})
+						.transpose()
+				};
+				let is_satisfied = |dependency_mod: &Option<LocalMod>| {
+					dependency_mod.as_ref().is_some_and(|dependency_mod| {
+						dependency.is_satisfied_by(dependency_mod.get_version())
+					})
+				};
+				let dependency_not_found = || {
+					Error::ModDependencyNotFound(
+						pending_mod.common.id.clone(),
+						dependency.to_string(),
+					)
+				};
+
+				let mut dependency_mod = get_pinned_dependency(&local_mods)?;
+
+				if !is_satisfied(&dependency_mod) {
+					let remote_dependency = remote_mods
+						.get(&dependency.id)
+						.filter(|remote_mod| {
+							remote_mod.common.loader_id == self.get_data().id
+								&& remote_mod.data.latest_version.as_ref().is_some_and(
+									|latest_version| {
+										dependency.is_satisfied_by(Some(&latest_version.id))
+									},
+								)
+						})
+						.ok_or_else(dependency_not_found)?;
+
+					self.download_mod(remote_dependency, on_progress).await?;
+					local_mods = self.get_local_mods()?;
+					dependency_mod = get_pinned_dependency(&local_mods)?;
+
+					// Happens when the game is pinned to a version that doesn't satisfy the dependency.
+					if !is_satisfied(&dependency_mod) {
+						return Err(dependency_not_found());
+					}
+				}
+
+				let dependency_mod = dependency_mod.ok_or_else(dependency_not_found)?;
+				pending_mods.push(dependency_mod.clone());
+				mods_to_install.insert(dependency.id.clone(), dependency_mod);
+			}
+		}
+
+		mod_dependencies::check_conflicts(game, &mods_to_install)?;
+
+		mod_dependencies::get_install_order(local_mod, &mods_to_install)
+	}
+
 	async fn get_remote_mods<F>(&self, error_handler: F) -> HashMap<String, RemoteMod>
 	where
 		F: Fn(Error) + Send,
@@ -74,89 +250,184 @@ pub trait ModLoaderActions {
 		let data = self.get_data();
 		let loader_id = &data.id;
 
-		let database = mod_database::get(loader_id).await.unwrap_or_else(|error| {
-			error_handler(error);
-			ModDatabase { mods: Vec::new() }
-		});
+		let databases =
+			mod_sources::get_databases(loader_id, self.has_official_database(), error_handler)
+				.await;
 
 		let mut mods_map = HashMap::new();
 
-		for database_mod in database.mods {
-			let remote_mod = RemoteMod {
-				common: CommonModData {
-					id: database_mod.id.clone(),
-					engine: database_mod.engine,
-					engine_version_range: database_mod.engine_version_range.clone(),
-					unity_backend: database_mod.unity_backend,
-					loader_id: loader_id.clone(),
-				},
-				data: RemoteModData {
-					author: database_mod.author.clone(),
-					description: database_mod.description.clone(),
-					source_code: database_mod.source_code.clone(),
-					title: database_mod.title.clone(),
-					latest_version: database_mod.get_download().await,
-					deprecated: database_mod.deprecated.unwrap_or(false),
-				},
-			};
-			mods_map.insert(database_mod.id.clone(), remote_mod);
+		// Databases are sorted by priority, so mods from higher priority sources replace the ones before them.
+		for (source, database) in databases {
+			for database_mod in database.mods {
+				let remote_mod = RemoteMod {
+					common: CommonModData {
+						id: database_mod.id.clone(),
+						engine: database_mod.engine,
+						engine_version_range: database_mod.engine_version_range.clone(),
+						unity_backend: database_mod.unity_backend,
+						architecture: database_mod.architecture,
+						loader_id: loader_id.clone(),
+					},
+					data: RemoteModData {
+						author: database_mod.author.clone(),
+						description: database_mod.description.clone(),
+						source_code: database_mod.source_code.clone(),
+						title: database_mod.title.clone(),
+						latest_version: database_mod.get_download().await,
+						deprecated: database_mod.deprecated.unwrap_or(false),
+						dependencies: database_mod.dependencies.clone().unwrap_or_default(),
+						incompatibilities: database_mod
+							.incompatibilities
+							.clone()
+							.unwrap_or_default(),
+						bepinex_builds: database_mod.bepinex_builds.clone().unwrap_or_default(),
+						config_file: database_mod.config_file.clone(),
+						versions: database_mod.versions.clone().unwrap_or_default(),
+						github: database_mod.github.clone(),
+						source: source.clone(),
+					},
+				};
+				mods_map.insert(database_mod.id.clone(), remote_mod);
+			}
 		}
 
 		mods_map
 	}
 
-	async fn download_mod(&self, remote_mod: &RemoteMod) -> Result {
-		if let Some(latest_version) = &remote_mod.data.latest_version {
-			let target_path = self.get_mod_path(&remote_mod.common)?;
-			let mod_loader_data = self.get_data();
-			let mod_id = &remote_mod.common.id;
-			let downloads_path = paths::installed_mods_path()?
-				.join(&mod_loader_data.id)
-				.join("downloads");
-
-			let response = reqwest::get(&latest_version.url).await?;
+	async fn download_mod<F>(&self, remote_mod: &RemoteMod, on_progress: F) -> Result
+	where
+		F: Fn(DownloadProgress) + Send + Sync,
+	{
+		let latest_version = remote_mod
+			.data
+			.latest_version
+			.as_ref()
+			.ok_or_else(|| Error::ModDownloadNotAvailable(remote_mod.common.id.clone()))?;
+
+		let version_path = self
+			.download_mod_version(remote_mod, latest_version, on_progress)
+			.await?;
+
+		// The regular mods folder always has the latest version, since that's where local mods are read from.
+		// Whatever version was there before goes to the versions folder, so games pinned to it keep working.
+		let target_path = self.get_mod_path(&remote_mod.common)?;
+		if target_path.exists() {
+			let previous_version_path =
+				mod_manifest::get(&local_mod::get_manifest_path(&target_path))
+					.filter(|manifest| manifest.version != latest_version.id)
+					.map(|manifest| {
+						mod_versions::get_version_path(
+							&remote_mod.common.loader_id,
+							&remote_mod.common.id,
+							&manifest.version,
+						)
+					})
+					.transpose()?;
+
+			match previous_version_path {
+				Some(previous_version_path) if !previous_version_path.exists() => {
+					fs::rename(&target_path, previous_version_path)?;
+				}
+				_ => fs::remove_dir_all(&target_path)?,
+			}
+		}
+		fs::create_dir_all(paths::path_parent(&target_path)?)?;
+		fs::rename(version_path, target_path)?;
 
-			fs::create_dir_all(&downloads_path)?;
+		Ok(())
+	}
 
-			let zip_path = downloads_path.join(format!("{mod_id}.zip"));
+	// Downloads the given version into the versions folder, next to any other versions of the same mod.
+	// Returns the path the version was saved to.
+	async fn download_mod_version<F>(
+		&self,
+		remote_mod: &RemoteMod,
+		version: &ModDownload,
+		on_progress: F,
+	) -> Result<PathBuf>
+	where
+		F: Fn(DownloadProgress) + Send + Sync,
+	{
+		let mod_loader_data = self.get_data();
+		let mod_id = &remote_mod.common.id;
+		let version_path =
+			mod_versions::get_version_path(&mod_loader_data.id, mod_id, &version.id)?;
+		let downloads_path = paths::installed_mods_path()?
+			.join(&mod_loader_data.id)
+			.join("downloads");
+
+		// Everything related to this download stays in its own folder,
+		// so we can name the downloaded file after the url without clashing with other mods.
+		let mod_downloads_path = downloads_path.join(mod_id);
+		fs::create_dir_all(&mod_downloads_path)?;
+
+		let download_path = mod_downloads_path.join(download::get_url_file_name(&version.url));
+		download::download_file(mod_id, &version.url, &download_path, on_progress).await?;
+
+		if let Err(error) = mod_verification::verify(mod_id, &download_path, version).await {
+			// Don't keep the file around, otherwise it could get picked up again later.
+			fs::remove_file(&download_path)?;
+			return Err(error);
+		}
 
-			// TODO Stream to disk instead of keeping it all in memory.
-			fs::write(&zip_path, response.bytes().await?)?;
-			let file = File::open(&zip_path)?;
+		// Mod is extracted to a temporary folder first,
+		// so a failed extraction doesn't leave a half-replaced mod behind.
+		let unzip_path = mod_downloads_path.join("extracted");
+		if unzip_path.exists() {
+			fs::remove_dir_all(&unzip_path)?;
+		}
 
-			let mut zip_archive = ZipArchive::new(file)?;
+		archive::extract(&download_path, &unzip_path)?;
+
+		let staged_path = version
+			.root
+			.as_ref()
+			.map_or_else(|| unzip_path.clone(), |root| unzip_path.join(root));
+
+		// Saves the manifest so we know which version of the mod we installed.
+		fs::write(
+			local_mod::get_manifest_path(&staged_path),
+			serde_json::to_string_pretty(&mod_manifest::Manifest {
+				title: Some(remote_mod.data.title.clone()),
+				version: version.id.clone(),
+				runnable: version.runnable.clone(),
+				engine: remote_mod.common.engine,
+				engine_version_range: remote_mod.common.engine_version_range.clone(),
+				unity_backend: remote_mod.common.unity_backend,
+				architecture: remote_mod.common.architecture,
+				dependencies: Some(remote_mod.data.dependencies.clone()),
+				incompatibilities: Some(remote_mod.data.incompatibilities.clone()),
+				bepinex_builds: Some(remote_mod.data.bepinex_builds.clone()),
+				config_file: remote_mod.data.config_file.clone(),
+				files: None,
+			})?,
+		)?;
+
+		if version_path.exists() {
+			fs::remove_dir_all(&version_path)?;
+		}
+		fs::create_dir_all(paths::path_parent(&version_path)?)?;
+		fs::rename(&staged_path, &version_path)?;
 
-			if let Some(root) = &latest_version.root {
-				let unzip_path = downloads_path.join(mod_id);
-				zip_archive.extract(downloads_path.join(mod_id))?;
-				files::copy_dir_all(unzip_path.join(root), &target_path)?;
-			} else {
-				zip_archive.extract(&target_path)?;
-			}
+		fs::remove_dir_all(&mod_downloads_path)?;
 
-			// Saves the manifest so we know which version of the mod we installed.
-			fs::write(
-				local_mod::get_manifest_path(&target_path),
-				serde_json::to_string_pretty(&mod_manifest::Manifest {
-					title: Some(remote_mod.data.title.clone()),
-					version: latest_version.id.clone(),
-					runnable: latest_version.runnable.clone(),
-					engine: remote_mod.common.engine,
-					engine_version_range: remote_mod.common.engine_version_range.clone(),
-					unity_backend: remote_mod.common.unity_backend,
-				})?,
-			)?;
-
-			return Ok(());
-		}
-		Err(Error::ModDownloadNotAvailable(remote_mod.common.id.clone()))
+		Ok(version_path)
 	}
 
-	fn delete_mod(&self, local_mod: &LocalMod) -> Result {
-		let mod_path = self.get_mod_path(&local_mod.common)?;
+	// Removes every downloaded version of the mod.
+	// Games pinned to one of them get unpinned, since there's nothing left to pin them to.
+	fn delete_mod(&self, local_mod: &LocalMod, games: &[Game]) -> Result {
+		for mod_path in [
+			self.get_mod_path(&local_mod.common)?,
+			mod_versions::get_versions_path(&self.get_data().id, &local_mod.common.id)?,
+		] {
+			if mod_path.exists() {
+				fs::remove_dir_all(&mod_path)?;
+			}
+		}
 
-		if mod_path.exists() {
-			fs::remove_dir_all(&mod_path)?;
+		for installed_game in games.iter().filter_map(|game| game.installed_game.as_ref()) {
+			mod_pins::forget_mod(installed_game, &local_mod.common.id)?;
 		}
 
 		Ok(())
@@ -206,10 +477,33 @@ pub fn get_map(resources_path: &Path) -> Map {
 
 	add_entry::<BepInEx>(resources_path, &mut map);
 	add_entry::<RunnableLoader>(resources_path, &mut map);
+	add_descriptor_entries(resources_path, &mut map);
 
 	map
 }
 
+// Loaders defined in code take precedence, in case a descriptor uses the same ID.
+fn add_descriptor_entries(resources_path: &Path, map: &mut Map) {
+	let descriptor_paths = paths::glob_path(
+		&resources_path
+			.join("*")
+			.join(descriptor_loader::DESCRIPTOR_FILE_NAME),
+	);
+
+	for descriptor_path in descriptor_paths {
+		match paths::path_parent(&descriptor_path).and_then(DescriptorLoader::new) {
+			Ok(mod_loader) => {
+				map.entry(mod_loader.data.id.clone())
+					.or_insert_with(|| mod_loader.into());
+			}
+			Err(err) => error!(
+				"Failed to create mod loader from descriptor {}: {err}",
+				descriptor_path.display()
+			),
+		}
+	}
+}
+
 pub fn get_data_map(map: &Map) -> Result<DataMap> {
 	map.values()
 		.map(|mod_loader| {
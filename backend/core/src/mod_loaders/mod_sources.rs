@@ -0,0 +1,151 @@
+use std::{fs, path::PathBuf};
+
+use log::error;
+use rai_pal_proc_macros::serializable_struct;
+
+use super::{
+	mod_database::{self, ModDatabase},
+	thunderstore::{ReqwestHttp, ThunderstoreSource},
+};
+use crate::{
+	paths,
+	result::{Error, Result},
+};
+
+// Label and priority used for the database at github.com/Raicuparta/rai-pal-db.
+pub const OFFICIAL_SOURCE_LABEL: &str = "official";
+pub const OFFICIAL_SOURCE_PRIORITY: i32 = 0;
+
+#[serializable_struct]
+pub struct ModSource {
+	pub loader_id: String,
+	// Either an http(s) url or a file:// url pointing to a local json file,
+	// with the same format as the official database.
+	// For Thunderstore sources, this is the base url of the Thunderstore instance, like "https://thunderstore.io".
+	pub url: String,
+	// If set, mods come from this Thunderstore community instead of a Rai Pal style database.
+	pub thunderstore_community: Option<String>,
+	// When the same mod ID shows up in more than one source, the one with the highest priority wins.
+	// The official database has priority 0, so negative values make a source a fallback.
+	pub priority: i32,
+	pub label: String,
+}
+
+#[serializable_struct]
+#[derive(Default)]
+pub struct ModSources {
+	pub sources: Vec<ModSource>,
+}
+
+const FILE_NAME: &str = "mod-sources.json";
+
+fn get_path() -> Result<PathBuf> {
+	Ok(paths::app_data_path()?.join(FILE_NAME))
+}
+
+fn try_read() -> Result<ModSources> {
+	let path = get_path()?;
+	if !path.is_file() {
+		return Ok(ModSources::default());
+	}
+
+	Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn read() -> ModSources {
+	try_read().unwrap_or_else(|err| {
+		error!("Failed to read mod sources: {err}");
+		ModSources::default()
+	})
+}
+
+pub fn write(sources: &ModSources) -> Result {
+	let path = get_path()?;
+	fs::create_dir_all(paths::path_parent(&path)?)?;
+
+	Ok(fs::write(path, serde_json::to_string_pretty(sources)?)?)
+}
+
+impl ModSources {
+	pub fn get_for_loader(&self, loader_id: &str) -> Vec<&ModSource> {
+		self.sources
+			.iter()
+			.filter(|source| source.loader_id == loader_id)
+			.collect()
+	}
+}
+
+impl ModSource {
+	pub async fn get_database(&self) -> Result<ModDatabase> {
+		if let Some(community) = &self.thunderstore_community {
+			return ThunderstoreSource {
+				base_url: self.url.clone(),
+				community: community.clone(),
+			}
+			.get_database(&ReqwestHttp)
+			.await;
+		}
+
+		let url = reqwest::Url::parse(&self.url)
+			.map_err(|err| Error::ModSourceUrlInvalid(self.url.clone(), err.to_string()))?;
+
+		if url.scheme() == "file" {
+			// Takes care of percent-encoding and Windows drive letters, which a plain prefix strip wouldn't.
+			let file_path = url.to_file_path().map_err(|()| {
+				Error::ModSourceUrlInvalid(self.url.clone(), "not a valid file path".to_string())
+			})?;
+
+			return Ok(serde_json::from_str(&fs::read_to_string(file_path)?)?);
+		}
+
+		Ok(reqwest::get(url)
+			.await?
+			.error_for_status()?
+			.json::<ModDatabase>()
+			.await?)
+	}
+}
+
+// All the databases for a mod loader, sorted from lowest to highest priority.
+// The official one is only included for mod loaders that have one.
+pub async fn get_databases<F>(
+	loader_id: &str,
+	include_official: bool,
+	error_handler: F,
+) -> Vec<(String, ModDatabase)>
+where
+	F: Fn(Error) + Send,
+{
+	let mut databases = Vec::new();
+
+	if include_official {
+		match mod_database::get(loader_id).await {
+			Ok(database) => databases.push((
+				OFFICIAL_SOURCE_PRIORITY,
+				OFFICIAL_SOURCE_LABEL.to_string(),
+				database,
+			)),
+			Err(err) => error_handler(err),
+		}
+	}
+
+	for source in read().get_for_loader(loader_id) {
+		match source.get_database().await {
+			Ok(database) => databases.push((source.priority, source.label.clone(), database)),
+			Err(err) => {
+				error!(
+					"Failed to get mod database `{}` from {}: {err}",
+					source.label, source.url
+				);
+				error_handler(err);
+			}
+		}
+	}
+
+	databases.sort_by_key(|(priority, _, _)| *priority);
+
+	databases
+		.into_iter()
+		.map(|(_, label, database)| (label, database))
+		.collect()
+}
@@ -0,0 +1,102 @@
+use std::{
+	fs::File,
+	io::{self, Read},
+	path::Path,
+};
+
+use minisign_verify::{PublicKey, Signature};
+use rai_pal_proc_macros::serializable_struct;
+use sha2::{Digest, Sha256};
+
+use super::mod_database::ModDownload;
+use crate::result::{Error, Result};
+
+#[serializable_struct]
+pub struct ModSignature {
+	// Base64 minisign public key, the second line of a minisign .pub file.
+	pub public_key: String,
+	// Defaults to the download url with ".minisig" appended, which is how minisign names signatures.
+	pub signature_url: Option<String>,
+}
+
+impl ModSignature {
+	pub fn get_signature_url(&self, download_url: &str) -> String {
+		self.signature_url
+			.clone()
+			.unwrap_or_else(|| format!("{download_url}.minisig"))
+	}
+}
+
+pub fn get_sha256(path: &Path) -> Result<String> {
+	let mut hasher = Sha256::new();
+	io::copy(&mut File::open(path)?, &mut hasher)?;
+
+	Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn verify_sha256(mod_id: &str, path: &Path, expected_sha256: &str) -> Result {
+	let sha256 = get_sha256(path)?;
+
+	if sha256.eq_ignore_ascii_case(expected_sha256.trim()) {
+		Ok(())
+	} else {
+		Err(Error::ModVerificationFailed(
+			mod_id.to_string(),
+			format!("expected SHA-256 `{expected_sha256}`, but got `{sha256}`"),
+		))
+	}
+}
+
+async fn verify_signature(
+	mod_id: &str,
+	path: &Path,
+	download_url: &str,
+	signature: &ModSignature,
+) -> Result {
+	let verification_error =
+		|reason: String| Error::ModVerificationFailed(mod_id.to_string(), reason);
+
+	let public_key = PublicKey::from_base64(&signature.public_key)
+		.map_err(|err| verification_error(format!("invalid public key: {err}")))?;
+
+	let signature_text = reqwest::get(signature.get_signature_url(download_url))
+		.await?
+		.error_for_status()?
+		.text()
+		.await?;
+
+	let decoded_signature = Signature::decode(&signature_text)
+		.map_err(|err| verification_error(format!("invalid signature: {err}")))?;
+
+	// Mods can be pretty big, so we go through the file in chunks instead of reading it all at once.
+	let mut verifier = public_key
+		.verify_stream(&decoded_signature)
+		.map_err(|err| verification_error(format!("unsupported signature: {err}")))?;
+	let mut file = File::open(path)?;
+	let mut buffer = [0; 8192];
+	loop {
+		let bytes_read = file.read(&mut buffer)?;
+		if bytes_read == 0 {
+			break;
+		}
+		verifier.update(&buffer[..bytes_read]);
+	}
+
+	verifier
+		.finalize()
+		.map_err(|err| verification_error(format!("signature doesn't match: {err}")))
+}
+
+// Checks the downloaded file against whatever integrity information the database gave us.
+// Downloads without a checksum or signature are accepted as they are.
+pub async fn verify(mod_id: &str, path: &Path, download: &ModDownload) -> Result {
+	if let Some(sha256) = &download.sha256 {
+		verify_sha256(mod_id, path, sha256)?;
+	}
+
+	if let Some(signature) = &download.minisign {
+		verify_signature(mod_id, path, &download.url, signature).await?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,190 @@
+use log::info;
+use rai_pal_proc_macros::serializable_struct;
+
+use super::{
+	bepinex_config,
+	mod_database::ModpackEntry,
+	mod_loader::{self, ModLoaderActions},
+	mod_sources,
+};
+use crate::{
+	download::DownloadProgress,
+	game_mod::CommonModData,
+	installed_game::InstalledGame,
+	local_mod::LocalMod,
+	maps::TryGettable,
+	mod_backups, mod_compatibility, mod_pins, mod_versions, remote_mod,
+	result::{Error, Result},
+};
+
+#[serializable_struct]
+pub struct Modpack {
+	pub loader_id: String,
+	// Label of the mod source the modpack came from.
+	pub source: String,
+	pub entry: ModpackEntry,
+}
+
+// Modpacks from every mod source of every mod loader.
+// Like with mods, modpacks from higher priority sources replace the ones before them.
+pub async fn get_all<F>(mod_loaders: &mod_loader::Map, error_handler: F) -> Vec<Modpack>
+where
+	F: Fn(Error) + Send + Sync,
+{
+	let mut modpacks: Vec<Modpack> = Vec::new();
+
+	for mod_loader in mod_loaders.values() {
+		let loader_id = &mod_loader.get_data().id;
+
+		let databases = mod_sources::get_databases(
+			loader_id,
+			mod_loader.has_official_database(),
+			&error_handler,
+		)
+		.await;
+
+		for (source, database) in databases {
+			for entry in database.modpacks.unwrap_or_default() {
+				modpacks.retain(|modpack| modpack.entry.id != entry.id);
+				modpacks.push(Modpack {
+					loader_id: loader_id.clone(),
+					source: source.clone(),
+					entry,
+				});
+			}
+		}
+	}
+
+	modpacks.sort_by(|modpack_a, modpack_b| modpack_a.entry.title.cmp(&modpack_b.entry.title));
+
+	modpacks
+}
+
+pub async fn get<F>(
+	mod_loaders: &mod_loader::Map,
+	modpack_id: &str,
+	error_handler: F,
+) -> Result<Modpack>
+where
+	F: Fn(Error) + Send + Sync,
+{
+	get_all(mod_loaders, error_handler)
+		.await
+		.into_iter()
+		.find(|modpack| modpack.entry.id == modpack_id)
+		.ok_or_else(|| Error::ModpackNotFound(modpack_id.to_string()))
+}
+
+impl Modpack {
+	// Modpacks have the same engine requirements as mods, so we can check them the same way.
+	fn get_common_data(&self) -> CommonModData {
+		CommonModData {
+			id: self.entry.id.clone(),
+			engine: self.entry.engine,
+			unity_backend: self.entry.unity_backend,
+			engine_version_range: self.entry.engine_version_range.clone(),
+			architecture: None,
+			loader_id: self.loader_id.clone(),
+		}
+	}
+
+	// Downloads everything first, then installs all the mods and applies the config overrides.
+	// If anything goes wrong while installing, the game's mods, and the files mod loaders put in the game folder,
+	// go back to how they were before.
+	pub async fn install<F>(
+		&self,
+		game: &InstalledGame,
+		mod_loaders: &mod_loader::Map,
+		remote_mods: &remote_mod::Map,
+		on_progress: &F,
+	) -> Result
+	where
+		F: Fn(DownloadProgress) + Send + Sync,
+	{
+		let compatibility = mod_compatibility::evaluate(&self.get_common_data(), game);
+		if compatibility.is_incompatible() {
+			return Err(Error::ModIncompatibleWithGame(
+				self.entry.id.clone(),
+				compatibility
+					.issues
+					.iter()
+					.map(ToString::to_string)
+					.collect::<Vec<_>>()
+					.join(", "),
+			));
+		}
+
+		let mod_loader = mod_loaders.try_get(&self.loader_id)?;
+
+		let mut local_mods = Vec::new();
+		for modpack_mod in &self.entry.mods {
+			local_mods.push(
+				mod_versions::download_if_needed(
+					mod_loader,
+					&modpack_mod.id,
+					modpack_mod.version.as_deref(),
+					remote_mods,
+					on_progress,
+				)
+				.await?,
+			);
+		}
+
+		mod_backups::create(game, mod_loaders)?;
+
+		let result = self
+			.install_downloaded(game, mod_loader, &local_mods, remote_mods, on_progress)
+			.await;
+
+		mod_backups::restore_on_error(game, result)?;
+
+		info!("Installed modpack `{}` in game {}", self.entry.id, game.id);
+
+		Ok(())
+	}
+
+	async fn install_downloaded<F>(
+		&self,
+		game: &InstalledGame,
+		mod_loader: &mod_loader::ModLoader,
+		local_mods: &[LocalMod],
+		remote_mods: &remote_mod::Map,
+		on_progress: &F,
+	) -> Result
+	where
+		F: Fn(DownloadProgress) + Send + Sync,
+	{
+		// Pinning the modpack versions means dependencies also get the right version when installed.
+		let mut pins = mod_pins::read(game);
+		for (modpack_mod, local_mod) in self.entry.mods.iter().zip(local_mods) {
+			match &modpack_mod.version {
+				Some(version) if local_mod.get_version() != Some(version) => {
+					pins.pin(&modpack_mod.id, version);
+				}
+				_ => {
+					pins.unpin(&modpack_mod.id);
+				}
+			}
+		}
+		mod_pins::write(game, &pins)?;
+
+		for local_mod in local_mods {
+			mod_loader
+				.install_mod(game, local_mod, remote_mods, on_progress)
+				.await?;
+		}
+
+		// Overrides go last, since installing mod loaders can reset configs.
+		for config_override in self.entry.config_overrides.iter().flatten() {
+			bepinex_config::override_value(
+				game,
+				&config_override.file_name,
+				&config_override.section,
+				&config_override.key,
+				&config_override.value,
+			)?;
+		}
+
+		Ok(())
+	}
+}
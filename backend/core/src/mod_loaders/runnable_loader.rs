@@ -6,16 +6,20 @@ use std::{
 use log::error;
 use rai_pal_proc_macros::{serializable_enum, serializable_struct};
 
-use super::mod_loader::{ModLoaderActions, ModLoaderData, ModLoaderStatic};
+use super::{
+	mod_loader::{ModLoaderActions, ModLoaderData, ModLoaderStatic},
+	runnable_processes,
+};
 use crate::{
 	game_mod::CommonModData,
 	installed_game::InstalledGame,
 	local_mod::{self, LocalMod, ModKind},
 	mod_manifest,
-	paths::glob_path,
+	paths::{self, glob_path},
 	providers::provider_command::ProviderCommand,
 	result::Error,
 	result::Result,
+	wine,
 };
 
 #[serializable_struct]
@@ -29,9 +33,38 @@ pub enum RunnableParameter {
 	ExecutablePath,
 	GameJson,
 	StartCommand,
+	// Expands to one argument per start command argument.
 	StartCommandArgs,
+	GameFolder,
+	EngineBrand,
+	EngineVersion,
+	Architecture,
+	ScriptingBackend,
+	ProviderId,
+	ExternalId,
+	InstalledModsFolder,
+	// Wine prefix Proton uses for the game. Only available for Steam games.
+	ProtonPrefix,
 }
 
+// Same order every time, unlike `RunnableParameter::variants`, so expanding arguments is predictable.
+const PARAMETERS: [RunnableParameter; 14] = [
+	RunnableParameter::ExecutableName,
+	RunnableParameter::ExecutablePath,
+	RunnableParameter::GameJson,
+	RunnableParameter::StartCommand,
+	RunnableParameter::StartCommandArgs,
+	RunnableParameter::GameFolder,
+	RunnableParameter::EngineBrand,
+	RunnableParameter::EngineVersion,
+	RunnableParameter::Architecture,
+	RunnableParameter::ScriptingBackend,
+	RunnableParameter::ProviderId,
+	RunnableParameter::ExternalId,
+	RunnableParameter::InstalledModsFolder,
+	RunnableParameter::ProtonPrefix,
+];
+
 impl ModLoaderStatic for RunnableLoader {
 	const ID: &'static str = "runnable";
 
@@ -53,56 +86,172 @@ fn get_parameter_token(parameter: RunnableParameter) -> String {
 	format!("{{{{{parameter}}}}}")
 }
 
-fn replace_parameter_value<TValue: AsRef<str>, TGetValue: Fn() -> Result<TValue>>(
-	argument: &str,
+fn get_missing_info_error(info: &str, game: &InstalledGame) -> Error {
+	Error::ModInstallInfoInsufficient(info.to_string(), game.executable.path.clone())
+}
+
+// Most parameters are a single value, but some (like the start command arguments) are a list.
+// Runnables that go through Wine get paths they can understand.
+fn get_parameter_values(
 	parameter: RunnableParameter,
-	get_value: TGetValue,
-) -> String {
-	if !argument.contains(&get_parameter_token(parameter)) {
+	game: &InstalledGame,
+	uses_wine: bool,
+) -> Result<Vec<String>> {
+	let path_to_string = |path: &Path| {
+		if uses_wine {
+			wine::to_wine_path(path)
+		} else {
+			path.to_string_lossy().to_string()
+		}
+	};
+
+	let value = match parameter {
+		RunnableParameter::ExecutableName => game.executable.name.clone(),
+		RunnableParameter::ExecutablePath => path_to_string(&game.executable.path),
+		RunnableParameter::GameJson => serde_json::to_string(&game)?,
+		RunnableParameter::StartCommand => game.start_command.as_ref().map_or_else(
+			|| path_to_string(&game.executable.path),
+			|provider_command| match provider_command {
+				ProviderCommand::String(s) => s.to_string(),
+				ProviderCommand::Path(exe_path, _) => path_to_string(exe_path),
+			},
+		),
+		RunnableParameter::StartCommandArgs => {
+			return Ok(match &game.start_command {
+				Some(ProviderCommand::Path(_, args)) => args.clone(),
+				Some(ProviderCommand::String(_)) | None => Vec::new(),
+			});
+		}
+		RunnableParameter::GameFolder => path_to_string(paths::path_parent(&game.executable.path)?),
+		RunnableParameter::EngineBrand => game
+			.executable
+			.engine
+			.as_ref()
+			.ok_or_else(|| get_missing_info_error("engine", game))?
+			.brand
+			.to_string(),
+		RunnableParameter::EngineVersion => game
+			.executable
+			.engine
+			.as_ref()
+			.and_then(|engine| engine.version.as_ref())
+			.ok_or_else(|| get_missing_info_error("engine_version", game))?
+			.display
+			.clone(),
+		RunnableParameter::Architecture => game
+			.executable
+			.architecture
+			.ok_or_else(|| get_missing_info_error("architecture", game))?
+			.to_string(),
+		RunnableParameter::ScriptingBackend => game
+			.executable
+			.scripting_backend
+			.ok_or_else(|| get_missing_info_error("scripting_backend", game))?
+			.to_string(),
+		RunnableParameter::ProviderId => game
+			.provider_id
+			.ok_or_else(|| get_missing_info_error("provider_id", game))?
+			.to_string(),
+		RunnableParameter::ExternalId => game
+			.external_id
+			.clone()
+			.ok_or_else(|| get_missing_info_error("external_id", game))?,
+		RunnableParameter::InstalledModsFolder => {
+			path_to_string(&game.get_installed_mods_folder()?)
+		}
+		// This one is for the Linux side of things, so it never gets translated.
+		RunnableParameter::ProtonPrefix => wine::get_proton_prefix(game)
+			.ok_or_else(|| get_missing_info_error("proton_prefix", game))?
+			.to_string_lossy()
+			.to_string(),
+	};
+
+	Ok(vec![value])
+}
+
+// Quotes the argument if needed, so a list of arguments can be joined into one and split back apart later.
+pub fn quote_argument(argument: &str) -> String {
+	if !argument.is_empty()
+		&& !argument.contains(|character: char| character.is_whitespace() || character == '"')
+	{
 		return argument.to_string();
 	}
 
-	match get_value() {
-		Ok(value) => argument.replace(&get_parameter_token(parameter), value.as_ref()),
-		Err(error) => {
-			error!("Failed get value to replace parameter `{parameter}` in runnable argument `{argument}`. Error: {error}");
-			argument.to_string()
+	// Backslashes are only special before a quote, so only those get doubled.
+	// That includes the trailing ones, since they end up before the closing quote.
+	let mut result = String::from('"');
+	let mut backslashes = 0;
+	for character in argument.chars() {
+		match character {
+			'\\' => backslashes += 1,
+			'"' => {
+				result.push_str(&"\\".repeat(backslashes * 2 + 1));
+				result.push('"');
+				backslashes = 0;
+			}
+			_ => {
+				result.push_str(&"\\".repeat(backslashes));
+				result.push(character);
+				backslashes = 0;
+			}
 		}
 	}
+	result.push_str(&"\\".repeat(backslashes * 2));
+	result.push('"');
+
+	result
 }
 
-fn replace_parameters(argument: &str, game: &InstalledGame) -> String {
+// Arguments that are just a token get replaced with however many arguments the token expands to.
+// Tokens in the middle of an argument get replaced in place, with multiple values quoted and joined.
+pub fn expand_argument(argument: &str, game: &InstalledGame, uses_wine: bool) -> Vec<String> {
+	if let Some(parameter) = PARAMETERS
+		.into_iter()
+		.find(|parameter| argument == get_parameter_token(*parameter))
+	{
+		return get_parameter_values(parameter, game, uses_wine).unwrap_or_else(|error| {
+			error!("Failed to get value for parameter `{parameter}` in runnable argument `{argument}`. Error: {error}");
+			vec![argument.to_string()]
+		});
+	}
+
 	let mut result = argument.to_string();
 
-	result = replace_parameter_value(&result, RunnableParameter::ExecutableName, || {
-		Ok(&game.executable.name)
-	});
-	result = replace_parameter_value(&result, RunnableParameter::ExecutablePath, || {
-		Ok(game.executable.path.to_string_lossy())
-	});
-	result = replace_parameter_value(&result, RunnableParameter::GameJson, || {
-		Ok(serde_json::to_string(&game)?)
-	});
-	result = replace_parameter_value(&result, RunnableParameter::StartCommand, || {
-		game.start_command.as_ref().map_or_else(
-			|| Ok(game.executable.path.to_string_lossy().to_string()),
-			|provider_command| match provider_command {
-				ProviderCommand::String(s) => Ok(s.to_string()),
-				ProviderCommand::Path(exe_path, _) => Ok(exe_path.to_string_lossy().to_string()),
-			},
-		)
-	});
-	result = replace_parameter_value(&result, RunnableParameter::StartCommandArgs, || {
-		game.start_command.as_ref().map_or_else(
-			|| Ok(String::new()),
-			|provider_command| match provider_command {
-				ProviderCommand::Path(_, args) => Ok(args.join(" ")),
-				ProviderCommand::String(_) => Ok(String::new()),
-			},
-		)
-	});
+	for parameter in PARAMETERS {
+		let token = get_parameter_token(parameter);
+		if !result.contains(&token) {
+			continue;
+		}
 
-	result
+		match get_parameter_values(parameter, game, uses_wine) {
+			Ok(values) => {
+				let value = match values.as_slice() {
+					[value] => value.clone(),
+					_ => values
+						.iter()
+						.map(|value| quote_argument(value))
+						.collect::<Vec<_>>()
+						.join(" "),
+				};
+				result = result.replace(&token, &value);
+			}
+			Err(error) => {
+				error!(
+					"Failed to get value for parameter `{parameter}` in runnable argument `{argument}`. Error: {error}"
+				);
+			}
+		}
+	}
+
+	vec![result]
+}
+
+fn create_command(executable_path: &Path, game: Option<&InstalledGame>) -> Command {
+	if wine::is_needed(executable_path) {
+		wine::create_command(executable_path, game)
+	} else {
+		Command::new(executable_path)
+	}
 }
 
 impl ModLoaderActions for RunnableLoader {
@@ -115,7 +264,8 @@ impl ModLoaderActions for RunnableLoader {
 	}
 
 	async fn install_mod_inner(&self, game: &InstalledGame, local_mod: &LocalMod) -> Result {
-		let mod_folder = self.get_mod_path(&local_mod.common)?;
+		// Not using `get_mod_path`, since the local mod can be a pinned version.
+		let mod_folder = &local_mod.data.path;
 
 		let runnable = local_mod
 			.data
@@ -124,16 +274,21 @@ impl ModLoaderActions for RunnableLoader {
 			.and_then(|manifest| manifest.runnable.as_ref())
 			.ok_or_else(|| Error::RunnableManifestNotFound(local_mod.common.id.clone()))?;
 
+		let executable_path = mod_folder.join(&runnable.path);
+		let uses_wine = wine::is_needed(&executable_path);
+
 		let args: Vec<String> = runnable
 			.args
 			.iter()
-			.map(|arg| replace_parameters(arg, game))
+			.flat_map(|arg| expand_argument(arg, game, uses_wine))
 			.collect();
 
-		Command::new(mod_folder.join(&runnable.path))
-			.current_dir(mod_folder)
-			.args(&args)
-			.spawn()?;
+		runnable_processes::spawn(
+			&local_mod.common.id,
+			create_command(&executable_path, Some(game))
+				.current_dir(mod_folder)
+				.args(&args),
+		)?;
 
 		Ok(())
 	}
@@ -144,6 +299,17 @@ impl ModLoaderActions for RunnableLoader {
 		Ok(())
 	}
 
+	fn set_mod_enabled(
+		&self,
+		_game: &InstalledGame,
+		_local_mod: &LocalMod,
+		_enabled: bool,
+	) -> Result {
+		// Runnables aren't deployed into the game, so there's nothing to park.
+
+		Ok(())
+	}
+
 	async fn run_without_game(&self, local_mod: &LocalMod) -> Result {
 		let mod_folder = self.get_mod_path(&local_mod.common)?;
 
@@ -154,9 +320,10 @@ impl ModLoaderActions for RunnableLoader {
 			.and_then(|manifest| manifest.runnable.as_ref())
 			.ok_or_else(|| Error::RunnableManifestNotFound(local_mod.common.id.clone()))?;
 
-		Command::new(mod_folder.join(&runnable.path))
-			.current_dir(mod_folder)
-			.spawn()?;
+		runnable_processes::spawn(
+			&local_mod.common.id,
+			create_command(&mod_folder.join(&runnable.path), None).current_dir(&mod_folder),
+		)?;
 
 		Ok(())
 	}
@@ -0,0 +1,250 @@
+use std::{
+	collections::HashMap,
+	fs::{self, OpenOptions},
+	io::Write,
+	path::PathBuf,
+	process::{Child, Command, Stdio},
+	sync::Mutex,
+	thread,
+	time::Duration,
+};
+
+use lazy_static::lazy_static;
+use log::{error, info};
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	paths,
+	result::{Error, Result},
+	timestamps,
+};
+
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[serializable_struct]
+pub struct RunnableProcess {
+	pub mod_id: String,
+	pub pid: u32,
+	pub log_path: PathBuf,
+	// Unix timestamp, in seconds.
+	pub started_at: u64,
+}
+
+#[serializable_struct]
+pub struct RunnableExit {
+	pub process: RunnableProcess,
+	// Missing if the process was killed by a signal.
+	pub exit_code: Option<i32>,
+	// Exited with an error, without anyone asking it to stop.
+	pub is_crash: bool,
+}
+
+struct TrackedProcess {
+	child: Child,
+	process: RunnableProcess,
+	is_stopping: bool,
+}
+
+type ExitHandler = Box<dyn Fn(RunnableExit) + Send>;
+
+lazy_static! {
+	static ref PROCESSES: Mutex<HashMap<u32, TrackedProcess>> = Mutex::new(HashMap::new());
+	static ref EXIT_HANDLER: Mutex<Option<ExitHandler>> = Mutex::new(None);
+}
+
+pub fn get_log_path(mod_id: &str) -> Result<PathBuf> {
+	Ok(paths::logs_path()?
+		.join("runnables")
+		.join(format!("{mod_id}.log")))
+}
+
+// Where the log from the previous run ends up, so the log from a crash doesn't get lost just because the mod was started again.
+pub fn get_previous_log_path(mod_id: &str) -> Result<PathBuf> {
+	Ok(paths::logs_path()?
+		.join("runnables")
+		.join(format!("{mod_id}.previous.log")))
+}
+
+// Called whenever a tracked process exits, no matter how.
+pub fn set_exit_handler<F: Fn(RunnableExit) + Send + 'static>(handler: F) {
+	match EXIT_HANDLER.lock() {
+		Ok(mut exit_handler) => *exit_handler = Some(Box::new(handler)),
+		Err(err) => error!("Failed to set runnable exit handler: {err}"),
+	}
+}
+
+// Starts the process with its output going to the mod's log file, and keeps track of it until it exits.
+pub fn spawn(mod_id: &str, command: &mut Command) -> Result<RunnableProcess> {
+	let log_path = get_log_path(mod_id)?;
+	fs::create_dir_all(paths::path_parent(&log_path)?)?;
+
+	// Only keeping the previous run around, so logs don't keep growing forever.
+	if log_path.is_file() {
+		fs::rename(&log_path, get_previous_log_path(mod_id)?)?;
+	}
+
+	let mut log_file = OpenOptions::new()
+		.create(true)
+		.write(true)
+		.truncate(true)
+		.open(&log_path)?;
+	let started_at = timestamps::now();
+	writeln!(
+		log_file,
+		"===== Started {mod_id} at {} =====",
+		timestamps::format(started_at)
+	)?;
+
+	// Own process group, so stopping it also stops anything it started.
+	// Wine in particular leaves the actual Windows executable running as a child process.
+	#[cfg(unix)]
+	std::os::unix::process::CommandExt::process_group(command, 0);
+
+	let child = command
+		.stdout(Stdio::from(log_file.try_clone()?))
+		.stderr(Stdio::from(log_file))
+		.spawn()?;
+
+	let process = RunnableProcess {
+		mod_id: mod_id.to_string(),
+		pid: child.id(),
+		log_path,
+		started_at,
+	};
+
+	info!("Started runnable {mod_id} with pid {}", process.pid);
+
+	PROCESSES
+		.lock()
+		.map_err(|err| Error::RunnableProcessRegistry(err.to_string()))?
+		.insert(
+			process.pid,
+			TrackedProcess {
+				child,
+				process: process.clone(),
+				is_stopping: false,
+			},
+		);
+
+	let pid = process.pid;
+	thread::spawn(move || watch(pid));
+
+	Ok(process)
+}
+
+fn watch(pid: u32) {
+	loop {
+		thread::sleep(EXIT_POLL_INTERVAL);
+
+		let exit = match try_get_exit(pid) {
+			Ok(Some(exit)) => exit,
+			Ok(None) => continue,
+			Err(err) => {
+				error!("Failed to check runnable process {pid}: {err}");
+				return;
+			}
+		};
+
+		info!(
+			"Runnable {} with pid {pid} exited with code {:?}",
+			exit.process.mod_id, exit.exit_code
+		);
+
+		match EXIT_HANDLER.lock() {
+			Ok(exit_handler) => {
+				if let Some(exit_handler) = exit_handler.as_ref() {
+					exit_handler(exit);
+				}
+			}
+			Err(err) => error!("Failed to get runnable exit handler: {err}"),
+		}
+
+		return;
+	}
+}
+
+// Removes the process from the registry once it exits.
+fn try_get_exit(pid: u32) -> Result<Option<RunnableExit>> {
+	let mut processes = PROCESSES
+		.lock()
+		.map_err(|err| Error::RunnableProcessRegistry(err.to_string()))?;
+
+	let tracked_process = processes.get_mut(&pid).ok_or_else(|| {
+		Error::RunnableProcessRegistry(format!("process {pid} isn't being tracked"))
+	})?;
+
+	let Some(status) = tracked_process.child.try_wait()? else {
+		return Ok(None);
+	};
+
+	let is_stopping = tracked_process.is_stopping;
+	let process = tracked_process.process.clone();
+	processes.remove(&pid);
+	drop(processes);
+
+	Ok(Some(RunnableExit {
+		process,
+		exit_code: status.code(),
+		is_crash: !is_stopping && !status.success(),
+	}))
+}
+
+pub fn get_running() -> Vec<RunnableProcess> {
+	PROCESSES
+		.lock()
+		.map(|processes| {
+			processes
+				.values()
+				.map(|tracked_process| tracked_process.process.clone())
+				.collect()
+		})
+		.unwrap_or_default()
+}
+
+// Kills every running process for this mod. The exit handler still gets called for each of them.
+pub fn stop(mod_id: &str) -> Result {
+	let mut processes = PROCESSES
+		.lock()
+		.map_err(|err| Error::RunnableProcessRegistry(err.to_string()))?;
+
+	let mut found = false;
+	for tracked_process in processes
+		.values_mut()
+		.filter(|tracked_process| tracked_process.process.mod_id == mod_id)
+	{
+		found = true;
+		tracked_process.is_stopping = true;
+		kill(&mut tracked_process.child)?;
+	}
+	drop(processes);
+
+	if found {
+		Ok(())
+	} else {
+		Err(Error::RunnableNotRunning(mod_id.to_string()))
+	}
+}
+
+#[cfg(unix)]
+fn kill(child: &mut Child) -> Result {
+	// Negative pid means the whole process group, which has the same id as the process we started.
+	let killed_group = Command::new("kill")
+		.args(["-KILL", "--", &format!("-{}", child.id())])
+		.status()
+		.is_ok_and(|status| status.success());
+
+	if !killed_group {
+		error!(
+			"Failed to kill process group {}, killing just the process.",
+			child.id()
+		);
+		child.kill()?;
+	}
+
+	Ok(())
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) -> Result {
+	Ok(child.kill()?)
+}
@@ -0,0 +1,157 @@
+use super::mod_database::{DatabaseEntry, ModDatabase, ModDownload};
+use crate::{
+	game_engines::{game_engine::EngineBrand, unity::UnityScriptingBackend},
+	game_mod::ModDependency,
+	result::Result,
+};
+
+// Packages that are just BepInEx itself. Rai Pal already takes care of installing BepInEx,
+// so we don't want these showing up as dependencies.
+const BEPINEX_PACK_NAMES: [&str; 2] = ["BepInExPack", "BepInExPack_IL2CPP"];
+
+#[derive(serde::Deserialize)]
+pub struct ThunderstorePackage {
+	pub full_name: String,
+	pub owner: String,
+	pub is_deprecated: bool,
+	// Newest first.
+	pub versions: Vec<ThunderstorePackageVersion>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct ThunderstorePackageVersion {
+	pub name: String,
+	pub description: String,
+	pub version_number: String,
+	// Strings like "Owner-Name-1.2.3".
+	pub dependencies: Vec<String>,
+	pub download_url: String,
+	pub website_url: String,
+}
+
+pub struct ThunderstoreSource {
+	// Like "https://thunderstore.io". Can also point to a different instance, or a local stand-in server.
+	pub base_url: String,
+	// Thunderstore splits packages by community, which is usually one per game.
+	pub community: String,
+}
+
+// Whatever we use to talk to Thunderstore. Swappable, so it doesn't have to be the real thing.
+pub trait ThunderstoreHttp: Sync {
+	fn get_text(&self, url: &str) -> impl Future<Output = Result<String>> + Send;
+}
+
+pub struct ReqwestHttp;
+
+impl ThunderstoreHttp for ReqwestHttp {
+	async fn get_text(&self, url: &str) -> Result<String> {
+		Ok(reqwest::get(url).await?.error_for_status()?.text().await?)
+	}
+}
+
+impl ThunderstoreSource {
+	pub fn get_packages_url(&self) -> String {
+		format!(
+			"{}/c/{}/api/v1/package/",
+			self.base_url.trim_end_matches('/'),
+			self.community
+		)
+	}
+
+	pub async fn get_database<H: ThunderstoreHttp>(&self, http: &H) -> Result<ModDatabase> {
+		let packages: Vec<ThunderstorePackage> =
+			serde_json::from_str(&http.get_text(&self.get_packages_url()).await?)?;
+
+		Ok(get_database(&packages))
+	}
+}
+
+// Splits "Owner-Name-1.2.3" into the package ID ("Owner-Name") and the version.
+pub fn parse_dependency(dependency: &str) -> Option<(&str, &str)> {
+	dependency.rsplit_once('-')
+}
+
+fn is_bepinex_pack(package_id: &str) -> bool {
+	package_id
+		.split_once('-')
+		.is_some_and(|(_, name)| BEPINEX_PACK_NAMES.contains(&name))
+}
+
+// Thunderstore doesn't tell us the scripting backend, but IL2CPP mods depend on the IL2CPP BepInEx pack.
+// Everything else is assumed to be Mono, since that's what the regular BepInEx pack supports.
+fn get_unity_backend(version: &ThunderstorePackageVersion) -> UnityScriptingBackend {
+	if version
+		.dependencies
+		.iter()
+		.any(|dependency| dependency.to_lowercase().contains("il2cpp"))
+	{
+		UnityScriptingBackend::Il2Cpp
+	} else {
+		UnityScriptingBackend::Mono
+	}
+}
+
+fn get_dependencies(version: &ThunderstorePackageVersion) -> Vec<ModDependency> {
+	version
+		.dependencies
+		.iter()
+		.filter_map(|dependency| parse_dependency(dependency))
+		.filter(|(package_id, _)| !is_bepinex_pack(package_id))
+		.map(|(package_id, version)| ModDependency {
+			id: package_id.to_string(),
+			// Thunderstore dependencies are the minimum version that works.
+			version: Some(format!(">={version}")),
+		})
+		.collect()
+}
+
+fn get_download(version: &ThunderstorePackageVersion) -> ModDownload {
+	ModDownload {
+		id: version.version_number.clone(),
+		url: version.download_url.clone(),
+		// The BepInEx loader knows how to find the plugins and patchers folders in Thunderstore packages.
+		root: None,
+		runnable: None,
+		sha256: None,
+		minisign: None,
+	}
+}
+
+fn get_entry(package: &ThunderstorePackage) -> Option<DatabaseEntry> {
+	let latest_version = package.versions.first()?;
+
+	Some(DatabaseEntry {
+		id: package.full_name.clone(),
+		title: latest_version.name.replace('_', " "),
+		author: package.owner.clone(),
+		source_code: latest_version.website_url.clone(),
+		description: latest_version.description.clone(),
+		latest_version: Some(get_download(latest_version)),
+		engine: Some(EngineBrand::Unity),
+		engine_version_range: None,
+		unity_backend: Some(get_unity_backend(latest_version)),
+		architecture: None,
+		github: None,
+		redownload_id: None,
+		deprecated: Some(package.is_deprecated),
+		dependencies: Some(get_dependencies(latest_version)),
+		incompatibilities: None,
+		bepinex_builds: None,
+		config_file: None,
+		versions: Some(package.versions.iter().skip(1).map(get_download).collect()),
+	})
+}
+
+// Turns Thunderstore packages into the same format as the Rai Pal mod database.
+pub fn get_database(packages: &[ThunderstorePackage]) -> ModDatabase {
+	ModDatabase {
+		mods: packages
+			.iter()
+			.filter(|package| !is_bepinex_pack(&package.full_name))
+			.filter_map(get_entry)
+			.collect(),
+		// Thunderstore modpacks are just packages that depend on other packages,
+		// so they end up as regular mods.
+		modpacks: None,
+	}
+}
@@ -5,8 +5,11 @@ use rai_pal_proc_macros::serializable_struct;
 
 use crate::{
 	game_engines::{game_engine::EngineBrand, unity::UnityScriptingBackend},
-	game_mod::EngineVersionRange,
-	mod_loaders::mod_database::RunnableModData,
+	game_executable::Architecture,
+	game_mod::{EngineVersionRange, ModDependency},
+	mod_loaders::{
+		bepinex_builds::BepInExBuild, mod_database::RunnableModData, mod_integrity::InstalledFile,
+	},
 };
 
 #[serializable_struct]
@@ -17,6 +20,14 @@ pub struct Manifest {
 	pub engine: Option<EngineBrand>,
 	pub engine_version_range: Option<EngineVersionRange>,
 	pub unity_backend: Option<UnityScriptingBackend>,
+	pub architecture: Option<Architecture>,
+	pub dependencies: Option<Vec<ModDependency>>,
+	pub incompatibilities: Option<Vec<ModDependency>>,
+	pub bepinex_builds: Option<Vec<BepInExBuild>>,
+	// BepInEx config file the mod keeps its settings in, like "com.someone.mod.cfg".
+	pub config_file: Option<String>,
+	// Only in the copies of the manifest we keep for each game, with the files the mod deployed to it.
+	pub files: Option<Vec<InstalledFile>>,
 }
 
 impl Manifest {
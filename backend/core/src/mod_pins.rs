@@ -0,0 +1,79 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use log::error;
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	installed_game::InstalledGame,
+	result::{Error, Result},
+};
+
+#[serializable_struct]
+#[derive(Default)]
+pub struct ModPins {
+	// Mod ID to the version ID the game is pinned to.
+	pub versions: HashMap<String, String>,
+}
+
+const FILE_NAME: &str = "pins.json";
+
+fn get_path(game: &InstalledGame) -> Result<PathBuf> {
+	Ok(game.get_installed_mods_folder()?.join(FILE_NAME))
+}
+
+fn try_read(game: &InstalledGame) -> Result<ModPins> {
+	let path = get_path(game)?;
+	if !path.is_file() {
+		return Ok(ModPins::default());
+	}
+
+	Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn read(game: &InstalledGame) -> ModPins {
+	try_read(game).unwrap_or_else(|err| {
+		error!("Failed to read mod pins for game {}: {err}", game.id);
+		ModPins::default()
+	})
+}
+
+pub fn write(game: &InstalledGame, pins: &ModPins) -> Result {
+	Ok(fs::write(
+		get_path(game)?,
+		serde_json::to_string_pretty(pins)?,
+	)?)
+}
+
+// Updating a pinned mod would only reinstall the pinned version, so we refuse instead.
+pub fn ensure_unpinned(game: &InstalledGame, mod_id: &str) -> Result {
+	read(game).get(mod_id).map_or(Ok(()), |pinned_version| {
+		Err(Error::ModPinned(
+			mod_id.to_string(),
+			pinned_version.to_string(),
+		))
+	})
+}
+
+pub fn forget_mod(game: &InstalledGame, mod_id: &str) -> Result {
+	let mut pins = read(game);
+	if pins.unpin(mod_id) {
+		write(game, &pins)?;
+	}
+
+	Ok(())
+}
+
+impl ModPins {
+	pub fn get(&self, mod_id: &str) -> Option<&str> {
+		self.versions.get(mod_id).map(String::as_str)
+	}
+
+	pub fn pin(&mut self, mod_id: &str, version: &str) {
+		self.versions
+			.insert(mod_id.to_string(), version.to_string());
+	}
+
+	pub fn unpin(&mut self, mod_id: &str) -> bool {
+		self.versions.remove(mod_id).is_some()
+	}
+}
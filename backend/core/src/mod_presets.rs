@@ -0,0 +1,282 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	path::Path,
+};
+
+use log::warn;
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	download::DownloadProgress,
+	installed_game::InstalledGame,
+	local_mod::{self, LocalMod},
+	maps::TryGettable,
+	mod_backups,
+	mod_loaders::{
+		bepinex::BepInEx,
+		bepinex_builds::PayloadChoice,
+		bepinex_config,
+		mod_loader::{self, ModLoader, ModLoaderActions},
+	},
+	mod_pins, mod_profiles, mod_versions, remote_mod,
+	result::{Error, Result},
+};
+
+// Bump this if presets ever change in a way older versions of Rai Pal can't read.
+const FORMAT_VERSION: u32 = 1;
+
+#[serializable_struct]
+pub struct ModPresetMod {
+	pub id: String,
+	pub loader_id: String,
+	pub version: String,
+	pub enabled: bool,
+}
+
+#[serializable_struct]
+pub struct ModPreset {
+	pub format_version: u32,
+	pub mods: Vec<ModPresetMod>,
+	// Mod loader ID to the version deployed to the game, if known.
+	pub loader_versions: HashMap<String, Option<String>>,
+	// BepInEx config file name to its contents.
+	pub configs: HashMap<String, String>,
+}
+
+// Everything needed to reproduce this game's mod setup somewhere else.
+pub fn create(
+	game: &InstalledGame,
+	mod_loaders: &mod_loader::Map,
+	local_mods: &local_mod::Map,
+) -> Result<ModPreset> {
+	let disabled_mods = game.get_disabled_mods();
+
+	let mut mods: Vec<ModPresetMod> = game
+		.get_available_mods()
+		.into_iter()
+		.filter_map(|(mod_id, version)| {
+			let Some(local_mod) = local_mods.get(&mod_id) else {
+				warn!(
+					"Mod `{mod_id}` is installed in game {} but wasn't found locally, so it won't be part of the preset.",
+					game.id
+				);
+				return None;
+			};
+
+			Some(ModPresetMod {
+				enabled: !disabled_mods.contains(&mod_id),
+				loader_id: local_mod.common.loader_id.clone(),
+				id: mod_id,
+				version,
+			})
+		})
+		.collect();
+
+	mods.sort_by(|mod_a, mod_b| mod_a.id.cmp(&mod_b.id));
+
+	let loader_versions = mods
+		.iter()
+		.map(|preset_mod| &preset_mod.loader_id)
+		.collect::<HashSet<_>>()
+		.into_iter()
+		.map(|loader_id| {
+			let version = mod_loaders
+				.get(loader_id)
+				.and_then(|mod_loader| mod_loader.get_installed_version(game));
+			(loader_id.clone(), version)
+		})
+		.collect();
+
+	Ok(ModPreset {
+		format_version: FORMAT_VERSION,
+		mods,
+		loader_versions,
+		configs: bepinex_config::read_files(game)?,
+	})
+}
+
+pub fn save(preset: &ModPreset, path: &Path) -> Result {
+	Ok(fs::write(path, serde_json::to_string_pretty(preset)?)?)
+}
+
+pub fn load(path: &Path) -> Result<ModPreset> {
+	Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+// Gets the mod loader version the preset was made with, if the game has a different one.
+// BepInEx versions come from the loader index, other mod loaders only have the version they come with.
+async fn download_loader_version<F>(
+	game: &InstalledGame,
+	mod_loaders: &mod_loader::Map,
+	loader_id: &str,
+	version: &str,
+	on_progress: &F,
+) -> Result
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+{
+	let mod_loader = mod_loaders.try_get(loader_id)?;
+	if mod_loader.get_installed_version(game).as_deref() == Some(version) {
+		return Ok(());
+	}
+
+	if let ModLoader::BepInEx(_) = mod_loader {
+		return BepInEx::download_version(game, version, on_progress).await;
+	}
+
+	Err(Error::ModLoaderVersionNotAvailable(
+		loader_id.to_string(),
+		version.to_string(),
+	))
+}
+
+// Deploys the mod loader version the preset was made with, if the game has a different one.
+// Expects `download_loader_version` to have gotten it first.
+fn deploy_loader_version(
+	game: &InstalledGame,
+	mod_loaders: &mod_loader::Map,
+	loader_id: &str,
+	version: &str,
+) -> Result {
+	let mod_loader = mod_loaders.try_get(loader_id)?;
+	if mod_loader.get_installed_version(game).as_deref() == Some(version) {
+		return Ok(());
+	}
+
+	if let ModLoader::BepInEx(bepinex) = mod_loader {
+		if let Some(build) = BepInEx::get_build_with_version(game, version)? {
+			return bepinex.upgrade(
+				game,
+				build,
+				&PayloadChoice::Version(Some(version.to_string())),
+			);
+		}
+	}
+
+	Err(Error::ModLoaderVersionNotAvailable(
+		loader_id.to_string(),
+		version.to_string(),
+	))
+}
+
+// Turns this game's mod setup into the one from the preset: same mods, same versions, same configs,
+// same mod loader versions. Mods that aren't part of the preset get uninstalled.
+// If anything goes wrong after the downloads, the game goes back to how it was before.
+pub async fn apply<F>(
+	game: &InstalledGame,
+	preset: &ModPreset,
+	mod_loaders: &mod_loader::Map,
+	remote_mods: &remote_mod::Map,
+	on_progress: &F,
+) -> Result
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+{
+	// Everything gets downloaded before touching the game, so a missing version doesn't leave it half done.
+	let mut preset_local_mods = Vec::new();
+	for preset_mod in &preset.mods {
+		let local_mod = mod_versions::download_if_needed(
+			mod_loaders.try_get(&preset_mod.loader_id)?,
+			&preset_mod.id,
+			Some(&preset_mod.version),
+			remote_mods,
+			on_progress,
+		)
+		.await?;
+		preset_local_mods.push((preset_mod, local_mod));
+	}
+	for (loader_id, preset_version) in &preset.loader_versions {
+		if let Some(preset_version) = preset_version {
+			download_loader_version(game, mod_loaders, loader_id, preset_version, on_progress)
+				.await?;
+		}
+	}
+
+	mod_backups::create(game, mod_loaders)?;
+
+	let result = apply_downloaded(
+		game,
+		preset,
+		&preset_local_mods,
+		mod_loaders,
+		remote_mods,
+		on_progress,
+	)
+	.await;
+
+	mod_backups::restore_on_error(game, result)
+}
+
+async fn apply_downloaded<F>(
+	game: &InstalledGame,
+	preset: &ModPreset,
+	preset_local_mods: &[(&ModPresetMod, LocalMod)],
+	mod_loaders: &mod_loader::Map,
+	remote_mods: &remote_mod::Map,
+	on_progress: &F,
+) -> Result
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+{
+	// Pinning every mod to the preset version means dependencies also get the right version when installed.
+	let mut pins = mod_pins::read(game);
+	for (preset_mod, local_mod) in preset_local_mods {
+		if local_mod.get_version() == Some(preset_mod.version.as_str()) {
+			pins.unpin(&preset_mod.id);
+		} else {
+			pins.pin(&preset_mod.id, &preset_mod.version);
+		}
+	}
+	mod_pins::write(game, &pins)?;
+
+	for mod_id in game.get_available_mods().keys() {
+		if preset
+			.mods
+			.iter()
+			.any(|preset_mod| &preset_mod.id == mod_id)
+		{
+			continue;
+		}
+
+		let Some((mod_loader, local_mod)) = mod_loaders.values().find_map(|mod_loader| {
+			let local_mod = mod_loader.get_local_mods().ok()?.remove(mod_id)?;
+			Some((mod_loader, local_mod))
+		}) else {
+			warn!(
+				"Mod `{mod_id}` is installed in game {} but wasn't found locally, so it can't be removed when applying the preset.",
+				game.id
+			);
+			continue;
+		};
+
+		mod_loader.uninstall_mod(game, &local_mod).await?;
+		mod_profiles::forget_mod(game, mod_id)?;
+	}
+
+	for (preset_mod, local_mod) in preset_local_mods {
+		mod_loaders
+			.try_get(&preset_mod.loader_id)?
+			.install_mod(game, local_mod, remote_mods, on_progress)
+			.await?;
+	}
+
+	for (preset_mod, local_mod) in preset_local_mods {
+		mod_loaders
+			.try_get(&preset_mod.loader_id)?
+			.set_mod_enabled(game, local_mod, preset_mod.enabled)?;
+	}
+
+	for (loader_id, preset_version) in &preset.loader_versions {
+		if let Some(preset_version) = preset_version {
+			deploy_loader_version(game, mod_loaders, loader_id, preset_version)?;
+		}
+	}
+
+	// Configs go last, since installing mod loaders can reset them.
+	for (file_name, contents) in &preset.configs {
+		bepinex_config::write_file(game, file_name, contents)?;
+	}
+
+	Ok(())
+}
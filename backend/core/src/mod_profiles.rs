@@ -0,0 +1,186 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	path::PathBuf,
+};
+
+use log::{error, warn};
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	installed_game::InstalledGame,
+	local_mod::{self, LocalMod},
+	maps::TryGettable,
+	mod_loaders::mod_loader::{self, ModLoaderActions},
+	result::{Error, Result},
+};
+
+#[serializable_struct]
+#[derive(Default)]
+pub struct ModProfile {
+	pub enabled_mods: HashSet<String>,
+}
+
+#[serializable_struct]
+#[derive(Default)]
+pub struct ModProfiles {
+	pub active: Option<String>,
+	pub profiles: HashMap<String, ModProfile>,
+}
+
+const FILE_NAME: &str = "mod-profiles.json";
+
+fn get_path(game: &InstalledGame) -> Result<PathBuf> {
+	Ok(game.get_installed_mods_folder()?.join(FILE_NAME))
+}
+
+fn try_read(game: &InstalledGame) -> Result<ModProfiles> {
+	let path = get_path(game)?;
+	if !path.is_file() {
+		return Ok(ModProfiles::default());
+	}
+
+	Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn read(game: &InstalledGame) -> ModProfiles {
+	try_read(game).unwrap_or_else(|err| {
+		error!("Failed to read mod profiles for game {}: {err}", game.id);
+		ModProfiles::default()
+	})
+}
+
+pub fn write(game: &InstalledGame, profiles: &ModProfiles) -> Result {
+	Ok(fs::write(
+		get_path(game)?,
+		serde_json::to_string_pretty(profiles)?,
+	)?)
+}
+
+impl ModProfiles {
+	pub fn try_get_profile(&self, profile_name: &str) -> Result<&ModProfile> {
+		self.profiles
+			.get(profile_name)
+			.ok_or_else(|| Error::ModProfileNotFound(profile_name.to_string()))
+	}
+
+	pub fn save_profile(&mut self, profile_name: &str, enabled_mods: HashSet<String>) {
+		self.profiles
+			.insert(profile_name.to_string(), ModProfile { enabled_mods });
+		self.active = Some(profile_name.to_string());
+	}
+
+	pub fn remove_profile(&mut self, profile_name: &str) -> Result {
+		if self.profiles.remove(profile_name).is_none() {
+			return Err(Error::ModProfileNotFound(profile_name.to_string()));
+		}
+
+		if self.active.as_deref() == Some(profile_name) {
+			self.active = None;
+		}
+
+		Ok(())
+	}
+
+	// Returns true if any profile changed.
+	pub fn remove_mod(&mut self, mod_id: &str) -> bool {
+		self.profiles.values_mut().fold(false, |changed, profile| {
+			profile.enabled_mods.remove(mod_id) || changed
+		})
+	}
+
+	// Returns true if the active profile changed.
+	pub fn set_enabled_in_active_profile(&mut self, mod_id: &str, enabled: bool) -> bool {
+		self.active
+			.as_ref()
+			.and_then(|active| self.profiles.get_mut(active))
+			.is_some_and(|profile| {
+				if enabled {
+					profile.enabled_mods.insert(mod_id.to_string())
+				} else {
+					profile.enabled_mods.remove(mod_id)
+				}
+			})
+	}
+}
+
+// Saves the mods that are currently enabled in this game as a profile, and makes it the active one.
+pub fn save_current(game: &InstalledGame, profile_name: &str) -> Result {
+	let disabled_mods = game.get_disabled_mods();
+	let enabled_mods = game
+		.get_available_mods()
+		.into_keys()
+		.filter(|mod_id| !disabled_mods.contains(mod_id))
+		.collect();
+
+	let mut profiles = read(game);
+	profiles.save_profile(profile_name, enabled_mods);
+
+	write(game, &profiles)
+}
+
+// Uninstalled mods would otherwise stay in the profiles, and switching to them would try to deploy mods that are gone.
+pub fn forget_mod(game: &InstalledGame, mod_id: &str) -> Result {
+	let mut profiles = read(game);
+	if profiles.remove_mod(mod_id) {
+		write(game, &profiles)?;
+	}
+
+	Ok(())
+}
+
+// Toggles the mod in the game and in the active profile, so switching profiles later doesn't undo it.
+pub fn set_mod_enabled(
+	game: &InstalledGame,
+	mod_loaders: &mod_loader::Map,
+	local_mod: &LocalMod,
+	enabled: bool,
+) -> Result {
+	mod_loaders
+		.try_get(&local_mod.common.loader_id)?
+		.set_mod_enabled(game, local_mod, enabled)?;
+
+	let mut profiles = read(game);
+	if profiles.set_enabled_in_active_profile(&local_mod.common.id, enabled) {
+		write(game, &profiles)?;
+	}
+
+	Ok(())
+}
+
+pub fn remove(game: &InstalledGame, profile_name: &str) -> Result {
+	let mut profiles = read(game);
+	profiles.remove_profile(profile_name)?;
+
+	write(game, &profiles)
+}
+
+// Parks every installed mod that isn't part of the profile, and re-deploys the ones that are.
+// Mods in the profile that aren't installed in this game are ignored.
+pub fn activate(
+	game: &InstalledGame,
+	profile_name: &str,
+	mod_loaders: &mod_loader::Map,
+	local_mods: &local_mod::Map,
+) -> Result {
+	let mut profiles = read(game);
+	let profile = profiles.try_get_profile(profile_name)?;
+
+	for mod_id in game.get_available_mods().keys() {
+		let Some(local_mod) = local_mods.get(mod_id) else {
+			warn!(
+				"Mod `{mod_id}` is installed in game {} but wasn't found locally, so it can't be toggled by profile `{profile_name}`.",
+				game.id
+			);
+			continue;
+		};
+
+		mod_loaders
+			.try_get(&local_mod.common.loader_id)?
+			.set_mod_enabled(game, local_mod, profile.enabled_mods.contains(mod_id))?;
+	}
+
+	profiles.active = Some(profile_name.to_string());
+
+	write(game, &profiles)
+}
@@ -0,0 +1,57 @@
+use std::cmp::Ordering;
+
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	game::{Game, GameId},
+	mod_version_requirements, remote_mod,
+};
+
+#[serializable_struct]
+pub struct OutdatedMod {
+	pub game_id: GameId,
+	pub mod_id: String,
+	pub installed_version: String,
+	pub latest_version: String,
+}
+
+// Different version strings that compare as equal still count as outdated,
+// since that's what happens when the database bumps a mod's redownload ID.
+// If the installed version is newer than the database one, we leave it alone.
+fn is_outdated(installed_version: &str, latest_version: &str) -> bool {
+	installed_version != latest_version
+		&& mod_version_requirements::compare(installed_version, latest_version) != Ordering::Greater
+}
+
+pub fn get_outdated_mods<'a>(
+	games: impl IntoIterator<Item = &'a Game>,
+	remote_mods: &remote_mod::Map,
+) -> Vec<OutdatedMod> {
+	let mut outdated_mods: Vec<_> = games
+		.into_iter()
+		.filter_map(|game| Some((&game.id, game.installed_game.as_ref()?)))
+		.flat_map(|(game_id, installed_game)| {
+			installed_game.installed_mod_versions.iter().filter_map(
+				move |(mod_id, installed_version)| {
+					// Pinned mods stay on their version on purpose.
+					if installed_game.pinned_mod_versions.contains_key(mod_id) {
+						return None;
+					}
+
+					let latest_version = remote_mods.get(mod_id)?.data.latest_version.as_ref()?;
+
+					is_outdated(installed_version, &latest_version.id).then(|| OutdatedMod {
+						game_id: game_id.clone(),
+						mod_id: mod_id.clone(),
+						installed_version: installed_version.clone(),
+						latest_version: latest_version.id.clone(),
+					})
+				},
+			)
+		})
+		.collect();
+
+	outdated_mods.sort_by(|mod_a, mod_b| mod_a.mod_id.cmp(&mod_b.mod_id));
+
+	outdated_mods
+}
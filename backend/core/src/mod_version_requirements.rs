@@ -0,0 +1,85 @@
+use std::cmp::Ordering;
+
+use lazy_regex::regex;
+
+// Mod versions are whatever the mod author used as a release tag (v1.2.3, 1.2, 0.4.1-beta, etc),
+// so parsing is pretty lax. We only care about the numbers, in order.
+fn parse_numbers(version: &str) -> Vec<u32> {
+	// Versions can have a redownload ID appended to them, like "v1.2.3/2", which we don't want to compare.
+	let version = version.split('/').next().unwrap_or(version);
+
+	regex!(r"\d+")
+		.find_iter(version)
+		.filter_map(|capture| capture.as_str().parse::<u32>().ok())
+		.collect()
+}
+
+pub fn compare(version_a: &str, version_b: &str) -> Ordering {
+	let numbers_a = parse_numbers(version_a);
+	let numbers_b = parse_numbers(version_b);
+
+	for index in 0..numbers_a.len().max(numbers_b.len()) {
+		let part_a = numbers_a.get(index).copied().unwrap_or(0);
+		let part_b = numbers_b.get(index).copied().unwrap_or(0);
+
+		match part_a.cmp(&part_b) {
+			Ordering::Equal => {}
+			other => return other,
+		}
+	}
+
+	Ordering::Equal
+}
+
+fn prefix_matches(version: &str, required: &str, prefix_length: usize) -> bool {
+	let version_numbers = parse_numbers(version);
+	let required_numbers = parse_numbers(required);
+
+	compare(version, required) != Ordering::Less
+		&& (0..prefix_length).all(|index| {
+			version_numbers.get(index).copied().unwrap_or(0)
+				== required_numbers.get(index).copied().unwrap_or(0)
+		})
+}
+
+// Order matters here, since some operators are prefixes of others.
+const OPERATORS: [&str; 7] = [">=", "<=", ">", "<", "^", "~", "="];
+
+fn matches_comparator(version: &str, comparator: &str) -> bool {
+	let comparator = comparator.trim();
+
+	if comparator.is_empty() || comparator == "*" {
+		return true;
+	}
+
+	let (operator, required) = OPERATORS
+		.iter()
+		.find_map(|operator| {
+			comparator
+				.strip_prefix(operator)
+				.map(|required| (*operator, required.trim()))
+		})
+		.unwrap_or(("=", comparator));
+
+	match operator {
+		">=" => compare(version, required) != Ordering::Less,
+		"<=" => compare(version, required) != Ordering::Greater,
+		">" => compare(version, required) == Ordering::Greater,
+		"<" => compare(version, required) == Ordering::Less,
+		// Same major version, at least the required one.
+		"^" => prefix_matches(version, required, 1),
+		// Same major and minor version, at least the required one.
+		"~" => prefix_matches(version, required, 2),
+		_ => compare(version, required) == Ordering::Equal,
+	}
+}
+
+// Requirements are a comma-separated list of comparators, like ">=1.2, <2".
+// A missing or empty requirement matches any version.
+pub fn matches_requirement(version: &str, requirement: Option<&str>) -> bool {
+	requirement.is_none_or(|requirement| {
+		requirement
+			.split(',')
+			.all(|comparator| matches_comparator(version, comparator))
+	})
+}
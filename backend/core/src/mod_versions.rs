@@ -0,0 +1,284 @@
+use std::{cmp::Ordering, collections::HashSet, path::PathBuf};
+
+use log::error;
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	download::DownloadProgress,
+	game_mod::CommonModData,
+	installed_game::InstalledGame,
+	local_mod::{self, LocalMod, LocalModData},
+	maps::TryGettable,
+	mod_loaders::{
+		mod_database::ModDownload,
+		mod_loader::{ModLoader, ModLoaderActions},
+	},
+	mod_manifest::{self, Manifest},
+	mod_pins, mod_version_requirements,
+	paths::{self, glob_path},
+	remote_mod::{self, RemoteMod},
+	result::{Error, Result},
+};
+
+#[serializable_struct]
+pub struct ModVersion {
+	pub id: String,
+	pub is_latest: bool,
+	pub is_downloaded: bool,
+	// Empty for versions we only have locally, since there's nowhere to download them from.
+	pub download: Option<ModDownload>,
+}
+
+// Older downloaded versions of a mod are kept here, next to the mod loader's regular mods folder.
+// The latest version only lives in the regular mods folder, so each version is stored once.
+pub fn get_versions_path(loader_id: &str, mod_id: &str) -> Result<PathBuf> {
+	Ok(paths::installed_mods_path()?
+		.join(loader_id)
+		.join("versions")
+		.join(mod_id))
+}
+
+// Version IDs are release tags, which can have pretty much anything in them,
+// including the slash from redownload IDs.
+fn get_version_folder_name(version: &str) -> String {
+	version
+		.chars()
+		.map(|character| {
+			if character.is_ascii_alphanumeric() || "-_.".contains(character) {
+				character
+			} else {
+				'_'
+			}
+		})
+		.collect()
+}
+
+pub fn get_version_path(loader_id: &str, mod_id: &str, version: &str) -> Result<PathBuf> {
+	Ok(get_versions_path(loader_id, mod_id)?.join(get_version_folder_name(version)))
+}
+
+// Sorted from newest to oldest. Includes the latest version if the local mod is given.
+pub fn get_downloaded_versions(
+	loader_id: &str,
+	mod_id: &str,
+	local_mod: Option<&LocalMod>,
+) -> Vec<String> {
+	let versions_path = match get_versions_path(loader_id, mod_id) {
+		Ok(versions_path) => versions_path,
+		Err(err) => {
+			error!("Failed to get versions path for mod {mod_id}: {err}");
+			return Vec::new();
+		}
+	};
+
+	let mut versions: Vec<_> = glob_path(&versions_path.join("*").join(Manifest::FILE_NAME))
+		.iter()
+		.filter_map(|manifest_path| Some(mod_manifest::get(manifest_path)?.version))
+		.collect();
+
+	if let Some(latest_version) = local_mod.and_then(LocalMod::get_version) {
+		if !versions.iter().any(|version| version == latest_version) {
+			versions.push(latest_version.to_string());
+		}
+	}
+
+	versions
+		.sort_by(|version_a, version_b| mod_version_requirements::compare(version_b, version_a));
+
+	versions
+}
+
+// Same mod, but with its files coming from the given downloaded version.
+pub fn get_local_mod_version(local_mod: &LocalMod, version: &str) -> Result<LocalMod> {
+	let version_path =
+		get_version_path(&local_mod.common.loader_id, &local_mod.common.id, version)?;
+
+	if !version_path.is_dir() {
+		return Err(Error::ModVersionNotDownloaded(
+			local_mod.common.id.clone(),
+			version.to_string(),
+		));
+	}
+
+	let manifest = mod_manifest::get(&local_mod::get_manifest_path(&version_path));
+
+	// Requirements can change between versions, so they come from the version's own manifest.
+	let common = manifest.as_ref().map_or_else(
+		|| local_mod.common.clone(),
+		|manifest| CommonModData {
+			engine: manifest.engine.or(local_mod.common.engine),
+			unity_backend: manifest.unity_backend.or(local_mod.common.unity_backend),
+			engine_version_range: manifest.engine_version_range.clone(),
+			architecture: manifest.architecture,
+			..local_mod.common.clone()
+		},
+	);
+
+	Ok(LocalMod {
+		data: LocalModData {
+			manifest,
+			path: version_path,
+		},
+		common,
+	})
+}
+
+pub fn is_downloaded(local_mod: &LocalMod, version: &str) -> bool {
+	local_mod.get_version() == Some(version)
+		|| get_version_path(&local_mod.common.loader_id, &local_mod.common.id, version)
+			.is_ok_and(|version_path| version_path.is_dir())
+}
+
+// Games pinned to a version of this mod get that version, everyone else gets the latest one.
+pub fn get_pinned_local_mod(game: &InstalledGame, local_mod: &LocalMod) -> Result<LocalMod> {
+	match mod_pins::read(game).get(&local_mod.common.id) {
+		Some(pinned_version) if Some(pinned_version) != local_mod.get_version() => {
+			get_local_mod_version(local_mod, pinned_version)
+		}
+		_ => Ok(local_mod.clone()),
+	}
+}
+
+// The newest downloaded version that's older than the installed one.
+pub fn get_rollback_version(local_mod: &LocalMod, installed_version: &str) -> Result<String> {
+	get_downloaded_versions(
+		&local_mod.common.loader_id,
+		&local_mod.common.id,
+		Some(local_mod),
+	)
+	.into_iter()
+	.find(|version| mod_version_requirements::compare(version, installed_version) == Ordering::Less)
+	.ok_or_else(|| Error::ModRollbackNotAvailable(local_mod.common.id.clone()))
+}
+
+async fn get_remote_versions(remote_mod: &RemoteMod) -> Vec<ModDownload> {
+	let mut versions = remote_mod.data.versions.clone();
+
+	if let Some(github) = &remote_mod.data.github {
+		match github.get_versions().await {
+			Ok(github_versions) => versions.extend(github_versions),
+			Err(err) => error!(
+				"Failed to get GitHub versions for mod {}: {err}",
+				remote_mod.common.id
+			),
+		}
+	}
+
+	if let Some(latest_version) = &remote_mod.data.latest_version {
+		versions.push(latest_version.clone());
+	}
+
+	versions
+}
+
+pub async fn get_download(remote_mod: &RemoteMod, version: &str) -> Result<ModDownload> {
+	get_remote_versions(remote_mod)
+		.await
+		.into_iter()
+		.find(|download| download.id == version)
+		.ok_or_else(|| Error::ModVersionNotFound(remote_mod.common.id.clone(), version.to_string()))
+}
+
+// Combines the versions that are available to download with the ones we already have.
+pub async fn get_versions(
+	loader_id: &str,
+	mod_id: &str,
+	local_mod: Option<&LocalMod>,
+	remote_mod: Option<&RemoteMod>,
+) -> Vec<ModVersion> {
+	let downloaded_versions: HashSet<String> =
+		get_downloaded_versions(loader_id, mod_id, local_mod)
+			.into_iter()
+			.collect();
+
+	let remote_versions = match remote_mod {
+		Some(remote_mod) => get_remote_versions(remote_mod).await,
+		None => Vec::new(),
+	};
+
+	let latest_version_id = remote_mod
+		.and_then(|remote_mod| remote_mod.data.latest_version.as_ref())
+		.map(|latest_version| latest_version.id.clone());
+
+	let mut versions: Vec<ModVersion> = Vec::new();
+
+	for download in remote_versions {
+		if versions.iter().any(|version| version.id == download.id) {
+			continue;
+		}
+
+		versions.push(ModVersion {
+			is_latest: latest_version_id.as_ref() == Some(&download.id),
+			is_downloaded: downloaded_versions.contains(&download.id),
+			id: download.id.clone(),
+			download: Some(download),
+		});
+	}
+
+	for downloaded_version in downloaded_versions {
+		if versions
+			.iter()
+			.any(|version| version.id == downloaded_version)
+		{
+			continue;
+		}
+
+		versions.push(ModVersion {
+			is_latest: latest_version_id.as_ref() == Some(&downloaded_version),
+			is_downloaded: true,
+			id: downloaded_version,
+			download: None,
+		});
+	}
+
+	versions.sort_by(|version_a, version_b| {
+		mod_version_requirements::compare(&version_b.id, &version_a.id)
+	});
+
+	versions
+}
+
+// Makes sure the given version of the mod is downloaded, and returns the mod from the regular mods folder.
+// Without a version, the mod gets updated to the latest one instead.
+pub async fn download_if_needed<F>(
+	mod_loader: &ModLoader,
+	mod_id: &str,
+	version: Option<&str>,
+	remote_mods: &remote_mod::Map,
+	on_progress: &F,
+) -> Result<LocalMod>
+where
+	F: Fn(DownloadProgress) + Send + Sync,
+{
+	let mut local_mod = mod_loader.get_local_mods()?.remove(mod_id);
+
+	let latest_version = remote_mods
+		.get(mod_id)
+		.and_then(|remote_mod| remote_mod.data.latest_version.as_ref())
+		.map(|latest_version| latest_version.id.as_str());
+	let is_outdated = version.is_none()
+		&& latest_version.is_some()
+		&& local_mod.as_ref().and_then(LocalMod::get_version) != latest_version;
+
+	if local_mod.is_none() || is_outdated {
+		mod_loader
+			.download_mod(remote_mods.try_get(mod_id)?, on_progress)
+			.await?;
+		local_mod = mod_loader.get_local_mods()?.remove(mod_id);
+	}
+
+	let local_mod = local_mod.ok_or_else(|| Error::DataEntryNotFound(mod_id.to_string()))?;
+
+	if let Some(version) = version {
+		if !is_downloaded(&local_mod, version) {
+			let remote_mod = remote_mods.try_get(mod_id)?;
+			let download = get_download(remote_mod, version).await?;
+
+			mod_loader
+				.download_mod_version(remote_mod, &download, on_progress)
+				.await?;
+		}
+	}
+
+	Ok(local_mod)
+}
@@ -5,7 +5,6 @@ use std::{
 	path::{Path, PathBuf},
 };
 
-use directories::ProjectDirs;
 use globwalk::glob;
 use log;
 
@@ -39,13 +38,20 @@ pub fn path_parent(path: &Path) -> Result<&Path> {
 		.ok_or_else(|| Error::PathParentNotFound(path.to_path_buf()))
 }
 
+#[cfg(not(test))]
 pub fn app_data_path() -> Result<PathBuf> {
-	let project_dirs =
-		ProjectDirs::from("com", "raicuparta", "rai-pal").ok_or_else(Error::AppDataNotFound)?;
+	let project_dirs = directories::ProjectDirs::from("com", "raicuparta", "rai-pal")
+		.ok_or_else(Error::AppDataNotFound)?;
 
 	Ok(project_dirs.data_dir().to_path_buf())
 }
 
+// Tests get their own throwaway data folder, so they never touch the real one.
+#[cfg(test)]
+pub fn app_data_path() -> Result<PathBuf> {
+	Ok(env::temp_dir().join(format!("rai-pal-tests-{}", std::process::id())))
+}
+
 pub fn logs_path() -> Result<PathBuf> {
 	Ok(app_data_path()?.join("logs"))
 }
@@ -81,6 +81,7 @@ fn get_game_from_path(path: &Path) -> Result<Game> {
 		file_name_without_extension(path)?,
 	);
 	game.installed_game = Some(installed_game);
+	game.link_installed_game();
 	Ok(game)
 }
 
@@ -0,0 +1,238 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
+
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use rai_pal_proc_macros::serializable_struct;
+use reqwest::{StatusCode, header};
+use serde::de::DeserializeOwned;
+
+use crate::{
+	paths,
+	result::{Error, Result},
+	timestamps::now,
+};
+
+// Mirrors of github.com/Raicuparta/rai-pal-db, tried in this order.
+// GitHub Pages is the main one, but it's been known to go down or get blocked.
+const MIRRORS: [&str; 2] = [
+	"https://raicuparta.github.io/rai-pal-db",
+	"https://raw.githubusercontent.com/Raicuparta/rai-pal-db/main",
+];
+
+lazy_static! {
+	static ref STATUSES: Mutex<HashMap<String, DatabaseStatus>> = Mutex::new(HashMap::new());
+}
+
+#[serializable_struct]
+pub struct DatabaseStatus {
+	pub path: String,
+	// Mirror the data was last confirmed up to date with.
+	pub mirror: Option<String>,
+	// Unix timestamp, in seconds.
+	pub updated_at: u64,
+	pub age_seconds: u64,
+	// True if none of the mirrors could be reached, so we're using whatever we had cached.
+	pub is_offline: bool,
+}
+
+#[serializable_struct]
+struct CacheMetadata {
+	url: String,
+	etag: Option<String>,
+	last_modified: Option<String>,
+	updated_at: u64,
+}
+
+enum FetchResult {
+	NotModified,
+	Modified {
+		body: String,
+		etag: Option<String>,
+		last_modified: Option<String>,
+	},
+}
+
+fn get_cache_path(path: &str) -> Result<PathBuf> {
+	Ok(paths::app_data_path()?
+		.join("cache")
+		.join("database")
+		.join(path))
+}
+
+fn get_metadata_path(path: &str) -> Result<PathBuf> {
+	let mut metadata_path = get_cache_path(path)?.into_os_string();
+	metadata_path.push(".meta");
+
+	Ok(PathBuf::from(metadata_path))
+}
+
+fn read_cache(path: &str) -> Option<(CacheMetadata, String)> {
+	let try_read = || -> Result<Option<(CacheMetadata, String)>> {
+		let cache_path = get_cache_path(path)?;
+		let metadata_path = get_metadata_path(path)?;
+
+		if !cache_path.is_file() || !metadata_path.is_file() {
+			return Ok(None);
+		}
+
+		Ok(Some((
+			serde_json::from_str(&fs::read_to_string(metadata_path)?)?,
+			fs::read_to_string(cache_path)?,
+		)))
+	};
+
+	try_read().unwrap_or_else(|err| {
+		error!("Failed to read database cache for {path}: {err}");
+		None
+	})
+}
+
+fn write_cache(path: &str, metadata: &CacheMetadata, body: Option<&str>) -> Result {
+	let cache_path = get_cache_path(path)?;
+	fs::create_dir_all(paths::path_parent(&cache_path)?)?;
+
+	if let Some(body) = body {
+		fs::write(cache_path, body)?;
+	}
+	fs::write(
+		get_metadata_path(path)?,
+		serde_json::to_string_pretty(metadata)?,
+	)?;
+
+	Ok(())
+}
+
+fn set_status(path: &str, mirror: Option<&str>, updated_at: u64, is_offline: bool) {
+	match STATUSES.lock() {
+		Ok(mut statuses) => {
+			statuses.insert(
+				path.to_string(),
+				DatabaseStatus {
+					path: path.to_string(),
+					mirror: mirror.map(ToString::to_string),
+					updated_at,
+					age_seconds: 0,
+					is_offline,
+				},
+			);
+		}
+		Err(err) => error!("Failed to set database status for {path}: {err}"),
+	}
+}
+
+pub fn get_statuses() -> Vec<DatabaseStatus> {
+	let now = now();
+
+	STATUSES
+		.lock()
+		.map(|statuses| {
+			statuses
+				.values()
+				.map(|status| DatabaseStatus {
+					age_seconds: now.saturating_sub(status.updated_at),
+					..status.clone()
+				})
+				.collect()
+		})
+		.unwrap_or_default()
+}
+
+fn get_header(response: &reqwest::Response, name: header::HeaderName) -> Option<String> {
+	Some(response.headers().get(name)?.to_str().ok()?.to_string())
+}
+
+async fn fetch(url: &str, cached_metadata: Option<&CacheMetadata>) -> Result<FetchResult> {
+	let mut request = reqwest::Client::new().get(url);
+
+	// Validators are only valid for the url they came from.
+	if let Some(metadata) = cached_metadata.filter(|metadata| metadata.url == url) {
+		if let Some(etag) = &metadata.etag {
+			request = request.header(header::IF_NONE_MATCH, etag);
+		}
+		if let Some(last_modified) = &metadata.last_modified {
+			request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+		}
+	}
+
+	let response = request.send().await?;
+
+	if response.status() == StatusCode::NOT_MODIFIED {
+		return Ok(FetchResult::NotModified);
+	}
+
+	let response = response.error_for_status()?;
+	let etag = get_header(&response, header::ETAG);
+	let last_modified = get_header(&response, header::LAST_MODIFIED);
+
+	Ok(FetchResult::Modified {
+		body: response.text().await?,
+		etag,
+		last_modified,
+	})
+}
+
+// Gets a file from the database, going through the mirrors until one of them works.
+// The last good response is cached on disk, so the app keeps working when offline.
+// `path` is relative to the database root, like "mod-db/0/bepinex.json".
+pub async fn get<T: DeserializeOwned>(path: &str) -> Result<T> {
+	let cached = read_cache(path);
+	let cached_metadata = cached.as_ref().map(|(metadata, _)| metadata);
+
+	for mirror in MIRRORS {
+		let url = format!("{mirror}/{path}");
+
+		match fetch(&url, cached_metadata).await {
+			Ok(FetchResult::NotModified) => {
+				if let Some((metadata, body)) = &cached {
+					match serde_json::from_str(body) {
+						Ok(data) => {
+							let metadata = CacheMetadata {
+								updated_at: now(),
+								..metadata.clone()
+							};
+							if let Err(err) = write_cache(path, &metadata, None) {
+								error!(
+									"Failed to update database cache metadata for {path}: {err}"
+								);
+							}
+							set_status(path, Some(mirror), metadata.updated_at, false);
+							info!("Database {path} hasn't changed since it was cached.");
+							return Ok(data);
+						}
+						Err(err) => error!("Failed to parse cached database {path}: {err}"),
+					}
+				}
+			}
+			Ok(FetchResult::Modified {
+				body,
+				etag,
+				last_modified,
+			}) => match serde_json::from_str(&body) {
+				Ok(data) => {
+					let metadata = CacheMetadata {
+						url,
+						etag,
+						last_modified,
+						updated_at: now(),
+					};
+					if let Err(err) = write_cache(path, &metadata, Some(&body)) {
+						error!("Failed to write database cache for {path}: {err}");
+					}
+					set_status(path, Some(mirror), metadata.updated_at, false);
+					return Ok(data);
+				}
+				Err(err) => warn!("Failed to parse database {path} from {mirror}: {err}"),
+			},
+			Err(err) => warn!("Failed to get database {path} from {mirror}: {err}"),
+		}
+	}
+
+	if let Some((metadata, body)) = cached {
+		warn!("All database mirrors failed for {path}, using the cached version.");
+		let data = serde_json::from_str(&body)?;
+		set_status(path, None, metadata.updated_at, true);
+		return Ok(data);
+	}
+
+	Err(Error::DatabaseUnavailable(path.to_string()))
+}
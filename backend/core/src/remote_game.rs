@@ -8,11 +8,9 @@ use crate::game_engines::game_engine::{
 };
 use crate::game_subscription::GameSubscription;
 use crate::providers::provider::ProviderId;
+use crate::remote_database;
 use crate::result::Result;
 
-// TODO: fall back to github if this one fails.
-const URL_BASE: &str = "https://raicuparta.github.io/rai-pal-db/game-db";
-
 // The repository over at github.com/Raicuparta/rai-pal-db can have multiple versions of the database.
 // This way we prevent old versions of Rai Pal from breaking unless we want them to.
 // So when you need to change the database in a backwards-incompatible way,
@@ -83,11 +81,8 @@ fn parse_version(version: &str) -> Option<EngineVersion> {
 pub type Map = HashMap<ProviderId, HashMap<String, RemoteGame>>;
 
 pub async fn get() -> Result<Map> {
-	let url = format!("{URL_BASE}/{DATABASE_VERSION}/games.json");
-
-	let response = reqwest::get(&url).await?;
-
-	let game_database: Vec<GameDatabaseEntry> = response.json().await?;
+	let game_database: Vec<GameDatabaseEntry> =
+		remote_database::get(&format!("game-db/{DATABASE_VERSION}/games.json")).await?;
 
 	let games: Vec<_> = game_database
 		.into_iter()
@@ -133,7 +128,7 @@ pub async fn get() -> Result<Map> {
 			map
 		});
 
-	log::info!("Remote game database downloaded and parsed successfully.");
+	log::info!("Remote game database loaded and parsed successfully.");
 
 	Ok(game_map)
 }
@@ -2,7 +2,13 @@ use std::collections::HashMap;
 
 use rai_pal_proc_macros::serializable_struct;
 
-use crate::{game_mod::CommonModData, mod_loaders::mod_database::ModDownload};
+use crate::{
+	game_mod::{CommonModData, ModDependency},
+	mod_loaders::{
+		bepinex_builds::BepInExBuild,
+		mod_database::{ModDownload, ModGithubInfo},
+	},
+};
 
 #[serializable_struct]
 pub struct RemoteModData {
@@ -12,6 +18,14 @@ pub struct RemoteModData {
 	pub source_code: String,
 	pub description: String,
 	pub latest_version: Option<ModDownload>,
+	pub dependencies: Vec<ModDependency>,
+	pub incompatibilities: Vec<ModDependency>,
+	pub bepinex_builds: Vec<BepInExBuild>,
+	pub config_file: Option<String>,
+	pub versions: Vec<ModDownload>,
+	pub github: Option<ModGithubInfo>,
+	// Label of the mod database this mod came from.
+	pub source: String,
 }
 
 #[serializable_struct]
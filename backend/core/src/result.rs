@@ -25,6 +25,13 @@ pub enum Error {
 		zip::result::ZipError,
 	),
 
+	#[error(transparent)]
+	SevenZip(
+		#[from]
+		#[serde(skip)]
+		sevenz_rust::Error,
+	),
+
 	#[error(transparent)]
 	Json(
 		#[from]
@@ -111,7 +118,9 @@ pub enum Error {
 	#[error("Failed to retrieve Unity version from asset `{0}`")]
 	FailedToParseUnityVersionAsset(PathBuf),
 
-	#[error("Failed to install mod, because the known game information is insufficient. Missing information: `{0}`. Game: `{1}`")]
+	#[error(
+		"Failed to install mod, because the known game information is insufficient. Missing information: `{0}`. Game: `{1}`"
+	)]
 	ModInstallInfoInsufficient(String, PathBuf),
 
 	#[error("Failed to get game data from path `{0}`")]
@@ -129,15 +138,125 @@ pub enum Error {
 	#[error("Download not available for mod `{0}`")]
 	ModDownloadNotAvailable(String),
 
-	#[error("Operation can't be completed without a `runnable` section in the mod manifest (rai-pal-manifest.json) `{0}`")]
+	#[error("Download `{0}` was cancelled.")]
+	DownloadCancelled(String),
+
+	#[error("Failed to verify download for mod `{0}`: {1}")]
+	ModVerificationFailed(String, String),
+
+	#[error("Couldn't get database `{0}` from any mirror, and it isn't cached.")]
+	DatabaseUnavailable(String),
+
+	#[error("Invalid mod source url `{0}`: {1}")]
+	ModSourceUrlInvalid(String, String),
+
+	#[error("Refusing to extract entry `{0}` from archive `{1}`: {2}")]
+	ArchiveEntryUnsafe(String, PathBuf, String),
+
+	#[error("Archive `{0}` is too large to extract: {1}")]
+	ArchiveTooLarge(PathBuf, String),
+
+	#[error("Can't extract `{0}`: {1}")]
+	ArchiveFormatUnsupported(PathBuf, String),
+
+	#[error("Mod `{0}` has no plugins or patchers folders, dlls or executables.")]
+	ModImportLayoutUnknown(String),
+
+	#[error("Can't import runnable mod `{0}` without choosing which of its executables to run.")]
+	ModImportExecutableMissing(String),
+
+	#[error("Invalid mod ID `{0}`.")]
+	ModImportInvalidId(String),
+
+	#[error("Runnable path `{1}` of mod `{0}` has to point to a file inside the mod folder.")]
+	ModImportRunnablePathInvalid(String, String),
+
+	#[error("A mod with ID `{0}` already exists.")]
+	ModAlreadyExists(String),
+
+	#[error("Version `{1}` of mod `{0}` hasn't been downloaded.")]
+	ModVersionNotDownloaded(String, String),
+
+	#[error("Couldn't find version `{1}` of mod `{0}`.")]
+	ModVersionNotFound(String, String),
+
+	#[error("There's no older downloaded version of mod `{0}` to roll back to.")]
+	ModRollbackNotAvailable(String),
+
+	#[error("Mod `{0}` is pinned to version `{1}` in this game. Unpin it to update.")]
+	ModPinned(String, String),
+
+	#[error(
+		"Operation can't be completed without a `runnable` section in the mod manifest (rai-pal-manifest.json) `{0}`"
+	)]
 	RunnableManifestNotFound(String),
 
 	#[error("Can't run mod with ID `{0}` because it isn't a runnable mod.")]
 	CantRunNonRunnable(String),
 
+	#[error("Runnable mod `{0}` isn't running.")]
+	RunnableNotRunning(String),
+
+	#[error("Failed to access the running runnables: {0}")]
+	RunnableProcessRegistry(String),
+
 	#[error("Provider ID {0} is invalid in this platform.")]
 	InvalidProviderId(String),
 
+	#[error(
+		"Mod `{0}` requires `{1}`, but no compatible version of it was found locally or in the database."
+	)]
+	ModDependencyNotFound(String, String),
+
+	#[error("Mod `{0}` can't be installed alongside mod `{1}`, since they're incompatible.")]
+	ModConflict(String, String),
+
+	#[error("Found circular dependency between mods: {0}")]
+	ModDependencyCycle(String),
+
+	#[error("Mod `{0}` isn't compatible with this game. Issues found: {1}")]
+	ModIncompatibleWithGame(String, String),
+
+	#[error("Mod profile `{0}` not found.")]
+	ModProfileNotFound(String),
+
+	#[error("Modpack `{0}` not found in any mod source.")]
+	ModpackNotFound(String),
+
+	#[error("Mod config file `{0}` not found.")]
+	ModConfigNotFound(String),
+
+	#[error("Setting `{1}` not found in mod config file `{0}`.")]
+	ModConfigEntryNotFound(String, String),
+
+	#[error("Invalid value for setting `{1}` in mod config file `{0}`: {2}")]
+	ModConfigValueInvalid(String, String, String),
+
+	#[error("BepInEx build `{0}` doesn't support the `{1}` scripting backend.")]
+	BepInExBuildUnsupported(String, String),
+
+	#[error("BepInEx build `{0}` isn't available for the `{1}` scripting backend.")]
+	BepInExBuildNotAvailable(String, String),
+
+	#[error("Files for BepInEx build `{0}` are missing. Couldn't find `{1}`.")]
+	BepInExBuildIncomplete(String, PathBuf),
+
+	#[error("Mod loader payload `{0}` can't be used: {1}")]
+	LoaderPayloadInvalid(String, String),
+
+	#[error("Mod loader payload `{0}` is already being downloaded. Try again once it's done.")]
+	LoaderPayloadBusy(String),
+
+	#[error(
+		"Mod `{0}` doesn't work with BepInEx build `{1}`. Pick a different BepInEx build for this game."
+	)]
+	BepInExBuildMismatch(String, String),
+
+	#[error(
+		"Version `{1}` of mod loader `{0}` isn't available, so it can't be deployed to the game."
+	)]
+	ModLoaderVersionNotAvailable(String, String),
+
 	#[error(
 		"This operation requires game `{0}` to be installed, but the installed game wasn't found."
 	)]
@@ -0,0 +1,346 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
+};
+
+use directories::BaseDirs;
+use lazy_regex::{regex_captures, regex_is_match};
+use log::error;
+use rai_pal_proc_macros::{serializable_enum, serializable_struct};
+
+use crate::{
+	game_engines::unity,
+	installed_game::InstalledGame,
+	mod_loaders::mod_loader::{self, ModLoaderActions},
+	paths, wine,
+};
+
+// Logs from games with broken mods can have thousands of errors, and the UI doesn't need all of them.
+const MAX_ISSUES: usize = 200;
+
+#[serializable_enum]
+pub enum SessionLog {
+	BepInEx,
+	UnityPlayer,
+}
+
+#[serializable_struct]
+pub struct SessionLogIssue {
+	pub log: SessionLog,
+	// Starts at 1, like in text editors.
+	pub line_number: usize,
+	pub message: String,
+	// Stack trace and anything else that came right after the message.
+	pub details: Vec<String>,
+	// Who logged the message, for logs that say it.
+	pub source: Option<String>,
+	pub mod_id: Option<String>,
+}
+
+#[serializable_struct]
+pub struct SessionDiagnostics {
+	pub bepinex_log_path: Option<PathBuf>,
+	pub player_log_path: Option<PathBuf>,
+	// Unix timestamp, in seconds, of the last time one of the logs was written to.
+	pub last_written_at: Option<u64>,
+	pub issues: Vec<SessionLogIssue>,
+	// Counts every issue, even the ones that didn't fit in the list.
+	pub issue_counts: HashMap<String, usize>,
+	pub unattributed_issue_count: usize,
+}
+
+// Figures out which mod is to blame for a log message,
+// by comparing log sources and stack trace namespaces with the names of each mod's files.
+pub struct ModAttributor {
+	// Mod ID and the normalized names that point to it.
+	candidates: Vec<(String, HashSet<String>)>,
+}
+
+fn normalize_name(name: &str) -> String {
+	name.chars()
+		.filter(char::is_ascii_alphanumeric)
+		.map(|character| character.to_ascii_lowercase())
+		.collect()
+}
+
+impl ModAttributor {
+	// Takes each mod ID with the names of its assemblies.
+	pub fn new(mods: Vec<(String, Vec<String>)>) -> Self {
+		Self {
+			candidates: mods
+				.into_iter()
+				.map(|(mod_id, assembly_names)| {
+					let mut names: HashSet<String> = assembly_names
+						.iter()
+						.flat_map(|assembly_name| {
+							// Assemblies like "UnityExplorer.BIE5.Mono" use the first part as their namespace.
+							[
+								normalize_name(assembly_name),
+								normalize_name(assembly_name.split('.').next().unwrap_or_default()),
+							]
+						})
+						.collect();
+					names.insert(normalize_name(&mod_id));
+					names.remove("");
+
+					(mod_id, names)
+				})
+				.collect(),
+		}
+	}
+
+	fn from_game(game: &InstalledGame, mod_loaders: &mod_loader::Map) -> Self {
+		let mods = mod_loaders
+			.values()
+			.flat_map(|mod_loader| {
+				game.get_available_mods()
+					.into_keys()
+					.filter_map(|mod_id| {
+						let deployed_paths =
+							mod_loader.get_deployed_mod_paths(game, &mod_id).ok()?;
+						if !deployed_paths.iter().any(|path| path.exists()) {
+							return None;
+						}
+
+						let assembly_names = deployed_paths
+							.iter()
+							.flat_map(|deployed_path| {
+								paths::glob_path(&deployed_path.join("**").join("*.dll"))
+							})
+							.filter_map(|assembly_path| {
+								Some(
+									paths::file_name_without_extension(&assembly_path)
+										.ok()?
+										.to_string(),
+								)
+							})
+							.collect();
+
+						Some((mod_id, assembly_names))
+					})
+					.collect::<Vec<_>>()
+			})
+			.collect();
+
+		Self::new(mods)
+	}
+
+	fn get_mod_by_name(&self, name: &str) -> Option<String> {
+		let name = normalize_name(name);
+
+		self.candidates
+			.iter()
+			.find(|(_, names)| names.contains(&name))
+			.map(|(mod_id, _)| mod_id.clone())
+	}
+
+	// Stack frames look like "at Namespace.Type.Method ()" or "Namespace.Type:Method ()".
+	fn get_mod_by_stack_frame(&self, frame: &str) -> Option<String> {
+		let frame = frame.trim();
+		let frame = frame.strip_prefix("at ").unwrap_or(frame);
+		let namespace = frame.split(['.', ':', ' ', '(']).next().unwrap_or_default();
+
+		self.get_mod_by_name(namespace)
+	}
+
+	fn attribute(&self, source: Option<&str>, details: &[String]) -> Option<String> {
+		source
+			.and_then(|source| self.get_mod_by_name(source))
+			.or_else(|| {
+				details
+					.iter()
+					.find_map(|detail| self.get_mod_by_stack_frame(detail))
+			})
+	}
+}
+
+// BepInEx lines look like "[Error  :   Some Plugin] Something went wrong",
+// with anything that doesn't start with a bracket belonging to the line before it.
+pub fn parse_bepinex_log(contents: &str, attributor: &ModAttributor) -> Vec<SessionLogIssue> {
+	let mut issues = Vec::new();
+	let mut current_issue: Option<SessionLogIssue> = None;
+
+	for (index, line) in contents.lines().enumerate() {
+		if let Some((_, level, source, message)) =
+			regex_captures!(r"^\[(\w+)\s*:\s*([^\]]*?)\s*\]\s?(.*)$", line)
+		{
+			issues.extend(current_issue.take());
+
+			if matches!(level, "Error" | "Fatal") {
+				current_issue = Some(SessionLogIssue {
+					log: SessionLog::BepInEx,
+					line_number: index + 1,
+					message: message.to_string(),
+					details: Vec::new(),
+					source: Some(source.to_string()),
+					mod_id: None,
+				});
+			}
+		} else if let Some(issue) = current_issue.as_mut() {
+			if !line.trim().is_empty() {
+				issue.details.push(line.to_string());
+			}
+		}
+	}
+
+	issues.extend(current_issue);
+
+	attribute_issues(issues, attributor)
+}
+
+// Unity doesn't tag its lines, so we look for exceptions and errors,
+// and take everything up to the next empty line as their stack trace.
+pub fn parse_player_log(contents: &str, attributor: &ModAttributor) -> Vec<SessionLogIssue> {
+	let mut issues = Vec::new();
+	let mut current_issue: Option<SessionLogIssue> = None;
+
+	for (index, line) in contents.lines().enumerate() {
+		if line.trim().is_empty() {
+			issues.extend(current_issue.take());
+		} else if let Some(issue) = current_issue.as_mut() {
+			// Unity ends each logged message with the file that logged it, which isn't useful here.
+			if !line.starts_with("(Filename:") {
+				issue.details.push(line.to_string());
+			}
+		} else if regex_is_match!(r"^([\w.]+Exception\b|Error\b|Fatal\b)", line) {
+			current_issue = Some(SessionLogIssue {
+				log: SessionLog::UnityPlayer,
+				line_number: index + 1,
+				message: line.to_string(),
+				details: Vec::new(),
+				source: None,
+				mod_id: None,
+			});
+		}
+	}
+
+	issues.extend(current_issue);
+
+	attribute_issues(issues, attributor)
+}
+
+fn attribute_issues(
+	issues: Vec<SessionLogIssue>,
+	attributor: &ModAttributor,
+) -> Vec<SessionLogIssue> {
+	issues
+		.into_iter()
+		.map(|issue| SessionLogIssue {
+			mod_id: attributor.attribute(issue.source.as_deref(), &issue.details),
+			..issue
+		})
+		.collect()
+}
+
+pub fn get_bepinex_log_path(game: &InstalledGame) -> Option<PathBuf> {
+	Some(
+		game.get_installed_mods_folder()
+			.ok()?
+			.join("BepInEx")
+			.join("LogOutput.log"),
+	)
+	.filter(|log_path| log_path.is_file())
+}
+
+// Unity keeps the company and product names in the first two lines of app.info,
+// and uses them to decide where Player.log goes.
+fn get_unity_app_info(game: &InstalledGame) -> Option<(String, String)> {
+	let app_info = fs::read_to_string(
+		unity::get_unity_data_path(&game.executable.path)
+			.ok()?
+			.join("app.info"),
+	)
+	.ok()?;
+	let mut lines = app_info.lines().map(str::trim);
+
+	Some((lines.next()?.to_string(), lines.next()?.to_string()))
+}
+
+pub fn get_player_log_path(game: &InstalledGame) -> Option<PathBuf> {
+	let (company, product) = get_unity_app_info(game)?;
+
+	let logs_folder = if wine::is_needed(&game.executable.path) {
+		// Windows games running through Proton write to the Windows user folder inside the prefix.
+		wine::get_proton_prefix(game)?
+			.join("drive_c")
+			.join("users")
+			.join("steamuser")
+			.join("AppData")
+			.join("LocalLow")
+	} else if cfg!(target_os = "windows") {
+		BaseDirs::new()?.home_dir().join("AppData").join("LocalLow")
+	} else if cfg!(target_os = "macos") {
+		BaseDirs::new()?.home_dir().join("Library").join("Logs")
+	} else {
+		BaseDirs::new()?.config_dir().join("unity3d")
+	};
+
+	Some(logs_folder.join(company).join(product).join("Player.log"))
+		.filter(|log_path| log_path.is_file())
+}
+
+fn read_log(log_path: &Path) -> Option<String> {
+	match fs::read(log_path) {
+		// Logs can have all sorts of garbage in them, so we don't want to fail on invalid UTF-8.
+		Ok(log_bytes) => Some(String::from_utf8_lossy(&log_bytes).to_string()),
+		Err(err) => {
+			error!("Failed to read log {}: {err}", log_path.display());
+			None
+		}
+	}
+}
+
+fn get_last_written_at(log_path: &Path) -> Option<u64> {
+	Some(
+		fs::metadata(log_path)
+			.ok()?
+			.modified()
+			.ok()?
+			.duration_since(UNIX_EPOCH)
+			.ok()?
+			.as_secs(),
+	)
+}
+
+// Both logs get overwritten every time the game starts, so they only ever have the last session.
+pub fn get_last_session(game: &InstalledGame, mod_loaders: &mod_loader::Map) -> SessionDiagnostics {
+	let attributor = ModAttributor::from_game(game, mod_loaders);
+	let bepinex_log_path = get_bepinex_log_path(game);
+	let player_log_path = get_player_log_path(game);
+
+	let mut issues = Vec::new();
+
+	if let Some(contents) = bepinex_log_path.as_deref().and_then(read_log) {
+		issues.extend(parse_bepinex_log(&contents, &attributor));
+	}
+
+	if let Some(contents) = player_log_path.as_deref().and_then(read_log) {
+		issues.extend(parse_player_log(&contents, &attributor));
+	}
+
+	let mut issue_counts: HashMap<String, usize> = HashMap::new();
+	let mut unattributed_issue_count = 0;
+	for issue in &issues {
+		match &issue.mod_id {
+			Some(mod_id) => *issue_counts.entry(mod_id.clone()).or_default() += 1,
+			None => unattributed_issue_count += 1,
+		}
+	}
+
+	issues.truncate(MAX_ISSUES);
+
+	SessionDiagnostics {
+		last_written_at: [&bepinex_log_path, &player_log_path]
+			.into_iter()
+			.flatten()
+			.filter_map(|log_path| get_last_written_at(log_path))
+			.max(),
+		bepinex_log_path,
+		player_log_path,
+		issues,
+		issue_counts,
+		unattributed_issue_count,
+	}
+}
@@ -1,8 +1,173 @@
 #![allow(clippy::unwrap_used)]
 
-use std::path::Path;
+use std::{
+	collections::HashMap,
+	fs,
+	io::Write,
+	path::{Path, PathBuf},
+	pin::pin,
+	task::{Context, Poll, Waker},
+};
 
-use crate::providers::steam::appinfo::SteamAppInfoReader;
+use flate2::{Compression, write::GzEncoder};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+	archive::{self, ArchiveFormat},
+	game::{Game, GameId},
+	game_engines::{
+		game_engine::{EngineBrand, EngineVersion, EngineVersionNumbers, GameEngine},
+		unity::UnityScriptingBackend,
+	},
+	game_executable::Architecture,
+	game_mod::{CommonModData, EngineVersionRange, ModDependency},
+	installed_game::InstalledGame,
+	local_mod::{self, LocalMod},
+	mod_backups,
+	mod_compatibility::{self, CompatibilityIssue, CompatibilityTarget, CompatibilityVerdict},
+	mod_loaders::bepinex_config::{self, BepInExConfigDocument},
+	mod_loaders::mod_loader::{self, ModLoader, ModLoaderActions, ModLoaderStatic},
+	mod_loaders::thunderstore::{ThunderstoreHttp, ThunderstoreSource},
+	mod_loaders::{
+		bepinex::BepInEx,
+		bepinex_builds::{self, BepInExBuild, BepInExGameBuild, PayloadChoice},
+		descriptor_loader::{DescriptorLoader, DescriptorRoot},
+		loader_payloads::{self, LoaderIndex, LoaderPayload},
+		mod_database::{ModDownload, ModGithubInfo, RunnableModData},
+		mod_import::{self, ModImportLayout, ModImportOptions},
+		mod_integrity::{self, FileIssueKind},
+		mod_verification::ModSignature,
+		runnable_loader,
+	},
+	mod_manifest::Manifest,
+	mod_pins,
+	mod_presets::{self, ModPreset},
+	mod_profiles, mod_version_requirements, mod_versions, paths,
+	providers::{
+		provider::ProviderId, provider_command::ProviderCommand, steam::appinfo::SteamAppInfoReader,
+	},
+	remote_mod,
+	result::{Error, Result},
+	session_diagnostics::{self, ModAttributor, SessionLog},
+	wine,
+};
+
+// Futures in these tests never actually wait on anything, so polling once is enough.
+fn poll_once<TFuture: Future>(future: TFuture) -> TFuture::Output {
+	let mut future = pin!(future);
+	let Poll::Ready(output) = future
+		.as_mut()
+		.poll(&mut Context::from_waker(Waker::noop()))
+	else {
+		panic!("Future wasn't ready");
+	};
+
+	output
+}
+
+// Makes an empty folder inside the test data folder, replacing whatever was there before.
+fn create_test_folder(name: &str) -> PathBuf {
+	let path = paths::app_data_path()
+		.unwrap()
+		.join("test-folders")
+		.join(name);
+	if path.exists() {
+		fs::remove_dir_all(&path).unwrap();
+	}
+	fs::create_dir_all(&path).unwrap();
+
+	path
+}
+
+fn create_test_game(name: &str) -> InstalledGame {
+	let executable_path = create_test_folder(name).join("Game.exe");
+	fs::write(&executable_path, "").unwrap();
+
+	let mut game = InstalledGame::new(&executable_path).unwrap();
+	let installed_mods_folder = game.get_installed_mods_folder().unwrap();
+	fs::remove_dir_all(&installed_mods_folder).unwrap();
+	fs::create_dir_all(&installed_mods_folder).unwrap();
+	game.executable.scripting_backend = Some(UnityScriptingBackend::Mono);
+
+	game
+}
+
+fn write_manifest(path: &Path, manifest: &Manifest) {
+	fs::create_dir_all(paths::path_parent(path).unwrap()).unwrap();
+	fs::write(path, serde_json::to_string(manifest).unwrap()).unwrap();
+}
+
+fn create_test_manifest(version: &str) -> Manifest {
+	Manifest {
+		title: None,
+		version: version.to_string(),
+		runnable: None,
+		engine: None,
+		engine_version_range: None,
+		unity_backend: None,
+		architecture: None,
+		dependencies: None,
+		incompatibilities: None,
+		bepinex_builds: None,
+		config_file: None,
+		files: None,
+	}
+}
+
+// Puts a BepInEx mod in the game as if it had been installed, and returns its local counterpart.
+fn deploy_test_mod(game: &InstalledGame, mods_folder: &Path, mod_id: &str) -> LocalMod {
+	let mod_path = mods_folder.join(mod_id);
+	write_manifest(
+		&local_mod::get_manifest_path(&mod_path),
+		&create_test_manifest("1.0.0"),
+	);
+
+	let plugin_path = game
+		.get_installed_mods_folder()
+		.unwrap()
+		.join("BepInEx")
+		.join("plugins")
+		.join(mod_id);
+	fs::create_dir_all(&plugin_path).unwrap();
+	fs::write(plugin_path.join(format!("{mod_id}.dll")), mod_id).unwrap();
+	write_manifest(
+		&game.get_installed_mod_manifest_path(mod_id).unwrap(),
+		&create_test_manifest("1.0.0"),
+	);
+
+	LocalMod::new(
+		BepInEx::ID,
+		&mod_path,
+		None,
+		Some(UnityScriptingBackend::Mono),
+	)
+	.unwrap()
+}
+
+// Files for one BepInEx build, laid out like the bundled resources.
+fn write_test_bepinex_build(scripting_backend_path: &Path, core_file_name: &str) {
+	let architecture_path = scripting_backend_path.join("Windows").join("X64");
+	let copy_to_game_path = architecture_path.join("copy-to-game");
+	fs::create_dir_all(&copy_to_game_path).unwrap();
+	fs::write(copy_to_game_path.join("winhttp.dll"), "proxy").unwrap();
+	write_test_archive(
+		&architecture_path,
+		"mod-loader.zip",
+		&create_test_zip(&[&format!("BepInEx/core/{core_file_name}")]),
+	);
+	fs::write(
+		scripting_backend_path.join("doorstop_config.ini"),
+		"target={{MOD_FILES_PATH}}",
+	)
+	.unwrap();
+}
+
+fn create_bepinex_map(resources_path: &Path) -> mod_loader::Map {
+	mod_loader::Map::from([(
+		BepInEx::ID.to_string(),
+		ModLoader::BepInEx(BepInEx::new(resources_path).unwrap()),
+	)])
+}
 
 #[test]
 fn benchmark_thing() {
@@ -16,3 +181,1800 @@ fn benchmark_thing() {
 		}
 	}
 }
+
+#[test]
+fn mod_version_requirements() {
+	assert!(mod_version_requirements::matches_requirement(
+		"v1.2.3", None
+	));
+	assert!(mod_version_requirements::matches_requirement(
+		"v1.2.3",
+		Some(">=1.2")
+	));
+	assert!(mod_version_requirements::matches_requirement(
+		"1.2.3/2",
+		Some("1.2.3")
+	));
+	assert!(mod_version_requirements::matches_requirement(
+		"1.4.0",
+		Some(">=1.2, <2")
+	));
+	assert!(!mod_version_requirements::matches_requirement(
+		"2.0.0",
+		Some(">=1.2, <2")
+	));
+	assert!(mod_version_requirements::matches_requirement(
+		"1.9",
+		Some("^1.2")
+	));
+	assert!(!mod_version_requirements::matches_requirement(
+		"1.1",
+		Some("^1.2")
+	));
+	assert!(!mod_version_requirements::matches_requirement(
+		"1.3.0",
+		Some("~1.2")
+	));
+}
+
+// Mod in the BepInEx mods folder, not deployed to any game.
+fn create_test_local_mod(
+	mod_id: &str,
+	dependencies: &[&str],
+	incompatibilities: &[&str],
+) -> LocalMod {
+	let to_mod_dependencies = |dependency_ids: &[&str]| {
+		dependency_ids
+			.iter()
+			.map(|dependency_id| ModDependency {
+				id: (*dependency_id).to_string(),
+				version: None,
+			})
+			.collect()
+	};
+	let mod_path = BepInEx::get_installed_mods_path()
+		.unwrap()
+		.join("Mono")
+		.join(mod_id);
+	write_manifest(
+		&local_mod::get_manifest_path(&mod_path),
+		&Manifest {
+			dependencies: Some(to_mod_dependencies(dependencies)),
+			incompatibilities: Some(to_mod_dependencies(incompatibilities)),
+			..create_test_manifest("1.0.0")
+		},
+	);
+
+	LocalMod::new(
+		BepInEx::ID,
+		&mod_path,
+		None,
+		Some(UnityScriptingBackend::Mono),
+	)
+	.unwrap()
+}
+
+#[test]
+fn mod_dependency_resolution() {
+	let game = create_test_game("mod-dependencies");
+	let mod_loaders = create_bepinex_map(&create_test_folder("mod-dependencies-resources"));
+	let resolve = |local_mod: &LocalMod| {
+		poll_once(mod_loaders[BepInEx::ID].resolve_dependencies(
+			&game,
+			local_mod,
+			&remote_mod::Map::new(),
+			&|_| {},
+		))
+	};
+	let get_ids = |local_mods: Vec<LocalMod>| {
+		local_mods
+			.into_iter()
+			.map(|local_mod| local_mod.common.id)
+			.collect::<Vec<_>>()
+	};
+
+	// Dependencies of dependencies get installed too, before the mods that need them.
+	create_test_local_mod("dependency-core", &[], &[]);
+	create_test_local_mod("dependency-lib", &["dependency-core"], &[]);
+	let app = create_test_local_mod("dependency-app", &["dependency-lib"], &[]);
+	assert_eq!(
+		get_ids(resolve(&app).unwrap()),
+		vec!["dependency-core", "dependency-lib", "dependency-app"]
+	);
+
+	// Dependencies the game already has don't need to be installed again.
+	deploy_test_mod(
+		&game,
+		&BepInEx::get_installed_mods_path().unwrap().join("Mono"),
+		"dependency-installed",
+	);
+	let needs_installed =
+		create_test_local_mod("dependency-needs-installed", &["dependency-installed"], &[]);
+	assert_eq!(
+		get_ids(resolve(&needs_installed).unwrap()),
+		vec!["dependency-needs-installed"]
+	);
+
+	let needs_missing =
+		create_test_local_mod("dependency-needs-missing", &["dependency-nowhere"], &[]);
+	assert!(matches!(
+		resolve(&needs_missing),
+		Err(Error::ModDependencyNotFound(..))
+	));
+
+	// Conflicts with mods in the game, and with mods that would be installed alongside it.
+	let conflicting =
+		create_test_local_mod("dependency-conflicting", &[], &["dependency-installed"]);
+	assert!(matches!(
+		resolve(&conflicting),
+		Err(Error::ModConflict(mod_id, other_mod_id)) if mod_id == "dependency-conflicting" && other_mod_id == "dependency-installed"
+	));
+	create_test_local_mod("dependency-rival", &[], &["dependency-core"]);
+	let pulls_rival = create_test_local_mod(
+		"dependency-pulls-rival",
+		&["dependency-rival", "dependency-lib"],
+		&[],
+	);
+	assert!(matches!(resolve(&pulls_rival), Err(Error::ModConflict(..))));
+
+	create_test_local_mod("dependency-cycle-b", &["dependency-cycle-a"], &[]);
+	let cycle_a = create_test_local_mod("dependency-cycle-a", &["dependency-cycle-b"], &[]);
+	assert!(matches!(
+		resolve(&cycle_a),
+		Err(Error::ModDependencyCycle(cycle)) if cycle == "dependency-cycle-a -> dependency-cycle-b -> dependency-cycle-a"
+	));
+}
+
+struct ThunderstoreStandIn;
+
+impl ThunderstoreHttp for ThunderstoreStandIn {
+	async fn get_text(&self, url: &str) -> Result<String> {
+		assert_eq!(url, "http://localhost:1234/c/some-game/api/v1/package/");
+
+		Ok(r#"[
+			{
+				"full_name": "BepInEx-BepInExPack",
+				"owner": "BepInEx",
+				"is_deprecated": false,
+				"versions": [{
+					"name": "BepInExPack",
+					"description": "BepInEx pack",
+					"version_number": "5.4.2100",
+					"dependencies": [],
+					"download_url": "http://localhost:1234/BepInExPack.zip",
+					"website_url": ""
+				}]
+			},
+			{
+				"full_name": "Someone-Cool_Mod",
+				"owner": "Someone",
+				"is_deprecated": false,
+				"versions": [
+					{
+						"name": "Cool_Mod",
+						"description": "Does cool things",
+						"version_number": "1.1.0",
+						"dependencies": ["BepInEx-BepInExPack_IL2CPP-6.0.667", "Someone-Library-2.0.1"],
+						"download_url": "http://localhost:1234/1.1.0.zip",
+						"website_url": "https://example.com"
+					},
+					{
+						"name": "Cool_Mod",
+						"description": "Does cool things",
+						"version_number": "1.0.0",
+						"dependencies": [],
+						"download_url": "http://localhost:1234/1.0.0.zip",
+						"website_url": "https://example.com"
+					}
+				]
+			}
+		]"#
+		.to_string())
+	}
+}
+
+#[test]
+fn thunderstore_packages_to_database() {
+	let source = ThunderstoreSource {
+		base_url: "http://localhost:1234/".to_string(),
+		community: "some-game".to_string(),
+	};
+
+	let database = poll_once(source.get_database(&ThunderstoreStandIn)).unwrap();
+
+	assert_eq!(database.mods.len(), 1);
+
+	let entry = &database.mods[0];
+	assert_eq!(entry.id, "Someone-Cool_Mod");
+	assert_eq!(entry.title, "Cool Mod");
+	assert_eq!(entry.unity_backend, Some(UnityScriptingBackend::Il2Cpp));
+	assert_eq!(entry.latest_version.as_ref().unwrap().id, "1.1.0");
+	assert_eq!(entry.versions.as_ref().unwrap()[0].id, "1.0.0");
+
+	let dependencies = entry.dependencies.as_ref().unwrap();
+	assert_eq!(dependencies.len(), 1);
+	assert_eq!(dependencies[0].id, "Someone-Library");
+	assert!(dependencies[0].is_satisfied_by(Some("2.1.0")));
+	assert!(!dependencies[0].is_satisfied_by(Some("2.0.0")));
+}
+
+#[test]
+fn bepinex_config_round_trip() {
+	let contents = "## Settings file was created by plugin Cool Mod v1.0.0\r
+## Plugin GUID: someone.coolmod\r
+\r
+[General]\r
+\r
+## Whether the mod does anything.\r
+## Turn it off if it breaks.\r
+# Setting type: Boolean\r
+# Default value: true\r
+Enabled = true\r
+\r
+[Camera.Settings]\r
+\r
+# Setting type: Single\r
+# Default value: 90\r
+# Acceptable value range: From 30 to 120\r
+Field Of View = 75\r
+\r
+# Setting type: CameraMode\r
+# Default value: Follow\r
+# Acceptable values: Follow, Fixed, Free\r
+Mode = Fixed\r
+";
+
+	let mut document = BepInExConfigDocument::parse(contents);
+	assert_eq!(document.write(), contents);
+
+	let config = document.get_config("someone.coolmod.cfg");
+	assert_eq!(config.plugin_name.as_deref(), Some("Cool Mod v1.0.0"));
+	assert_eq!(config.plugin_guid.as_deref(), Some("someone.coolmod"));
+	assert_eq!(config.entries.len(), 3);
+
+	let enabled = &config.entries[0];
+	assert_eq!(enabled.section, "General");
+	assert_eq!(
+		enabled.description.as_deref(),
+		Some("Whether the mod does anything.\nTurn it off if it breaks.")
+	);
+	assert_eq!(enabled.setting_type.as_deref(), Some("Boolean"));
+	assert_eq!(enabled.default_value.as_deref(), Some("true"));
+
+	let field_of_view = &config.entries[1];
+	assert_eq!(field_of_view.section, "Camera.Settings");
+	assert_eq!(field_of_view.key, "Field Of View");
+	assert_eq!(field_of_view.value, "75");
+	assert_eq!(field_of_view.description, None);
+	let range = field_of_view.acceptable_range.as_ref().unwrap();
+	assert_eq!((range.from.as_str(), range.to.as_str()), ("30", "120"));
+
+	assert_eq!(
+		config.entries[2].acceptable_values.as_deref(),
+		Some(["Follow", "Fixed", "Free"].map(String::from).as_slice())
+	);
+
+	assert!(document.set_value("Camera.Settings", "Field Of View", "100"));
+	assert!(!document.set_value("General", "Field Of View", "100"));
+	assert_eq!(
+		document.write(),
+		contents.replace("Field Of View = 75", "Field Of View = 100")
+	);
+
+	document.set_or_add_value("General", "Verbose", "false");
+	document.set_or_add_value("Extra", "Speed", "2");
+	assert_eq!(
+		document.write(),
+		contents
+			.replace("Field Of View = 75", "Field Of View = 100")
+			.replace(
+				"Enabled = true\r\n",
+				"Enabled = true\r\nVerbose = false\r\n"
+			) + "\r\n[Extra]\r\nSpeed = 2\r\n"
+	);
+	assert_eq!(document.get_config("").entries.len(), 5);
+}
+
+#[test]
+fn session_logs_attribute_issues_to_mods() {
+	let attributor = ModAttributor::new(vec![
+		(
+			"unity-explorer".to_string(),
+			vec!["UnityExplorer.BIE5.Mono".to_string()],
+		),
+		("uuvr".to_string(), vec!["Uuvr".to_string()]),
+	]);
+
+	let bepinex_log = "[Info   :   BepInEx] Loading [UnityExplorer 4.9.0]\n\
+		[Error  :    Uuvr] Failed to patch camera\n\
+		System.NullReferenceException: Object reference not set\n\
+		[Info   :   BepInEx] Chainloader startup complete\n\
+		[Fatal  : Unity Log] Something exploded\n\
+		\x20 at UnityExplorer.Loader.Init () [0x00000]\n";
+
+	let bepinex_issues = session_diagnostics::parse_bepinex_log(bepinex_log, &attributor);
+	assert_eq!(bepinex_issues.len(), 2);
+	assert_eq!(bepinex_issues[0].line_number, 2);
+	assert_eq!(bepinex_issues[0].source.as_deref(), Some("Uuvr"));
+	assert_eq!(bepinex_issues[0].message, "Failed to patch camera");
+	assert_eq!(bepinex_issues[0].details.len(), 1);
+	assert_eq!(bepinex_issues[0].mod_id.as_deref(), Some("uuvr"));
+	assert_eq!(bepinex_issues[1].mod_id.as_deref(), Some("unity-explorer"));
+
+	let player_log = "Initialize engine version: 2019.4.40f1\n\
+		\n\
+		NullReferenceException: Object reference not set to an instance of an object\n\
+		\x20 at Uuvr.VrCamera.Update () [0x00010] in <abc>:0\n\
+		(Filename: <abc> Line: 0)\n\
+		\n\
+		ArgumentException: Bad argument\n\
+		\x20 at GameAssembly.Player.Move () [0x00000] in <abc>:0\n";
+
+	let player_issues = session_diagnostics::parse_player_log(player_log, &attributor);
+	assert_eq!(player_issues.len(), 2);
+	assert!(matches!(player_issues[0].log, SessionLog::UnityPlayer));
+	assert_eq!(player_issues[0].line_number, 3);
+	assert_eq!(player_issues[0].details.len(), 1);
+	assert_eq!(player_issues[0].mod_id.as_deref(), Some("uuvr"));
+	assert_eq!(player_issues[1].mod_id, None);
+}
+
+#[test]
+fn mod_profiles_park_and_switch() {
+	let game = create_test_game("mod-profiles");
+	let mods_folder = create_test_folder("mod-profiles-mods");
+	let mod_loaders = create_bepinex_map(&mods_folder);
+	let bepinex = &mod_loaders[BepInEx::ID];
+
+	let local_mods: local_mod::Map = ["first", "second"]
+		.into_iter()
+		.map(|mod_id| {
+			(
+				mod_id.to_string(),
+				deploy_test_mod(&game, &mods_folder, mod_id),
+			)
+		})
+		.collect();
+	let plugins_folder = game
+		.get_installed_mods_folder()
+		.unwrap()
+		.join("BepInEx")
+		.join("plugins");
+
+	mod_profiles::save_current(&game, "everything").unwrap();
+
+	bepinex
+		.set_mod_enabled(&game, &local_mods["second"], false)
+		.unwrap();
+	assert!(!plugins_folder.join("second").exists());
+	assert!(game.get_disabled_mods().contains("second"));
+	mod_profiles::save_current(&game, "only-first").unwrap();
+
+	mod_profiles::activate(&game, "everything", &mod_loaders, &local_mods).unwrap();
+	assert!(plugins_folder.join("second").join("second.dll").is_file());
+	assert!(game.get_disabled_mods().is_empty());
+
+	mod_profiles::activate(&game, "only-first", &mod_loaders, &local_mods).unwrap();
+	assert!(!plugins_folder.join("second").exists());
+	assert!(plugins_folder.join("first").is_dir());
+	assert_eq!(
+		mod_profiles::read(&game).active.as_deref(),
+		Some("only-first")
+	);
+
+	// Toggling a mod updates the active profile too, so switching back and forth keeps the change.
+	mod_profiles::set_mod_enabled(&game, &mod_loaders, &local_mods["second"], true).unwrap();
+	assert!(
+		mod_profiles::read(&game).profiles["only-first"]
+			.enabled_mods
+			.contains("second")
+	);
+	mod_profiles::activate(&game, "everything", &mod_loaders, &local_mods).unwrap();
+	mod_profiles::activate(&game, "only-first", &mod_loaders, &local_mods).unwrap();
+	assert!(plugins_folder.join("second").join("second.dll").is_file());
+
+	mod_profiles::forget_mod(&game, "second").unwrap();
+	let profiles = mod_profiles::read(&game);
+	assert!(
+		profiles
+			.profiles
+			.values()
+			.all(|profile| !profile.enabled_mods.contains("second"))
+	);
+	assert!(
+		profiles.profiles["everything"]
+			.enabled_mods
+			.contains("first")
+	);
+}
+
+const fn engine_version_numbers(major: u32, minor: Option<u32>) -> EngineVersionNumbers {
+	EngineVersionNumbers {
+		major,
+		minor,
+		patch: None,
+	}
+}
+
+#[test]
+fn mod_compatibility_evaluation() {
+	let engine = GameEngine {
+		brand: EngineBrand::Unity,
+		version: Some(EngineVersion {
+			numbers: EngineVersionNumbers {
+				major: 2019,
+				minor: Some(4),
+				patch: Some(2),
+			},
+			suffix: None,
+			display: "2019.4.2".to_string(),
+		}),
+	};
+	let target = CompatibilityTarget {
+		engine: Some(&engine),
+		unity_backend: Some(UnityScriptingBackend::Il2Cpp),
+		architecture: Some(Architecture::X64),
+	};
+	let mod_data = CommonModData {
+		id: "mod".to_string(),
+		engine: Some(EngineBrand::Unity),
+		unity_backend: Some(UnityScriptingBackend::Il2Cpp),
+		engine_version_range: Some(EngineVersionRange {
+			// Bounds without a minor version only compare the major version.
+			minimum: Some(engine_version_numbers(2019, None)),
+			maximum: Some(engine_version_numbers(2020, None)),
+		}),
+		architecture: Some(Architecture::X64),
+		loader_id: BepInEx::ID.to_string(),
+	};
+
+	let compatible = mod_compatibility::evaluate_target(&mod_data, &target);
+	assert_eq!(compatible.verdict, CompatibilityVerdict::Compatible);
+	assert!(compatible.issues.is_empty());
+
+	let mismatched = mod_compatibility::evaluate_target(
+		&CommonModData {
+			engine_version_range: Some(EngineVersionRange {
+				minimum: Some(engine_version_numbers(2019, Some(5))),
+				maximum: None,
+			}),
+			unity_backend: Some(UnityScriptingBackend::Mono),
+			architecture: Some(Architecture::X86),
+			..mod_data.clone()
+		},
+		&target,
+	);
+	assert_eq!(mismatched.verdict, CompatibilityVerdict::Incompatible);
+	assert_eq!(
+		mismatched.issues,
+		vec![
+			CompatibilityIssue::EngineVersionTooOld,
+			CompatibilityIssue::UnityBackendMismatch,
+			CompatibilityIssue::ArchitectureMismatch,
+		]
+	);
+
+	let wrong_engine = mod_compatibility::evaluate_target(
+		&CommonModData {
+			engine: Some(EngineBrand::Unreal),
+			..mod_data.clone()
+		},
+		&target,
+	);
+	assert_eq!(
+		wrong_engine.issues,
+		vec![CompatibilityIssue::EngineMismatch]
+	);
+
+	// Missing game information makes the verdict unknown, but never blocks the mod.
+	let unknown = mod_compatibility::evaluate_target(
+		&mod_data,
+		&CompatibilityTarget {
+			engine: None,
+			unity_backend: None,
+			architecture: None,
+		},
+	);
+	assert_eq!(unknown.verdict, CompatibilityVerdict::Unknown);
+	assert_eq!(
+		unknown.issues,
+		vec![
+			CompatibilityIssue::EngineUnknown,
+			CompatibilityIssue::UnityBackendUnknown,
+			CompatibilityIssue::ArchitectureUnknown,
+		]
+	);
+}
+
+// Tar entries with the given names, written straight into the header,
+// since the tar crate refuses to build archives with unsafe paths.
+fn create_test_tar(entries: &[(&str, tar::EntryType, &[u8])]) -> Vec<u8> {
+	let mut builder = tar::Builder::new(Vec::new());
+
+	for (name, entry_type, contents) in entries {
+		let mut header = tar::Header::new_gnu();
+		header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+		header.set_entry_type(*entry_type);
+		header.set_size(u64::try_from(contents.len()).unwrap());
+		header.set_mode(0o644);
+		if entry_type.is_symlink() {
+			header.set_link_name("/etc/passwd").unwrap();
+		}
+		header.set_cksum();
+		builder.append(&header, *contents).unwrap();
+	}
+
+	builder.into_inner().unwrap()
+}
+
+fn create_test_zip(file_names: &[&str]) -> Vec<u8> {
+	let mut writer = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+
+	for file_name in file_names {
+		writer
+			.start_file(*file_name, SimpleFileOptions::default())
+			.unwrap();
+		writer.write_all(file_name.as_bytes()).unwrap();
+	}
+
+	writer.finish().unwrap().into_inner()
+}
+
+fn write_test_archive(folder: &Path, file_name: &str, contents: &[u8]) -> PathBuf {
+	let archive_path = folder.join(file_name);
+	fs::write(&archive_path, contents).unwrap();
+
+	archive_path
+}
+
+#[test]
+fn archive_extraction_rejects_unsafe_entries() {
+	let folder = create_test_folder("archive-unsafe-entries");
+	let target_path = folder.join("extracted");
+
+	let mut zip_symlink_writer = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+	zip_symlink_writer
+		.add_symlink("link", "/etc/passwd", SimpleFileOptions::default())
+		.unwrap();
+
+	let unsafe_archives = [
+		(
+			"zip-slip.zip",
+			create_test_zip(&["fine.txt", "../evil.txt"]),
+		),
+		(
+			"zip-symlink.zip",
+			zip_symlink_writer.finish().unwrap().into_inner(),
+		),
+		(
+			"tar-slip.tar",
+			create_test_tar(&[
+				("fine.txt", tar::EntryType::Regular, b"fine"),
+				("nested/../../evil.txt", tar::EntryType::Regular, b"evil"),
+			]),
+		),
+		(
+			"tar-absolute.tar",
+			create_test_tar(&[("/tmp/evil.txt", tar::EntryType::Regular, b"evil")]),
+		),
+		(
+			"tar-symlink.tar",
+			create_test_tar(&[("link", tar::EntryType::Symlink, b"")]),
+		),
+	];
+
+	for (file_name, contents) in unsafe_archives {
+		let archive_path = write_test_archive(&folder, file_name, &contents);
+
+		assert!(
+			matches!(
+				archive::extract(&archive_path, &target_path),
+				Err(Error::ArchiveEntryUnsafe(..))
+			),
+			"{file_name} should have been rejected"
+		);
+		// Nothing gets written if any of the entries is unsafe, not even the safe ones.
+		assert!(!target_path.exists(), "{file_name} left files behind");
+	}
+	assert!(!folder.parent().unwrap().join("evil.txt").exists());
+
+	let safe_archive_path = write_test_archive(
+		&folder,
+		"safe.tar",
+		&create_test_tar(&[
+			("plugins/", tar::EntryType::Directory, b""),
+			("plugins/mod.dll", tar::EntryType::Regular, b"mod"),
+		]),
+	);
+	archive::extract(&safe_archive_path, &target_path).unwrap();
+	assert_eq!(
+		fs::read_to_string(target_path.join("plugins").join("mod.dll")).unwrap(),
+		"mod"
+	);
+}
+
+#[test]
+fn archive_extraction_limits() {
+	let folder = create_test_folder("archive-limits");
+	let target_path = folder.join("extracted");
+
+	let file_names: Vec<String> = (0..=archive::MAX_FILE_COUNT)
+		.map(|index| format!("{index}.txt"))
+		.collect();
+	let many_files_path = write_test_archive(
+		&folder,
+		"many-files.zip",
+		&create_test_zip(&file_names.iter().map(String::as_str).collect::<Vec<_>>()),
+	);
+	assert!(matches!(
+		archive::extract(&many_files_path, &target_path),
+		Err(Error::ArchiveTooLarge(..))
+	));
+
+	// Compresses down to almost nothing, but goes over the limit once extracted.
+	let zeros = vec![0; usize::try_from(archive::MAX_TOTAL_SIZE).unwrap() + 1];
+	let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+	encoder
+		.write_all(&create_test_tar(&[(
+			"zeros.bin",
+			tar::EntryType::Regular,
+			&zeros,
+		)]))
+		.unwrap();
+	let bomb_path = write_test_archive(&folder, "bomb.tar.gz", &encoder.finish().unwrap());
+	assert!(matches!(
+		archive::extract(&bomb_path, &target_path),
+		Err(Error::ArchiveTooLarge(..))
+	));
+
+	assert!(!target_path.exists());
+}
+
+fn gzip(contents: &[u8]) -> Vec<u8> {
+	let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+	encoder.write_all(contents).unwrap();
+
+	encoder.finish().unwrap()
+}
+
+#[test]
+fn archive_format_detection() {
+	let folder = create_test_folder("archive-formats");
+	let tar = create_test_tar(&[("mod.dll", tar::EntryType::Regular, b"mod")]);
+
+	let supported_files = [
+		("mod.zip", create_test_zip(&["mod.dll"]), ArchiveFormat::Zip),
+		("mod.tar", tar.clone(), ArchiveFormat::Tar),
+		("mod.tar.gz", gzip(&tar), ArchiveFormat::TarGz),
+		// Executables are recognized by content, even without an extension.
+		(
+			"download",
+			b"MZ\x90\x00".to_vec(),
+			ArchiveFormat::SingleFile,
+		),
+		(
+			"Mod.DLL",
+			b"not really a dll".to_vec(),
+			ArchiveFormat::SingleFile,
+		),
+	];
+
+	for (file_name, contents, expected_format) in supported_files {
+		let archive_path = write_test_archive(&folder, file_name, &contents);
+		assert_eq!(
+			archive::detect_format(&archive_path).unwrap(),
+			expected_format,
+			"{file_name}"
+		);
+	}
+
+	let unsupported_files = [
+		("mod.rar", b"Rar!\x1A\x07\x01\x00".to_vec()),
+		("readme.gz", gzip(b"just some text")),
+		("readme.txt", b"just some text".to_vec()),
+	];
+
+	for (file_name, contents) in unsupported_files {
+		let archive_path = write_test_archive(&folder, file_name, &contents);
+		assert!(
+			matches!(
+				archive::detect_format(&archive_path),
+				Err(Error::ArchiveFormatUnsupported(..))
+			),
+			"{file_name} should have been rejected"
+		);
+	}
+}
+
+#[test]
+fn mod_import_layout_and_runnable_path() {
+	let source_path = create_test_folder("import-source").join("some-mod");
+	fs::create_dir_all(&source_path).unwrap();
+	fs::write(source_path.join("SomeMod.dll"), "").unwrap();
+	fs::write(source_path.join("Configurator.exe"), "").unwrap();
+	let staging_path = paths::installed_mods_path()
+		.unwrap()
+		.join("imports")
+		.join("some-mod");
+
+	// Mods with dlls are plugins, even if they come with an executable.
+	let preview = mod_import::preview(&source_path).unwrap();
+	assert_eq!(preview.layout, ModImportLayout::LoosePlugins);
+	assert_eq!(preview.executables, vec!["Configurator.exe".to_string()]);
+	assert!(!staging_path.exists());
+
+	let mod_loaders = create_bepinex_map(&create_test_folder("import-mods"));
+	for runnable_path in ["../Configurator.exe", "/bin/sh"] {
+		let result = mod_import::import(
+			&ModImportOptions {
+				source_path: source_path.clone(),
+				id: None,
+				title: None,
+				version: None,
+				layout: None,
+				unity_backend: None,
+				runnable: Some(RunnableModData {
+					path: runnable_path.to_string(),
+					args: Vec::new(),
+				}),
+			},
+			&mod_loaders,
+		);
+
+		assert!(
+			matches!(result, Err(Error::ModImportRunnablePathInvalid(..))),
+			"{runnable_path} should have been rejected"
+		);
+		assert!(!staging_path.exists());
+	}
+}
+
+#[test]
+fn pinned_mod_versions() {
+	let game = create_test_game("pinned-mod-versions");
+	let mods_folder = create_test_folder("pinned-mod-versions-mods");
+	let local_mod = deploy_test_mod(&game, &mods_folder, "pinned");
+
+	let old_version_path = mod_versions::get_version_path(BepInEx::ID, "pinned", "0.9.0").unwrap();
+	write_manifest(
+		&local_mod::get_manifest_path(&old_version_path),
+		&Manifest {
+			architecture: Some(Architecture::X86),
+			..create_test_manifest("0.9.0")
+		},
+	);
+
+	// The latest version lives in the regular mods folder, and still counts as downloaded.
+	assert_eq!(
+		mod_versions::get_downloaded_versions(BepInEx::ID, "pinned", Some(&local_mod)),
+		vec!["1.0.0".to_string(), "0.9.0".to_string()]
+	);
+	assert!(mod_pins::ensure_unpinned(&game, "pinned").is_ok());
+
+	let mut pins = mod_pins::read(&game);
+	pins.pin("pinned", "0.9.0");
+	mod_pins::write(&game, &pins).unwrap();
+
+	let pinned_mod = mod_versions::get_pinned_local_mod(&game, &local_mod).unwrap();
+	assert_eq!(pinned_mod.get_version(), Some("0.9.0"));
+	assert_eq!(pinned_mod.data.path, old_version_path);
+	// Requirements come from the pinned version, not the latest one.
+	assert_eq!(pinned_mod.common.architecture, Some(Architecture::X86));
+	assert_eq!(local_mod.common.architecture, None);
+
+	assert!(matches!(
+		mod_pins::ensure_unpinned(&game, "pinned"),
+		Err(Error::ModPinned(..))
+	));
+}
+
+#[test]
+fn delete_mod_removes_versions_and_pins() {
+	let mod_loaders = create_bepinex_map(&create_test_folder("delete-mod-resources"));
+	let local_mod = create_test_local_mod("delete-me", &[], &[]);
+	let old_version_path =
+		mod_versions::get_version_path(BepInEx::ID, "delete-me", "0.9.0").unwrap();
+	write_manifest(
+		&local_mod::get_manifest_path(&old_version_path),
+		&create_test_manifest("0.9.0"),
+	);
+
+	let installed_game = create_test_game("delete-mod");
+	let mut pins = mod_pins::read(&installed_game);
+	pins.pin("delete-me", "0.9.0");
+	mod_pins::write(&installed_game, &pins).unwrap();
+	let mut game = Game::new(
+		GameId {
+			provider_id: ProviderId::Manual,
+			game_id: "delete-mod".to_string(),
+		},
+		"Delete Mod",
+	);
+	game.installed_game = Some(installed_game.clone());
+
+	mod_loaders[BepInEx::ID]
+		.delete_mod(&local_mod, &[game])
+		.unwrap();
+
+	assert!(!local_mod.data.path.exists());
+	assert!(!old_version_path.exists());
+	assert!(mod_pins::read(&installed_game).get("delete-me").is_none());
+}
+
+#[test]
+fn descriptor_loader_sample() {
+	let loader_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-data/sample-loader");
+	let loader = DescriptorLoader::new(&loader_path).unwrap();
+	assert_eq!(loader.data.id, "sample-loader");
+	assert_eq!(loader.descriptor.engine, Some(EngineBrand::Unity));
+	assert_eq!(loader.descriptor.files.len(), 2);
+	assert_eq!(
+		loader.descriptor.mods_folder.root,
+		DescriptorRoot::ModFilesFolder
+	);
+	assert!(!loader.has_official_database());
+
+	let mut game = create_test_game("descriptor-loader");
+	// The sample only has the 64-bit loader, so it gets left out for this game.
+	game.executable.architecture = Some(Architecture::X86);
+	let game_folder = paths::path_parent(&game.executable.path).unwrap();
+	let mods_folder = game.get_installed_mods_folder().unwrap();
+
+	let preview = loader.get_install_preview(&game).unwrap();
+	assert_eq!(
+		preview
+			.iter()
+			.map(|entry| entry.path.clone())
+			.collect::<Vec<_>>(),
+		vec![
+			game_folder.join("winhttp.dll"),
+			game_folder.join("sample_loader.ini"),
+		]
+	);
+
+	loader.install(&game).unwrap();
+	assert_eq!(
+		fs::read_to_string(game_folder.join("winhttp.dll")).unwrap(),
+		"proxy"
+	);
+	assert!(!mods_folder.join("sample_loader.dll").exists());
+
+	let config = fs::read_to_string(game_folder.join("sample_loader.ini")).unwrap();
+	assert!(!config.contains("{{"));
+	assert!(config.contains(&format!(
+		"ModsPath={}/SampleLoader/mods",
+		mods_folder.display()
+	)));
+	assert!(config.contains(&format!("GamePath={}", game_folder.display())));
+}
+
+#[test]
+fn runnable_argument_expansion() {
+	let mut game = create_test_game("runnable-arguments");
+	game.executable.architecture = Some(Architecture::X64);
+	game.external_id = None;
+	game.start_command = Some(ProviderCommand::Path(
+		game.executable.path.clone(),
+		vec!["-flag".to_string(), "with space".to_string()],
+	));
+	let game_folder = paths::path_parent(&game.executable.path).unwrap();
+
+	let expand = |argument: &str| runnable_loader::expand_argument(argument, &game, false);
+
+	// A lone token can become any number of arguments.
+	assert_eq!(
+		expand("{{StartCommandArgs}}"),
+		vec!["-flag".to_string(), "with space".to_string()]
+	);
+	// Inside a bigger argument, the values get quoted and joined instead.
+	assert_eq!(
+		expand("--args={{StartCommandArgs}}"),
+		vec!["--args=-flag \"with space\"".to_string()]
+	);
+	assert_eq!(
+		expand("--target={{ExecutableName}}-{{Architecture}}"),
+		vec![format!("--target={}-X64", game.executable.name)]
+	);
+	assert_eq!(
+		expand("{{GameFolder}}"),
+		vec![game_folder.to_string_lossy().to_string()]
+	);
+	// Values that can't be found leave the token alone.
+	assert_eq!(expand("{{ExternalId}}"), vec!["{{ExternalId}}".to_string()]);
+	assert_eq!(expand("no-tokens"), vec!["no-tokens".to_string()]);
+}
+
+#[test]
+fn runnable_argument_quoting() {
+	assert_eq!(runnable_loader::quote_argument("plain"), "plain");
+	assert_eq!(runnable_loader::quote_argument(""), r#""""#);
+	assert_eq!(
+		runnable_loader::quote_argument("two words"),
+		r#""two words""#
+	);
+	assert_eq!(
+		runnable_loader::quote_argument(r#"say "hi""#),
+		r#""say \"hi\"""#
+	);
+	// Backslashes right before a quote get doubled, on top of the one escaping the quote.
+	assert_eq!(runnable_loader::quote_argument(r#"a\"b"#), r#""a\\\"b""#);
+	// Backslashes anywhere else stay as they are, except for the ones before the closing quote.
+	assert_eq!(
+		runnable_loader::quote_argument(r"C:\some folder\"),
+		r#""C:\some folder\\""#
+	);
+}
+
+#[test]
+fn wine_paths() {
+	assert_eq!(
+		wine::to_wine_path(Path::new("/home/someone/Game/Game.exe")),
+		r"Z:\home\someone\Game\Game.exe"
+	);
+	assert_eq!(
+		wine::to_wine_path(Path::new("folder with spaces/file")),
+		r"Z:folder with spaces\file"
+	);
+	// Already a Windows path, so it shouldn't end up on the Z: drive.
+	assert_eq!(
+		wine::to_wine_path(Path::new(r"C:\Games\Game.exe")),
+		r"C:\Games\Game.exe"
+	);
+	assert_eq!(
+		wine::to_wine_path(Path::new("D:/Games/Game.exe")),
+		r"D:\Games\Game.exe"
+	);
+}
+
+#[test]
+fn wine_proton_prefix() {
+	let steamapps_path = create_test_folder("proton-prefix").join("steamapps");
+	let game_folder = steamapps_path.join("common").join("Some Game");
+	fs::create_dir_all(&game_folder).unwrap();
+	let executable_path = game_folder.join("Game.exe");
+	fs::write(&executable_path, "").unwrap();
+
+	let mut game = InstalledGame::new(&executable_path).unwrap();
+	game.external_id = Some("1234".to_string());
+
+	// Only Steam games get a Proton prefix.
+	assert_eq!(wine::get_proton_prefix(&game), None);
+
+	game.provider_id = Some(ProviderId::Steam);
+	let compat_data_path = steamapps_path.join("compatdata").join("1234");
+	assert_eq!(
+		wine::get_steam_compat_data_path(&game),
+		Some(compat_data_path.clone())
+	);
+	// Prefixes that don't exist yet don't count.
+	assert_eq!(wine::get_proton_prefix(&game), None);
+
+	let prefix_path = compat_data_path.join("pfx");
+	fs::create_dir_all(&prefix_path).unwrap();
+	assert_eq!(wine::get_proton_prefix(&game), Some(prefix_path.clone()));
+
+	// The game's own Proton gets picked up from the paths Steam writes to config_info.
+	let proton_path = steamapps_path.join("common").join("Proton 9.0");
+	let wine_path = proton_path.join("files").join("bin").join("wine64");
+	fs::create_dir_all(paths::path_parent(&wine_path).unwrap()).unwrap();
+	fs::write(&wine_path, "").unwrap();
+	fs::write(
+		compat_data_path.join("config_info"),
+		format!("9.0-1\n{}/files/share/fonts/\n", proton_path.display()),
+	)
+	.unwrap();
+
+	let runtime = wine::get_runtime(Some(&game), Some(Architecture::X64));
+	assert_eq!(runtime.wine_path, wine_path);
+	assert_eq!(runtime.prefix_path, Some(prefix_path));
+}
+
+#[test]
+fn bepinex_config_validation() {
+	let game = create_test_game("bepinex-config-validation");
+	let mods_folder = create_test_folder("bepinex-config-validation-mods");
+	let config_folder = bepinex_config::get_config_folder(&game).unwrap();
+	fs::create_dir_all(&config_folder).unwrap();
+	fs::write(
+		config_folder.join("someone.coolmod.cfg"),
+		"## Plugin GUID: someone.coolmod
+
+[General]
+
+# Setting type: Single
+# Default value: 90
+# Acceptable value range: From 30 to 120
+Field Of View = 75
+
+# Setting type: CameraMode
+# Default value: Follow
+# Acceptable values: Follow, Fixed, Free
+Mode = Fixed
+
+# Setting type: LogLevel
+# Default value: Info
+# Acceptable values: Debug, Info, Warning
+# Multiple values can be set at the same time by separating them with , (e.g. Debug, Warning)
+Log Levels = Info
+
+# Setting type: String
+# Default value: Player
+Name = Player
+",
+	)
+	.unwrap();
+
+	let set_value = |key: &str, value: &str| {
+		bepinex_config::set_value(&game, "someone.coolmod.cfg", "General", key, value)
+	};
+	let is_invalid = |result: Result<_>| matches!(result, Err(Error::ModConfigValueInvalid(..)));
+
+	assert!(is_invalid(set_value("Name", "Someone\nEvil = true")));
+	assert!(is_invalid(set_value("Name", "Someone\r")));
+	assert!(is_invalid(set_value("Field Of View", "200")));
+	assert!(is_invalid(set_value("Field Of View", "wide")));
+	assert!(is_invalid(set_value("Mode", "Sideways")));
+	assert!(is_invalid(set_value("Mode", "Follow, Free")));
+	assert!(is_invalid(set_value("Log Levels", "Debug, Everything")));
+	assert!(matches!(
+		bepinex_config::override_value(&game, "someone.coolmod.cfg", "Other", "Key", "line\nbreak"),
+		Err(Error::ModConfigValueInvalid(..))
+	));
+
+	set_value("Field Of View", "120").unwrap();
+	set_value("Mode", "free").unwrap();
+	set_value("Log Levels", "Debug, Warning").unwrap();
+	let config = set_value("Name", "Someone Else").unwrap();
+	assert_eq!(
+		config
+			.entries
+			.iter()
+			.map(|entry| entry.value.as_str())
+			.collect::<Vec<_>>(),
+		vec!["120", "free", "Debug, Warning", "Someone Else"]
+	);
+
+	// Found by GUID when the mod doesn't say which config is its own.
+	let cool_mod = deploy_test_mod(&game, &mods_folder, "coolmod");
+	assert_eq!(
+		bepinex_config::get_mod_config_path(&game, &cool_mod).unwrap(),
+		Some(config_folder.join("someone.coolmod.cfg"))
+	);
+	let other_mod = deploy_test_mod(&game, &mods_folder, "other-mod");
+	assert_eq!(
+		bepinex_config::get_mod_config_path(&game, &other_mod).unwrap(),
+		None
+	);
+
+	let mut named_mod = other_mod;
+	named_mod.data.manifest = Some(Manifest {
+		config_file: Some("someone.coolmod.cfg".to_string()),
+		..create_test_manifest("1.0.0")
+	});
+	assert_eq!(
+		bepinex_config::get_mod_config_path(&game, &named_mod).unwrap(),
+		Some(config_folder.join("someone.coolmod.cfg"))
+	);
+}
+
+#[test]
+fn mod_backups_restore_game_files() {
+	let loader_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-data/sample-loader");
+	let loader = DescriptorLoader::new(&loader_path).unwrap();
+	let mod_loaders =
+		mod_loader::Map::from([(loader.data.id.clone(), ModLoader::DescriptorLoader(loader))]);
+
+	let mut game = create_test_game("mod-backups");
+	game.executable.architecture = Some(Architecture::X64);
+	let game_folder = paths::path_parent(&game.executable.path).unwrap();
+	let mods_folder = game.get_installed_mods_folder().unwrap();
+	fs::write(game_folder.join("winhttp.dll"), "original").unwrap();
+	fs::write(mods_folder.join("kept.txt"), "kept").unwrap();
+
+	mod_backups::create(&game, &mod_loaders).unwrap();
+
+	let result = mod_backups::restore_on_error(&game, {
+		for mod_loader in mod_loaders.values() {
+			mod_loader.install(&game).unwrap();
+		}
+		fs::remove_file(mods_folder.join("kept.txt")).unwrap();
+		assert!(game_folder.join("sample_loader.ini").is_file());
+		Err(Error::ModpackNotFound("broken".to_string()))
+	});
+
+	// The original error is what comes out, after everything goes back to how it was.
+	assert!(matches!(result, Err(Error::ModpackNotFound(..))));
+	assert_eq!(
+		fs::read_to_string(game_folder.join("winhttp.dll")).unwrap(),
+		"original"
+	);
+	assert!(!game_folder.join("sample_loader.ini").exists());
+	assert_eq!(
+		fs::read_to_string(mods_folder.join("kept.txt")).unwrap(),
+		"kept"
+	);
+	assert!(!mods_folder.join("SampleLoader").exists());
+
+	// Restoring without a backup fails, but still returns the original error.
+	assert!(matches!(
+		mod_backups::restore_on_error(&game, Err(Error::ModpackNotFound("again".to_string()))),
+		Err(Error::ModpackNotFound(id)) if id == "again"
+	));
+}
+
+#[test]
+fn mod_preset_rolls_back_when_applying_fails() {
+	let game = create_test_game("preset-rollback");
+	let mods_folder = BepInEx::get_installed_mods_path().unwrap().join("Mono");
+	deploy_test_mod(&game, &mods_folder, "preset-leftover");
+	let mod_loaders = create_bepinex_map(&create_test_folder("preset-rollback-resources"));
+
+	// Configs are written last, so the bad file name fails after everything else changed.
+	let preset = ModPreset {
+		format_version: 1,
+		mods: Vec::new(),
+		loader_versions: HashMap::new(),
+		configs: HashMap::from([
+			("someone.mod.cfg".to_string(), "[General]".to_string()),
+			("../escaped.cfg".to_string(), "[General]".to_string()),
+		]),
+	};
+
+	let result = poll_once(mod_presets::apply(
+		&game,
+		&preset,
+		&mod_loaders,
+		&remote_mod::Map::new(),
+		&|_| {},
+	));
+	assert!(matches!(result, Err(Error::ModConfigNotFound(..))));
+
+	// The mod that isn't part of the preset got uninstalled before the error, and is back now.
+	assert!(game.get_available_mods().contains_key("preset-leftover"));
+	assert!(
+		game.get_installed_mods_folder()
+			.unwrap()
+			.join("BepInEx")
+			.join("plugins")
+			.join("preset-leftover")
+			.join("preset-leftover.dll")
+			.is_file()
+	);
+	assert!(
+		!bepinex_config::get_config_folder(&game)
+			.unwrap()
+			.join("someone.mod.cfg")
+			.exists()
+	);
+}
+
+#[test]
+fn mod_preset_deploys_loader_version() {
+	let resources_path = create_test_folder("preset-loader-version-resources");
+	let bepinex_path = resources_path.join("bepinex");
+	write_test_bepinex_build(
+		&bepinex_path
+			.join("builds")
+			.join("BleedingEdge")
+			.join("Il2Cpp"),
+		"Bundled.dll",
+	);
+	fs::create_dir_all(bepinex_path.join("config")).unwrap();
+	fs::write(bepinex_path.join("config").join("BepInEx.cfg"), "default").unwrap();
+	let mod_loaders = create_bepinex_map(&resources_path);
+	write_test_payload(BepInExBuild::BleedingEdge, "6.0.0-be.725");
+
+	let mut game = create_test_game("preset-loader-version");
+	game.executable.scripting_backend = Some(UnityScriptingBackend::Il2Cpp);
+	game.executable.architecture = Some(Architecture::X64);
+
+	let preset = ModPreset {
+		format_version: 1,
+		mods: Vec::new(),
+		loader_versions: HashMap::from([(
+			BepInEx::ID.to_string(),
+			Some("6.0.0-be.725".to_string()),
+		)]),
+		configs: HashMap::new(),
+	};
+
+	// Already downloaded, so nothing needs the loader index.
+	poll_once(mod_presets::apply(
+		&game,
+		&preset,
+		&mod_loaders,
+		&remote_mod::Map::new(),
+		&|_| {},
+	))
+	.unwrap();
+
+	let game_build = bepinex_builds::read(&game);
+	assert_eq!(game_build.deployed, Some(BepInExBuild::BleedingEdge));
+	assert_eq!(game_build.deployed_payload.as_deref(), Some("6.0.0-be.725"));
+	assert!(
+		game.get_installed_mods_folder()
+			.unwrap()
+			.join("BepInEx")
+			.join("core")
+			.join("Payload-6.0.0-be.725.dll")
+			.is_file()
+	);
+}
+
+#[test]
+fn mod_integrity_verify_and_repair() {
+	let loader_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-data/sample-loader");
+	let loader = DescriptorLoader::new(&loader_path).unwrap();
+	let loader_id = loader.data.id.clone();
+	let mod_loaders =
+		mod_loader::Map::from([(loader_id.clone(), ModLoader::DescriptorLoader(loader))]);
+	let mod_loader = &mod_loaders[&loader_id];
+
+	let mut game = create_test_game("mod-integrity");
+	game.executable.architecture = Some(Architecture::X64);
+	let game_folder = paths::path_parent(&game.executable.path).unwrap();
+
+	let mod_path = create_test_folder("mod-integrity-mods").join("intact");
+	write_manifest(
+		&local_mod::get_manifest_path(&mod_path),
+		&create_test_manifest("1.0.0"),
+	);
+	fs::write(mod_path.join("intact.dll"), "mod").unwrap();
+	let local_mod = LocalMod::new(&loader_id, &mod_path, None, None).unwrap();
+	let local_mods = local_mod::Map::from([("intact".to_string(), local_mod.clone())]);
+
+	poll_once(mod_loader.install_single_mod(&game, &local_mod)).unwrap();
+	let intact_integrity = mod_integrity::verify(&game, &mod_loaders).unwrap();
+	assert!(intact_integrity.loaders.is_empty());
+	assert!(intact_integrity.mods.is_empty());
+
+	let proxy_path = game_folder.join("winhttp.dll");
+	let mod_dll_path = mod_loader
+		.get_deployed_mod_paths(&game, "intact")
+		.unwrap()
+		.remove(0)
+		.join("intact.dll");
+	fs::write(&proxy_path, "tampered").unwrap();
+	fs::remove_file(&mod_dll_path).unwrap();
+
+	let broken_integrity = mod_integrity::verify(&game, &mod_loaders).unwrap();
+	assert_eq!(broken_integrity.loaders.len(), 1);
+	assert_eq!(
+		broken_integrity.loaders[0]
+			.issues
+			.iter()
+			.map(|issue| (issue.path.clone(), issue.kind))
+			.collect::<Vec<_>>(),
+		vec![(proxy_path.clone(), FileIssueKind::Modified)]
+	);
+	assert_eq!(broken_integrity.mods.len(), 1);
+	assert_eq!(broken_integrity.mods[0].mod_id, "intact");
+	assert_eq!(
+		broken_integrity.mods[0]
+			.issues
+			.iter()
+			.map(|issue| (issue.path.clone(), issue.kind))
+			.collect::<Vec<_>>(),
+		vec![(mod_dll_path.clone(), FileIssueKind::Missing)]
+	);
+
+	let repaired_integrity =
+		poll_once(mod_integrity::repair(&game, &mod_loaders, &local_mods)).unwrap();
+	assert!(repaired_integrity.loaders.is_empty());
+	assert!(repaired_integrity.mods.is_empty());
+	assert_eq!(fs::read_to_string(&proxy_path).unwrap(), "proxy");
+	assert_eq!(fs::read_to_string(&mod_dll_path).unwrap(), "mod");
+}
+
+#[test]
+fn mod_integrity_ignores_edited_configs() {
+	let resources_path = create_test_folder("mod-integrity-configs-resources");
+	let bepinex_path = resources_path.join("bepinex");
+	write_test_bepinex_build(&bepinex_path.join("Mono"), "BepInEx.dll");
+	fs::create_dir_all(bepinex_path.join("config")).unwrap();
+	fs::write(bepinex_path.join("config").join("BepInEx.cfg"), "default").unwrap();
+	let mod_loaders = create_bepinex_map(&resources_path);
+
+	let mut game = create_test_game("mod-integrity-configs");
+	game.executable.architecture = Some(Architecture::X64);
+
+	let mod_path = BepInEx::get_installed_mods_path()
+		.unwrap()
+		.join("Mono")
+		.join("integrity-configs");
+	write_manifest(
+		&local_mod::get_manifest_path(&mod_path),
+		&create_test_manifest("1.0.0"),
+	);
+	fs::create_dir_all(mod_path.join("plugins")).unwrap();
+	fs::write(mod_path.join("plugins").join("IntegrityConfigs.dll"), "").unwrap();
+	fs::create_dir_all(mod_path.join("config")).unwrap();
+	fs::write(mod_path.join("config").join("integrity-configs.json"), "{}").unwrap();
+	let local_mod = LocalMod::new(
+		BepInEx::ID,
+		&mod_path,
+		None,
+		Some(UnityScriptingBackend::Mono),
+	)
+	.unwrap();
+	poll_once(mod_loaders[BepInEx::ID].install_single_mod(&game, &local_mod)).unwrap();
+
+	fs::write(
+		bepinex_config::get_config_folder(&game)
+			.unwrap()
+			.join("integrity-configs.json"),
+		r#"{"edited": true}"#,
+	)
+	.unwrap();
+
+	let integrity = mod_integrity::verify(&game, &mod_loaders).unwrap();
+	assert!(integrity.loaders.is_empty());
+	assert!(integrity.mods.is_empty());
+}
+
+#[test]
+fn bepinex_deployed_mod_paths_include_configs() {
+	let game = create_test_game("bepinex-deployed-paths");
+	let mod_loaders = create_bepinex_map(&create_test_folder("bepinex-deployed-paths-resources"));
+	let mods_folder = BepInEx::get_installed_mods_path().unwrap().join("Mono");
+	deploy_test_mod(&game, &mods_folder, "with-config");
+
+	let mod_config_path = mods_folder.join("with-config").join("config");
+	fs::create_dir_all(mod_config_path.join("nested")).unwrap();
+	fs::write(mod_config_path.join("with-config.json"), "{}").unwrap();
+	fs::write(mod_config_path.join("nested").join("extra.txt"), "").unwrap();
+
+	let bepinex_folder = game.get_installed_mods_folder().unwrap().join("BepInEx");
+	let mut deployed_paths = mod_loaders[BepInEx::ID]
+		.get_deployed_mod_paths(&game, "with-config")
+		.unwrap();
+	deployed_paths.sort();
+	assert_eq!(
+		deployed_paths,
+		vec![
+			bepinex_folder
+				.join("config")
+				.join("nested")
+				.join("extra.txt"),
+			bepinex_folder.join("config").join("with-config.json"),
+			bepinex_folder.join("patchers").join("with-config"),
+			bepinex_folder.join("plugins").join("with-config"),
+		]
+	);
+}
+
+#[test]
+fn bepinex_config_survives_mod_installs() {
+	let resources_path = create_test_folder("bepinex-config-survives-resources");
+	let bepinex_path = resources_path.join("bepinex");
+	write_test_bepinex_build(&bepinex_path.join("Mono"), "BepInEx.dll");
+	fs::create_dir_all(bepinex_path.join("config")).unwrap();
+	fs::write(
+		bepinex_path.join("config").join("BepInEx.cfg"),
+		"[Logging]
+
+# Setting type: Boolean
+# Default value: true
+Enabled = true
+",
+	)
+	.unwrap();
+	let mod_loaders = create_bepinex_map(&resources_path);
+	let mod_loader = &mod_loaders[BepInEx::ID];
+
+	let mut game = create_test_game("bepinex-config-survives");
+	game.executable.architecture = Some(Architecture::X64);
+	let mods_folder = BepInEx::get_installed_mods_path().unwrap().join("Mono");
+	let install_mod = |mod_id: &str| {
+		let mod_path = mods_folder.join(mod_id);
+		write_manifest(
+			&local_mod::get_manifest_path(&mod_path),
+			&create_test_manifest("1.0.0"),
+		);
+		fs::create_dir_all(mod_path.join("plugins")).unwrap();
+		fs::write(mod_path.join("plugins").join(format!("{mod_id}.dll")), "").unwrap();
+		let local_mod = LocalMod::new(
+			BepInEx::ID,
+			&mod_path,
+			None,
+			Some(UnityScriptingBackend::Mono),
+		)
+		.unwrap();
+		poll_once(mod_loader.install_single_mod(&game, &local_mod)).unwrap();
+	};
+
+	install_mod("config-survives-first");
+	bepinex_config::set_value(&game, "BepInEx.cfg", "Logging", "Enabled", "false").unwrap();
+	install_mod("config-survives-second");
+
+	assert!(
+		fs::read_to_string(
+			bepinex_config::get_config_folder(&game)
+				.unwrap()
+				.join("BepInEx.cfg")
+		)
+		.unwrap()
+		.contains("Enabled = false")
+	);
+}
+
+#[test]
+fn bepinex_install_preview_legacy_config() {
+	let resources_path = create_test_folder("bepinex-preview-legacy-resources");
+	let bepinex_path = resources_path.join("bepinex");
+	write_test_bepinex_build(&bepinex_path.join("Mono"), "BepInEx.dll");
+	fs::create_dir_all(bepinex_path.join("config")).unwrap();
+	fs::write(bepinex_path.join("config").join("BepInEx.cfg"), "default").unwrap();
+	let legacy_config = "legacy config for old Unity versions";
+	fs::write(
+		bepinex_path.join("config").join("BepInEx-legacy.cfg"),
+		legacy_config,
+	)
+	.unwrap();
+	let bepinex = BepInEx::new(&resources_path).unwrap();
+
+	let mut game = create_test_game("bepinex-preview-legacy");
+	game.executable.architecture = Some(Architecture::X64);
+	game.executable.engine = Some(GameEngine {
+		brand: EngineBrand::Unity,
+		version: Some(EngineVersion {
+			numbers: engine_version_numbers(5, Some(4)),
+			suffix: None,
+			display: "5.4".to_string(),
+		}),
+	});
+
+	let config_entry = bepinex
+		.get_install_preview(&game)
+		.unwrap()
+		.into_iter()
+		.find(|entry| entry.path.ends_with("BepInEx.cfg"))
+		.unwrap();
+	assert_eq!(
+		config_entry.size,
+		u64::try_from(legacy_config.len()).unwrap()
+	);
+}
+
+#[test]
+fn bepinex_target_build_precedence() {
+	let game = create_test_game("bepinex-target-build");
+	let mods_folder = create_test_folder("bepinex-target-build-mods");
+	let bepinex = BepInEx::new(&mods_folder).unwrap();
+
+	let mut local_mod = deploy_test_mod(&game, &mods_folder, "needs-stable6");
+	local_mod.data.manifest = Some(Manifest {
+		bepinex_builds: Some(vec![BepInExBuild::Stable6]),
+		..create_test_manifest("1.0.0")
+	});
+
+	// Nothing else to go on, so it's the default for the scripting backend.
+	assert_eq!(
+		bepinex.get_target_build(&game, None).unwrap(),
+		BepInExBuild::Stable5
+	);
+	// Then whatever the mod needs.
+	assert_eq!(
+		bepinex.get_target_build(&game, Some(&local_mod)).unwrap(),
+		BepInExBuild::Stable6
+	);
+
+	// The deployed build wins over what the mod needs, even if the mod doesn't work with it.
+	bepinex_builds::write(
+		&game,
+		&BepInExGameBuild {
+			selected: None,
+			deployed: Some(BepInExBuild::Stable5),
+			deployed_payload: None,
+		},
+	)
+	.unwrap();
+	assert_eq!(
+		bepinex.get_target_build(&game, None).unwrap(),
+		BepInExBuild::Stable5
+	);
+	assert!(matches!(
+		bepinex.get_target_build(&game, Some(&local_mod)),
+		Err(Error::BepInExBuildMismatch(..))
+	));
+
+	// The build the user picked wins over everything.
+	BepInEx::select_build(&game, Some(BepInExBuild::Stable6)).unwrap();
+	assert_eq!(
+		bepinex.get_target_build(&game, Some(&local_mod)).unwrap(),
+		BepInExBuild::Stable6
+	);
+}
+
+#[test]
+fn bepinex_build_resources_path() {
+	let bepinex_path = create_test_folder("bepinex-resources-path");
+	let mono = UnityScriptingBackend::Mono;
+
+	assert!(matches!(
+		BepInExBuild::Stable5.get_resources(
+			&bepinex_path,
+			UnityScriptingBackend::Il2Cpp,
+			&PayloadChoice::Latest
+		),
+		Err(Error::BepInExBuildUnsupported(..))
+	));
+	assert!(matches!(
+		BepInExBuild::Stable5.get_resources(&bepinex_path, mono, &PayloadChoice::Latest),
+		Err(Error::BepInExBuildNotAvailable(..))
+	));
+
+	// The default build is at the root, the others get their own folder.
+	fs::create_dir_all(bepinex_path.join("Mono")).unwrap();
+	fs::create_dir_all(
+		bepinex_path
+			.join("builds")
+			.join("BleedingEdge")
+			.join("Mono"),
+	)
+	.unwrap();
+	assert_eq!(
+		BepInExBuild::Stable5
+			.get_resources(&bepinex_path, mono, &PayloadChoice::Latest)
+			.unwrap()
+			.path,
+		bepinex_path.join("Mono")
+	);
+	assert_eq!(
+		BepInExBuild::BleedingEdge
+			.get_resources(&bepinex_path, mono, &PayloadChoice::Latest)
+			.unwrap()
+			.path,
+		bepinex_path
+			.join("builds")
+			.join("BleedingEdge")
+			.join("Mono")
+	);
+	assert_eq!(
+		BepInExBuild::get_available(&bepinex_path, mono),
+		vec![BepInExBuild::Stable5, BepInExBuild::BleedingEdge]
+	);
+}
+
+#[test]
+fn bepinex_upgrade() {
+	let resources_path = create_test_folder("bepinex-upgrade-resources");
+	let bepinex_path = resources_path.join("bepinex");
+	let builds_path = bepinex_path.join("builds");
+	write_test_bepinex_build(&bepinex_path.join("Mono"), "Stable5.dll");
+	write_test_bepinex_build(&builds_path.join("Stable6").join("Mono"), "Stable6.dll");
+	// Doesn't have the mod loader archive, so it can't be deployed.
+	fs::create_dir_all(builds_path.join("BleedingEdge").join("Mono")).unwrap();
+	fs::create_dir_all(bepinex_path.join("config")).unwrap();
+	fs::write(bepinex_path.join("config").join("BepInEx.cfg"), "default").unwrap();
+	let bepinex = BepInEx::new(&resources_path).unwrap();
+
+	let mut game = create_test_game("bepinex-upgrade");
+	game.executable.architecture = Some(Architecture::X64);
+	let bepinex_folder = game.get_installed_mods_folder().unwrap().join("BepInEx");
+	let config_path = bepinex_folder.join("config").join("BepInEx.cfg");
+
+	bepinex.install(&game).unwrap();
+	assert!(bepinex_folder.join("core").join("Stable5.dll").is_file());
+	for folder_name in ["interop", "unhollowed", "cache"] {
+		fs::create_dir_all(bepinex_folder.join(folder_name)).unwrap();
+		fs::write(bepinex_folder.join(folder_name).join("Generated.dll"), "").unwrap();
+	}
+	fs::write(&config_path, "edited").unwrap();
+
+	// Nothing gets deleted if the new build can't be deployed.
+	assert!(matches!(
+		bepinex.upgrade(&game, BepInExBuild::BleedingEdge, &PayloadChoice::Latest),
+		Err(Error::BepInExBuildIncomplete(..))
+	));
+	assert!(bepinex_folder.join("core").join("Stable5.dll").is_file());
+	assert!(bepinex_folder.join("interop").is_dir());
+
+	bepinex
+		.upgrade(&game, BepInExBuild::Stable6, &PayloadChoice::Latest)
+		.unwrap();
+	assert!(bepinex_folder.join("core").join("Stable6.dll").is_file());
+	assert!(!bepinex_folder.join("core").join("Stable5.dll").exists());
+	for folder_name in ["interop", "unhollowed", "cache"] {
+		assert!(!bepinex_folder.join(folder_name).exists());
+	}
+	assert_eq!(fs::read_to_string(&config_path).unwrap(), "edited");
+	assert_eq!(
+		bepinex_builds::read(&game).deployed,
+		Some(BepInExBuild::Stable6)
+	);
+}
+
+fn create_test_payload(build: &str, version: &str, sha256: Option<&str>) -> LoaderPayload {
+	LoaderPayload {
+		build: build.to_string(),
+		scripting_backend: UnityScriptingBackend::Il2Cpp,
+		download: ModDownload {
+			id: version.to_string(),
+			url: format!("https://example.com/{build}-{version}.zip"),
+			root: None,
+			runnable: None,
+			sha256: sha256.map(ToString::to_string),
+			minisign: None,
+		},
+	}
+}
+
+// Same thing a finished download leaves behind.
+fn write_test_payload(build: BepInExBuild, version: &str) {
+	let payload = create_test_payload(&build.to_string(), version, Some("abc"));
+	let payload_path = loader_payloads::get_payload_path(
+		BepInEx::ID,
+		&payload.build,
+		payload.scripting_backend,
+		version,
+	)
+	.unwrap();
+	write_test_bepinex_build(&payload_path, &format!("Payload-{version}.dll"));
+	fs::write(
+		payload_path.join("rai-pal-loader-payload.json"),
+		serde_json::to_string(&payload).unwrap(),
+	)
+	.unwrap();
+}
+
+#[test]
+fn loader_payload_validation() {
+	assert!(
+		loader_payloads::validate(
+			BepInEx::ID,
+			&create_test_payload("Stable6", "6.0.0", Some("abc"))
+		)
+		.is_ok()
+	);
+	assert!(matches!(
+		loader_payloads::validate(BepInEx::ID, &create_test_payload("Stable6", "6.0.0", None)),
+		Err(Error::LoaderPayloadInvalid(..))
+	));
+	assert!(matches!(
+		loader_payloads::validate(
+			BepInEx::ID,
+			&create_test_payload("Stable6", "../6.0.0", Some("abc"))
+		),
+		Err(Error::LoaderPayloadInvalid(..))
+	));
+	assert!(matches!(
+		loader_payloads::validate(
+			BepInEx::ID,
+			&create_test_payload("..", "6.0.0", Some("abc"))
+		),
+		Err(Error::LoaderPayloadInvalid(..))
+	));
+}
+
+#[test]
+fn loader_payload_latest_per_build() {
+	let index = LoaderIndex {
+		payloads: vec![
+			create_test_payload("Stable6", "6.0.9", Some("abc")),
+			create_test_payload("Stable6", "6.0.10", Some("abc")),
+			create_test_payload("Stable6", "6.0.2", Some("abc")),
+			create_test_payload("BleedingEdge", "6.0.0-be.700", Some("abc")),
+			create_test_payload("BleedingEdge", "6.0.0-be.725", Some("abc")),
+		],
+	};
+
+	let mut latest_payloads: Vec<(String, String)> = loader_payloads::get_latest_payloads(&index)
+		.into_iter()
+		.map(|payload| (payload.build.clone(), payload.download.id.clone()))
+		.collect();
+	latest_payloads.sort();
+
+	assert_eq!(
+		latest_payloads,
+		vec![
+			("BleedingEdge".to_string(), "6.0.0-be.725".to_string()),
+			("Stable6".to_string(), "6.0.10".to_string()),
+		]
+	);
+}
+
+#[test]
+fn bepinex_deployed_payload_version() {
+	let build = BepInExBuild::Stable6;
+	let il2cpp = UnityScriptingBackend::Il2Cpp;
+	let resources_path = create_test_folder("bepinex-payload-resources");
+	let bepinex_path = resources_path.join("bepinex");
+	let bundled_path = bepinex_path.join("builds").join("Stable6").join("Il2Cpp");
+	write_test_bepinex_build(&bundled_path, "Bundled.dll");
+	fs::create_dir_all(bepinex_path.join("config")).unwrap();
+	fs::write(bepinex_path.join("config").join("BepInEx.cfg"), "default").unwrap();
+	let bepinex = BepInEx::new(&resources_path).unwrap();
+
+	let mut game = create_test_game("bepinex-payload");
+	game.executable.scripting_backend = Some(il2cpp);
+	game.executable.architecture = Some(Architecture::X64);
+	let core_path = game
+		.get_installed_mods_folder()
+		.unwrap()
+		.join("BepInEx")
+		.join("core");
+	BepInEx::select_build(&game, Some(build)).unwrap();
+
+	// Nothing downloaded yet, so the bundled files get deployed.
+	bepinex.install(&game).unwrap();
+	assert!(core_path.join("Bundled.dll").is_file());
+	assert_eq!(bepinex_builds::read(&game).deployed_payload, None);
+
+	// Fresh deploys get the latest payload.
+	write_test_payload(build, "6.0.9");
+	bepinex
+		.upgrade(&game, build, &PayloadChoice::Latest)
+		.unwrap();
+	assert!(core_path.join("Payload-6.0.9.dll").is_file());
+	assert_eq!(
+		bepinex_builds::read(&game).deployed_payload.as_deref(),
+		Some("6.0.9")
+	);
+
+	// Newer payloads don't get deployed when BepInEx gets redeployed, only when upgrading.
+	write_test_payload(build, "6.0.10");
+	assert_eq!(
+		loader_payloads::get_downloaded_versions(BepInEx::ID, "Stable6", il2cpp),
+		vec!["6.0.10".to_string(), "6.0.9".to_string()]
+	);
+	bepinex.install(&game).unwrap();
+	assert!(!core_path.join("Payload-6.0.10.dll").exists());
+	assert_eq!(
+		bepinex_builds::read(&game).deployed_payload.as_deref(),
+		Some("6.0.9")
+	);
+
+	bepinex
+		.upgrade(&game, build, &PayloadChoice::Latest)
+		.unwrap();
+	assert!(core_path.join("Payload-6.0.10.dll").is_file());
+	assert!(!core_path.join("Payload-6.0.9.dll").exists());
+	assert_eq!(
+		bepinex_builds::read(&game).deployed_payload.as_deref(),
+		Some("6.0.10")
+	);
+
+	// Payloads that aren't downloaded fall back to the bundled files.
+	let resources = build
+		.get_resources(
+			&bepinex_path,
+			il2cpp,
+			&PayloadChoice::Version(Some("6.0.11".to_string())),
+		)
+		.unwrap();
+	assert_eq!(resources.path, bundled_path);
+	assert_eq!(resources.payload_version, None);
+}
+
+#[test]
+fn github_checksums_by_tag() {
+	let mut github = ModGithubInfo {
+		user: "someone".to_string(),
+		repo: "cool-mod".to_string(),
+		asset_name: "CoolMod.zip".to_string(),
+		root: None,
+		runnable: None,
+		sha256: None,
+		minisign: None,
+	};
+	let get_sha256 = |github_info: &ModGithubInfo, tag: &str| {
+		github_info
+			.get_release_download(tag.to_string(), format!("https://example.com/{tag}"))
+			.map(|download| download.sha256)
+	};
+
+	// Nothing to check against, so nothing to enforce either.
+	assert_eq!(get_sha256(&github, "v2"), Some(None));
+
+	github.sha256 = Some(HashMap::from([("v1".to_string(), "abc".to_string())]));
+	assert_eq!(get_sha256(&github, "v1"), Some(Some("abc".to_string())));
+	// Newer releases don't get to skip verification.
+	assert_eq!(get_sha256(&github, "v2"), None);
+
+	github.minisign = Some(ModSignature {
+		public_key: "key".to_string(),
+		signature_url: None,
+	});
+	assert_eq!(get_sha256(&github, "v2"), Some(None));
+}
@@ -0,0 +1,21 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::DateTime;
+
+// Unix timestamp, in seconds.
+pub fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |duration| duration.as_secs())
+}
+
+// For timestamps that end up in front of people, like in log files.
+pub fn format(timestamp: u64) -> String {
+	i64::try_from(timestamp)
+		.ok()
+		.and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+		.map_or_else(
+			|| timestamp.to_string(),
+			|date_time| date_time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+		)
+}
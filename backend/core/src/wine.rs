@@ -0,0 +1,213 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+use log::{error, info};
+use rai_pal_proc_macros::serializable_struct;
+
+use crate::{
+	game_executable::{self, Architecture},
+	installed_game::InstalledGame,
+	paths,
+	providers::provider::ProviderId,
+	result::Result,
+};
+
+#[serializable_struct]
+#[derive(Default)]
+pub struct WineSettings {
+	// Wine binary used when the game doesn't have its own Proton. Uses `wine` from the PATH if empty.
+	pub default_runner: Option<PathBuf>,
+	// Prefix used with the default runner. Uses Wine's own default if empty.
+	pub default_prefix: Option<PathBuf>,
+}
+
+// Where a Windows program should run.
+pub struct WineRuntime {
+	pub wine_path: PathBuf,
+	pub prefix_path: Option<PathBuf>,
+}
+
+const FILE_NAME: &str = "wine.json";
+
+fn get_path() -> Result<PathBuf> {
+	Ok(paths::app_data_path()?.join(FILE_NAME))
+}
+
+fn try_read() -> Result<WineSettings> {
+	let path = get_path()?;
+	if !path.is_file() {
+		return Ok(WineSettings::default());
+	}
+
+	Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn read() -> WineSettings {
+	try_read().unwrap_or_else(|err| {
+		error!("Failed to read Wine settings: {err}");
+		WineSettings::default()
+	})
+}
+
+pub fn write(settings: &WineSettings) -> Result {
+	let path = get_path()?;
+	fs::create_dir_all(paths::path_parent(&path)?)?;
+
+	Ok(fs::write(path, serde_json::to_string_pretty(settings)?)?)
+}
+
+// Windows programs need Wine everywhere except Windows.
+pub fn is_needed(executable_path: &Path) -> bool {
+	cfg!(not(target_os = "windows"))
+		&& executable_path
+			.extension()
+			.is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+}
+
+// Turns a path into one Windows programs running in Wine understand.
+// Wine maps the Z: drive to the root of the file system.
+// Paths that already start with a drive letter are already Windows paths, so they keep their drive.
+pub fn to_wine_path(path: &Path) -> String {
+	let path_string = path.to_string_lossy().replace('/', "\\");
+	let mut characters = path_string.chars();
+	let has_drive_letter = characters
+		.next()
+		.is_some_and(|character| character.is_ascii_alphabetic())
+		&& characters.next() == Some(':');
+
+	if has_drive_letter {
+		path_string
+	} else {
+		format!("Z:{path_string}")
+	}
+}
+
+// Steam keeps each game's Proton files in the same library as the game.
+pub fn get_steam_compat_data_path(game: &InstalledGame) -> Option<PathBuf> {
+	if game.provider_id != Some(ProviderId::Steam) {
+		return None;
+	}
+
+	let steamapps_path = game
+		.executable
+		.path
+		.ancestors()
+		.find(|ancestor| ancestor.file_name().is_some_and(|name| name == "steamapps"))?;
+
+	Some(
+		steamapps_path
+			.join("compatdata")
+			.join(game.external_id.as_ref()?),
+	)
+}
+
+pub fn get_proton_prefix(game: &InstalledGame) -> Option<PathBuf> {
+	Some(get_steam_compat_data_path(game)?.join("pfx")).filter(|prefix_path| prefix_path.is_dir())
+}
+
+// Steam writes the paths of the Proton version a game last ran with to config_info,
+// with lines like "/path/to/Proton 9.0/files/share/fonts/".
+fn get_proton_path(game: &InstalledGame) -> Option<PathBuf> {
+	let config_info =
+		fs::read_to_string(get_steam_compat_data_path(game)?.join("config_info")).ok()?;
+
+	config_info.lines().find_map(|line| {
+		let (proton_path, _) = line
+			.split_once("/files/share/")
+			.or_else(|| line.split_once("/dist/share/"))?;
+
+		Some(PathBuf::from(proton_path))
+	})
+}
+
+fn get_proton_wine_path(proton_path: &Path, architecture: Option<Architecture>) -> Option<PathBuf> {
+	let binary_names: &[&str] = if architecture == Some(Architecture::X86) {
+		&["wine"]
+	} else {
+		&["wine64", "wine"]
+	};
+
+	["files", "dist"]
+		.iter()
+		.flat_map(|folder_name| {
+			binary_names
+				.iter()
+				.map(move |binary_name| proton_path.join(folder_name).join("bin").join(binary_name))
+		})
+		.find(|wine_path| wine_path.is_file())
+}
+
+// Prefers the game's own Proton and prefix, falling back to the default runner from the settings.
+pub fn get_runtime(
+	game: Option<&InstalledGame>,
+	architecture: Option<Architecture>,
+) -> WineRuntime {
+	if let Some(game) = game {
+		if let Some(wine_path) = get_proton_path(game)
+			.and_then(|proton_path| get_proton_wine_path(&proton_path, architecture))
+		{
+			return WineRuntime {
+				wine_path,
+				prefix_path: get_proton_prefix(game),
+			};
+		}
+	}
+
+	let settings = read();
+
+	WineRuntime {
+		wine_path: settings
+			.default_runner
+			.unwrap_or_else(|| PathBuf::from("wine")),
+		prefix_path: settings
+			.default_prefix
+			.or_else(|| game.and_then(get_proton_prefix)),
+	}
+}
+
+// Command that runs the given Windows executable through Wine.
+pub fn create_command(executable_path: &Path, game: Option<&InstalledGame>) -> Command {
+	let architecture = game_executable::get_architecture(executable_path).unwrap_or_else(|err| {
+		error!(
+			"Failed to read architecture of {}: {err}",
+			executable_path.display()
+		);
+		None
+	});
+
+	let runtime = get_runtime(game, architecture);
+
+	info!(
+		"Running {} with Wine from {}",
+		executable_path.display(),
+		runtime.wine_path.display()
+	);
+
+	let mut command = Command::new(&runtime.wine_path);
+	command.arg(executable_path);
+
+	if let Some(prefix_path) = &runtime.prefix_path {
+		command.env("WINEPREFIX", prefix_path);
+	}
+
+	// WINEARCH only matters when Wine creates a new prefix, and breaks existing prefixes of the other kind.
+	if let Some(architecture) = architecture.filter(|_| {
+		runtime
+			.prefix_path
+			.as_ref()
+			.is_some_and(|prefix_path| !prefix_path.exists())
+	}) {
+		command.env(
+			"WINEARCH",
+			match architecture {
+				Architecture::X64 => "win64",
+				Architecture::X86 => "win32",
+			},
+		);
+	}
+
+	command
+}
@@ -5,7 +5,13 @@ use serde::Serialize;
 use tauri_specta::Event;
 
 use rai_pal_core::{
-	game::GameId, local_mod, mod_loaders::mod_loader, providers::provider::ProviderId, remote_mod,
+	download::DownloadProgress,
+	game::GameId,
+	local_mod,
+	mod_loaders::{mod_loader, runnable_processes::RunnableExit},
+	mod_updates::OutdatedMod,
+	providers::provider::ProviderId,
+	remote_mod,
 };
 
 #[serializable_event]
@@ -23,6 +29,18 @@ pub struct SyncLocalMods(pub local_mod::Map);
 #[serializable_event]
 pub struct SyncRemoteMods(pub remote_mod::Map);
 
+#[serializable_event]
+pub struct SyncOutdatedMods(pub Vec<OutdatedMod>);
+
+#[serializable_event]
+pub struct ModDownloadProgress(pub DownloadProgress);
+
+#[serializable_event]
+pub struct RunnableExited(pub RunnableExit);
+
+#[serializable_event]
+pub struct RunnableCrashed(pub RunnableExit);
+
 #[serializable_event]
 pub struct ExecutedProviderCommand;
 
@@ -60,6 +78,10 @@ pub fn collect_events() -> tauri_specta::Events {
 		SyncModLoaders,
 		SyncLocalMods,
 		SyncRemoteMods,
+		SyncOutdatedMods,
+		ModDownloadProgress,
+		RunnableExited,
+		RunnableCrashed,
 		ExecutedProviderCommand,
 		SelectInstalledGame,
 		ErrorRaised,
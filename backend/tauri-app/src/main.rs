@@ -4,17 +4,36 @@
 #![allow(clippy::unused_async)]
 
 use std::sync::RwLock;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+};
 
 use crate::result::{Error, Result};
 use app_state::{AppState, StateData, StatefulHandle};
 use events::EventEmitter;
+use rai_pal_core::archive::ArchiveEntry;
 use rai_pal_core::game::{self, Game, GameId};
 use rai_pal_core::games_query::GamesQuery;
 use rai_pal_core::installed_game::InstalledGame;
 use rai_pal_core::local_mod::{self, LocalMod};
 use rai_pal_core::maps::TryGettable;
-use rai_pal_core::mod_loaders::mod_loader::{self, ModLoaderActions};
+use rai_pal_core::mod_compatibility::{self, CompatibleMods, ModCompatibility};
+use rai_pal_core::mod_loaders::bepinex::{self, BepInEx, BepInExBuildOptions};
+use rai_pal_core::mod_loaders::bepinex_builds::{BepInExBuild, PayloadChoice};
+use rai_pal_core::mod_loaders::bepinex_config::{self, BepInExConfig};
+use rai_pal_core::mod_loaders::loader_payloads;
+use rai_pal_core::mod_loaders::mod_import::{self, ModImportOptions, ModImportPreview};
+use rai_pal_core::mod_loaders::mod_integrity::{self, GameIntegrity};
+use rai_pal_core::mod_loaders::mod_loader::{self, ModLoaderActions, ModLoaderStatic};
+use rai_pal_core::mod_loaders::mod_sources::{self, ModSources};
+use rai_pal_core::mod_loaders::modpacks::{self, Modpack};
+use rai_pal_core::mod_loaders::runnable_processes::{self, RunnableProcess};
+use rai_pal_core::mod_pins;
+use rai_pal_core::mod_presets;
+use rai_pal_core::mod_profiles::{self, ModProfiles};
+use rai_pal_core::mod_updates::{self, OutdatedMod};
+use rai_pal_core::mod_versions::{self, ModVersion};
 use rai_pal_core::paths::{self, normalize_path};
 use rai_pal_core::providers::provider::ProviderId;
 use rai_pal_core::providers::provider_cache;
@@ -24,9 +43,12 @@ use rai_pal_core::providers::{
 	provider::{self, ProviderActions},
 	provider_command::ProviderCommandAction,
 };
+use rai_pal_core::remote_database::{self, DatabaseStatus};
+use rai_pal_core::session_diagnostics::{self, SessionDiagnostics};
 #[cfg(target_os = "windows")]
 use rai_pal_core::windows;
-use rai_pal_core::{analytics, remote_game, remote_mod};
+use rai_pal_core::wine::{self, WineSettings};
+use rai_pal_core::{analytics, download, remote_game, remote_mod};
 use rai_pal_proc_macros::serializable_struct;
 use tauri::path::BaseDirectory;
 use tauri::{AppHandle, Manager};
@@ -107,6 +129,14 @@ async fn open_mod_loader_folder(mod_loader_id: &str, handle: AppHandle) -> Resul
 		.open_folder()?)
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn cancel_mod_download(mod_id: &str) -> Result {
+	download::cancel(mod_id);
+
+	Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn download_mod(mod_id: &str, handle: AppHandle) -> Result {
@@ -117,7 +147,9 @@ async fn download_mod(mod_id: &str, handle: AppHandle) -> Result {
 
 	mod_loaders
 		.try_get(&remote_mod.common.loader_id)?
-		.download_mod(remote_mod)
+		.download_mod(remote_mod, |progress| {
+			handle.emit_safe(events::ModDownloadProgress(progress))
+		})
 		.await?;
 
 	refresh_local_mods(&mod_loaders, &handle)?;
@@ -133,15 +165,38 @@ async fn delete_mod(mod_id: &str, handle: AppHandle) -> Result {
 	let local_mod = local_mods.try_get(mod_id)?;
 	let mod_loaders = state.mod_loaders.read_state()?;
 
+	let mut games = Vec::new();
+	for provider_games in state.games.values() {
+		games.extend(provider_games.read_state()?.values().cloned());
+	}
+
 	mod_loaders
 		.try_get(&local_mod.common.loader_id)?
-		.delete_mod(local_mod)?;
+		.delete_mod(local_mod, &games)?;
 
 	refresh_local_mods(&mod_loaders, &handle)?;
 
 	Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn preview_mod_import(source_path: PathBuf) -> Result<ModImportPreview> {
+	Ok(mod_import::preview(&source_path)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn import_mod(options: ModImportOptions, handle: AppHandle) -> Result<LocalMod> {
+	let mod_loaders = handle.app_state().mod_loaders.read_state()?.clone();
+
+	let local_mod = mod_import::import(&options, &mod_loaders)?;
+
+	refresh_local_mods(&mod_loaders, &handle)?;
+
+	Ok(local_mod)
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn start_game(installed_game: InstalledGame, handle: AppHandle) -> Result {
@@ -187,12 +242,16 @@ async fn install_mod(game_id: GameId, mod_id: &str, handle: AppHandle) -> Result
 		game.try_get_installed_game()?.clone()
 	};
 
-	// Uninstall mod if it already exists, in case there are conflicting leftover files when updating.
+	let remote_mods = state.remote_mods.read_state()?.clone();
+
 	mod_loader
-		.uninstall_mod(&installed_game, &local_mod)
+		.install_mod(&installed_game, &local_mod, &remote_mods, &|progress| {
+			handle.emit_safe(events::ModDownloadProgress(progress));
+		})
 		.await?;
 
-	mod_loader.install_mod(&installed_game, &local_mod).await?;
+	// Dependencies might have been downloaded during install.
+	refresh_local_mods(&mod_loaders, &handle)?;
 
 	refresh_game_mods(&game_id, &handle)?;
 
@@ -201,6 +260,117 @@ async fn install_mod(game_id: GameId, mod_id: &str, handle: AppHandle) -> Result
 	Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn get_mod_compatibility(
+	game_id: GameId,
+	mod_id: &str,
+	handle: AppHandle,
+) -> Result<ModCompatibility> {
+	let state = handle.app_state();
+
+	let mod_data = match state.local_mods.read_state()?.get(mod_id) {
+		Some(local_mod) => local_mod.common.clone(),
+		None => state
+			.remote_mods
+			.read_state()?
+			.try_get(mod_id)?
+			.common
+			.clone(),
+	};
+
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(mod_compatibility::evaluate(&mod_data, installed_game))
+}
+
+// Errors from the last time the game ran, read from the BepInEx and Unity logs.
+#[tauri::command]
+#[specta::specta]
+async fn get_last_session_diagnostics(
+	game_id: GameId,
+	handle: AppHandle,
+) -> Result<SessionDiagnostics> {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(session_diagnostics::get_last_session(
+		installed_game,
+		&mod_loaders,
+	))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn verify_game_mods(game_id: GameId, handle: AppHandle) -> Result<GameIntegrity> {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(mod_integrity::verify(installed_game, &mod_loaders)?)
+}
+
+// Returns whatever is still broken after repairing.
+#[tauri::command]
+#[specta::specta]
+async fn repair_game_mods(game_id: GameId, handle: AppHandle) -> Result<GameIntegrity> {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let local_mods = refresh_local_mods(&mod_loaders, &handle)?;
+
+	let installed_game = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let game = games.try_get(&game_id.game_id)?;
+		game.try_get_installed_game()?.clone()
+	};
+
+	let integrity = mod_integrity::repair(&installed_game, &mod_loaders, &local_mods).await?;
+
+	refresh_game_mods(&game_id, &handle)?;
+
+	Ok(integrity)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_mod_loader_install_preview(
+	game_id: GameId,
+	mod_loader_id: &str,
+	handle: AppHandle,
+) -> Result<Vec<ArchiveEntry>> {
+	let state = handle.app_state();
+
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(state
+		.mod_loaders
+		.read_state()?
+		.try_get(mod_loader_id)?
+		.get_install_preview(installed_game)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_compatible_mods(game_id: GameId, handle: AppHandle) -> Result<CompatibleMods> {
+	let state = handle.app_state();
+	let local_mods = state.local_mods.read_state()?.clone();
+	let remote_mods = state.remote_mods.read_state()?.clone();
+
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let game = games.try_get(&game_id.game_id)?;
+
+	Ok(mod_compatibility::get_compatible_mods(
+		game,
+		&local_mods,
+		&remote_mods,
+	))
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn run_runnable_without_game(mod_id: &str, handle: AppHandle) -> Result {
@@ -238,6 +408,39 @@ async fn configure_mod(game_id: GameId, mod_id: &str, handle: AppHandle) -> Resu
 	Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn get_mod_configs(game_id: GameId, handle: AppHandle) -> Result<Vec<BepInExConfig>> {
+	let state = handle.app_state();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(bepinex_config::get_configs(installed_game)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn set_mod_config_value(
+	game_id: GameId,
+	file_name: &str,
+	section: &str,
+	key: &str,
+	value: &str,
+	handle: AppHandle,
+) -> Result<BepInExConfig> {
+	let state = handle.app_state();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(bepinex_config::set_value(
+		installed_game,
+		file_name,
+		section,
+		key,
+		value,
+	)?)
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn open_installed_mod_folder(game_id: GameId, mod_id: &str, handle: AppHandle) -> Result {
@@ -297,6 +500,7 @@ async fn uninstall_mod(game_id: GameId, mod_id: &str, handle: AppHandle) -> Resu
 	mod_loader
 		.uninstall_mod(&installed_game, &local_mod)
 		.await?;
+	mod_profiles::forget_mod(&installed_game, mod_id)?;
 
 	refresh_game_mods(&game_id, &handle)?;
 
@@ -320,6 +524,222 @@ async fn uninstall_all_mods(game_id: GameId, handle: AppHandle) -> Result {
 	Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn set_mod_enabled(
+	game_id: GameId,
+	mod_id: &str,
+	enabled: bool,
+	handle: AppHandle,
+) -> Result {
+	let state = handle.app_state();
+
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let local_mod = refresh_and_get_local_mod(mod_id, &mod_loaders, &handle).await?;
+
+	let installed_game = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let game = games.try_get(&game_id.game_id)?;
+		game.try_get_installed_game()?.clone()
+	};
+
+	mod_profiles::set_mod_enabled(&installed_game, &mod_loaders, &local_mod, enabled)?;
+
+	refresh_game_mods(&game_id, &handle)?;
+
+	Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_modpacks(handle: AppHandle) -> Result<Vec<Modpack>> {
+	let mod_loaders = handle.app_state().mod_loaders.read_state()?.clone();
+
+	Ok(modpacks::get_all(&mod_loaders, |error| {
+		handle.emit_error(format!("Failed to get modpacks: {error}"));
+	})
+	.await)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn install_modpack(game_id: GameId, modpack_id: &str, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let remote_mods = state.remote_mods.read_state()?.clone();
+
+	let modpack = modpacks::get(&mod_loaders, modpack_id, |error| {
+		handle.emit_error(format!("Failed to get modpacks: {error}"));
+	})
+	.await?;
+
+	let installed_game = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let game = games.try_get(&game_id.game_id)?;
+		game.try_get_installed_game()?.clone()
+	};
+
+	let result = modpack
+		.install(&installed_game, &mod_loaders, &remote_mods, &|progress| {
+			handle.emit_safe(events::ModDownloadProgress(progress));
+		})
+		.await;
+
+	// Downloads are kept even if the install failed.
+	refresh_local_mods(&mod_loaders, &handle)?;
+	refresh_game_mods(&game_id, &handle)?;
+	refresh_outdated_mods(&handle)?;
+
+	Ok(result?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_bepinex_build_options(
+	game_id: GameId,
+	handle: AppHandle,
+) -> Result<BepInExBuildOptions> {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(bepinex::get(&mod_loaders)?.get_build_options(installed_game)?)
+}
+
+// Only takes effect the next time BepInEx gets deployed. Use `upgrade_bepinex` to switch right away.
+#[tauri::command]
+#[specta::specta]
+async fn select_bepinex_build(
+	game_id: GameId,
+	build: Option<BepInExBuild>,
+	handle: AppHandle,
+) -> Result {
+	let state = handle.app_state();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(BepInEx::select_build(installed_game, build)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn upgrade_bepinex(game_id: GameId, build: BepInExBuild, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+
+	let installed_game = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let game = games.try_get(&game_id.game_id)?;
+		game.try_get_installed_game()?.clone()
+	};
+
+	bepinex::get(&mod_loaders)?.upgrade(&installed_game, build, &PayloadChoice::Latest)?;
+
+	refresh_game_mods(&game_id, &handle)?;
+
+	Ok(())
+}
+
+// Saves the game's mods, their versions and configs to a file that can be shared with other people.
+#[tauri::command]
+#[specta::specta]
+async fn export_mod_preset(game_id: GameId, path: PathBuf, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let local_mods = refresh_local_mods(&mod_loaders, &handle)?;
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	let preset = mod_presets::create(installed_game, &mod_loaders, &local_mods)?;
+
+	Ok(mod_presets::save(&preset, &path)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn import_mod_preset(game_id: GameId, path: PathBuf, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+	let preset = mod_presets::load(&path)?;
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let remote_mods = state.remote_mods.read_state()?.clone();
+
+	let installed_game = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let game = games.try_get(&game_id.game_id)?;
+		game.try_get_installed_game()?.clone()
+	};
+
+	let result = mod_presets::apply(
+		&installed_game,
+		&preset,
+		&mod_loaders,
+		&remote_mods,
+		&|progress| {
+			handle.emit_safe(events::ModDownloadProgress(progress));
+		},
+	)
+	.await;
+
+	// Even if applying failed halfway, some mods might have been downloaded or installed.
+	refresh_local_mods(&mod_loaders, &handle)?;
+	refresh_game_mods(&game_id, &handle)?;
+	refresh_outdated_mods(&handle)?;
+
+	Ok(result?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_mod_profiles(game_id: GameId, handle: AppHandle) -> Result<ModProfiles> {
+	let state = handle.app_state();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(mod_profiles::read(installed_game))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn save_mod_profile(game_id: GameId, profile_name: &str, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(mod_profiles::save_current(installed_game, profile_name)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn delete_mod_profile(game_id: GameId, profile_name: &str, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	Ok(mod_profiles::remove(installed_game, profile_name)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn activate_mod_profile(game_id: GameId, profile_name: &str, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let local_mods = refresh_local_mods(&mod_loaders, &handle)?;
+
+	let installed_game = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let game = games.try_get(&game_id.game_id)?;
+		game.try_get_installed_game()?.clone()
+	};
+
+	mod_profiles::activate(&installed_game, profile_name, &mod_loaders, &local_mods)?;
+
+	refresh_game_mods(&game_id, &handle)?;
+
+	Ok(())
+}
+
 fn refresh_local_mods(mod_loaders: &mod_loader::Map, handle: &AppHandle) -> Result<local_mod::Map> {
 	let local_mods: HashMap<_, _> = mod_loaders
 		.values()
@@ -398,7 +818,9 @@ async fn refresh_and_get_local_mod(
 					// If local mod still can't be found on disk,
 					// we try to download it from the database.
 					mod_loader
-						.download_mod(remote_mods.try_get(mod_id)?)
+						.download_mod(remote_mods.try_get(mod_id)?, |progress| {
+							handle.emit_safe(events::ModDownloadProgress(progress));
+						})
 						.await?;
 				} else {
 					// If downloading from the database isn't possible,
@@ -442,6 +864,279 @@ async fn refresh_mods(handle: AppHandle) -> Result {
 		.mod_loaders
 		.write_state_value(mod_loaders)?;
 
+	refresh_outdated_mods(&handle)?;
+
+	// Not being able to update the mod loader files isn't a big deal, since the bundled ones still work.
+	// Runs in the background, since nothing here needs the new files right away.
+	log::info!("Updating mod loader payloads...");
+	let payload_handle = handle.clone();
+	tauri::async_runtime::spawn(async move {
+		loader_payloads::update(
+			BepInEx::ID,
+			&|progress| {
+				payload_handle.emit_safe(events::ModDownloadProgress(progress));
+			},
+			|error| {
+				log::error!("Failed to update mod loader payloads: {error}");
+			},
+		)
+		.await;
+	});
+
+	Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_mod_sources() -> Result<ModSources> {
+	Ok(mod_sources::read())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn save_mod_sources(sources: ModSources, handle: AppHandle) -> Result {
+	mod_sources::write(&sources)?;
+
+	let mod_loaders = handle.app_state().mod_loaders.read_state()?.clone();
+	refresh_remote_mods(&mod_loaders, &handle).await?;
+	refresh_outdated_mods(&handle)?;
+
+	Ok(())
+}
+
+fn refresh_outdated_mods(handle: &AppHandle) -> Result<Vec<OutdatedMod>> {
+	let state = handle.app_state();
+	let remote_mods = state.remote_mods.read_state()?.clone();
+
+	let mut games = Vec::new();
+	for provider_games in state.games.values() {
+		games.extend(provider_games.read_state()?.values().cloned());
+	}
+
+	let outdated_mods = mod_updates::get_outdated_mods(&games, &remote_mods);
+
+	log::info!("Found {} outdated installed mods.", outdated_mods.len());
+	handle.emit_safe(events::SyncOutdatedMods(outdated_mods.clone()));
+
+	Ok(outdated_mods)
+}
+
+// Re-downloads the latest version of the mod and reinstalls it in the given game.
+// Set `download` to false if the latest version was already downloaded.
+async fn update_game_mod(
+	game_id: &GameId,
+	mod_id: &str,
+	download: bool,
+	handle: &AppHandle,
+) -> Result {
+	let state = handle.app_state();
+
+	{
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+		mod_pins::ensure_unpinned(installed_game, mod_id)?;
+	}
+
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let remote_mods = state.remote_mods.read_state()?.clone();
+	let remote_mod = remote_mods.try_get(mod_id)?;
+	let mod_loader = mod_loaders.try_get(&remote_mod.common.loader_id)?;
+
+	if download {
+		mod_loader
+			.download_mod(remote_mod, |progress| {
+				handle.emit_safe(events::ModDownloadProgress(progress));
+			})
+			.await?;
+	}
+
+	reinstall_game_mod(game_id, mod_id, handle).await
+}
+
+// Installs the mod again in the given game, which also applies the game's pinned version, if any.
+async fn reinstall_game_mod(game_id: &GameId, mod_id: &str, handle: &AppHandle) -> Result {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let remote_mods = state.remote_mods.read_state()?.clone();
+
+	let local_mods = refresh_local_mods(&mod_loaders, handle)?;
+	let local_mod = local_mods.try_get(mod_id)?;
+	let mod_loader = mod_loaders.try_get(&local_mod.common.loader_id)?;
+
+	let installed_game = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let game = games.try_get(&game_id.game_id)?;
+		game.try_get_installed_game()?.clone()
+	};
+
+	mod_loader
+		.install_mod(&installed_game, local_mod, &remote_mods, &|progress| {
+			handle.emit_safe(events::ModDownloadProgress(progress));
+		})
+		.await?;
+
+	refresh_game_mods(game_id, handle)?;
+
+	Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_mod_versions(mod_id: &str, handle: AppHandle) -> Result<Vec<ModVersion>> {
+	let state = handle.app_state();
+	let remote_mod = state.remote_mods.read_state()?.get(mod_id).cloned();
+
+	let loader_id = match &remote_mod {
+		Some(remote_mod) => remote_mod.common.loader_id.clone(),
+		None => state
+			.local_mods
+			.read_state()?
+			.try_get(mod_id)?
+			.common
+			.loader_id
+			.clone(),
+	};
+
+	let local_mod = state.local_mods.read_state()?.get(mod_id).cloned();
+
+	Ok(
+		mod_versions::get_versions(&loader_id, mod_id, local_mod.as_ref(), remote_mod.as_ref())
+			.await,
+	)
+}
+
+fn set_mod_pin(
+	game_id: &GameId,
+	mod_id: &str,
+	version: Option<&str>,
+	handle: &AppHandle,
+) -> Result {
+	let state = handle.app_state();
+	let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+	let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+
+	let mut pins = mod_pins::read(installed_game);
+	match version {
+		Some(version) => pins.pin(mod_id, version),
+		None => {
+			pins.unpin(mod_id);
+		}
+	}
+	mod_pins::write(installed_game, &pins)?;
+
+	Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn pin_mod_version(
+	game_id: GameId,
+	mod_id: &str,
+	version: &str,
+	handle: AppHandle,
+) -> Result {
+	let state = handle.app_state();
+	let mod_loaders = state.mod_loaders.read_state()?.clone();
+	let local_mod = refresh_and_get_local_mod(mod_id, &mod_loaders, &handle).await?;
+
+	// Older versions need to be downloaded before the game can use them.
+	if !mod_versions::is_downloaded(&local_mod, version) {
+		let remote_mods = state.remote_mods.read_state()?.clone();
+		let remote_mod = remote_mods.try_get(mod_id)?;
+		let download = mod_versions::get_download(remote_mod, version).await?;
+
+		mod_loaders
+			.try_get(&local_mod.common.loader_id)?
+			.download_mod_version(remote_mod, &download, |progress| {
+				handle.emit_safe(events::ModDownloadProgress(progress));
+			})
+			.await?;
+	}
+
+	set_mod_pin(&game_id, mod_id, Some(version), &handle)?;
+	reinstall_game_mod(&game_id, mod_id, &handle).await?;
+	refresh_outdated_mods(&handle)?;
+
+	Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn unpin_mod_version(game_id: GameId, mod_id: &str, handle: AppHandle) -> Result {
+	set_mod_pin(&game_id, mod_id, None, &handle)?;
+	reinstall_game_mod(&game_id, mod_id, &handle).await?;
+	refresh_outdated_mods(&handle)?;
+
+	Ok(())
+}
+
+// Pins the game to the newest downloaded version that's older than the installed one.
+#[tauri::command]
+#[specta::specta]
+async fn rollback_mod(game_id: GameId, mod_id: &str, handle: AppHandle) -> Result {
+	let state = handle.app_state();
+
+	let installed_version = {
+		let games = state.games.try_get(&game_id.provider_id)?.read_state()?;
+		let installed_game = games.try_get(&game_id.game_id)?.try_get_installed_game()?;
+		installed_game
+			.installed_mod_versions
+			.try_get(mod_id)?
+			.clone()
+	};
+
+	let local_mod = state.local_mods.read_state()?.try_get(mod_id)?.clone();
+	let rollback_version = mod_versions::get_rollback_version(&local_mod, &installed_version)?;
+
+	set_mod_pin(&game_id, mod_id, Some(&rollback_version), &handle)?;
+	reinstall_game_mod(&game_id, mod_id, &handle).await?;
+	refresh_outdated_mods(&handle)?;
+
+	Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn update_mod(game_id: GameId, mod_id: &str, handle: AppHandle) -> Result {
+	update_game_mod(&game_id, mod_id, true, &handle).await?;
+
+	refresh_outdated_mods(&handle)?;
+
+	Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn update_all_mods(handle: AppHandle) -> Result {
+	let mut downloaded_mod_ids = HashSet::new();
+
+	for outdated_mod in refresh_outdated_mods(&handle)? {
+		// The same mod can be outdated in multiple games, but we only need to download it once.
+		// If the download fails, the next game with this mod gets to try again.
+		let download = !downloaded_mod_ids.contains(&outdated_mod.mod_id);
+
+		match update_game_mod(
+			&outdated_mod.game_id,
+			&outdated_mod.mod_id,
+			download,
+			&handle,
+		)
+		.await
+		{
+			Ok(()) => {
+				downloaded_mod_ids.insert(outdated_mod.mod_id.clone());
+			}
+			Err(err) => {
+				handle.emit_error(format!(
+					"Failed to update mod {}: {err}",
+					outdated_mod.mod_id
+				));
+			}
+		}
+	}
+
+	refresh_outdated_mods(&handle)?;
+
 	Ok(())
 }
 
@@ -501,6 +1196,8 @@ async fn refresh_games(handle: AppHandle, provider_id: ProviderId) -> Result {
 
 	provider
 		.get_games(|mut game: Game| {
+			game.link_installed_game();
+
 			match state.remote_games.read() {
 				Ok(remote_games) => {
 					// Assign the remote game here as we find the new game.
@@ -564,6 +1261,12 @@ async fn get_provider_ids() -> Result<Vec<ProviderId>> {
 	Ok(provider::get_provider_ids())
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn get_database_statuses() -> Result<Vec<DatabaseStatus>> {
+	Ok(remote_database::get_statuses())
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn add_game(path: PathBuf, handle: AppHandle) -> Result {
@@ -634,6 +1337,30 @@ async fn frontend_ready() -> Result {
 	Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn get_wine_settings() -> Result<WineSettings> {
+	Ok(wine::read())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn save_wine_settings(settings: WineSettings) -> Result {
+	Ok(wine::write(&settings)?)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_running_runnables() -> Result<Vec<RunnableProcess>> {
+	Ok(runnable_processes::get_running())
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn stop_runnable(mod_id: &str) -> Result {
+	Ok(runnable_processes::stop(mod_id)?)
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn open_logs_folder() -> Result {
@@ -714,19 +1441,39 @@ fn main() {
 
 	let builder = Builder::<tauri::Wry>::new()
 		.commands(tauri_specta::collect_commands![
+			activate_mod_profile,
 			add_game,
+			cancel_mod_download,
 			clear_cache,
 			configure_mod,
+			delete_mod_profile,
 			delete_mod,
 			reset_steam_cache,
 			download_mod,
+			export_mod_preset,
 			frontend_ready,
+			get_bepinex_build_options,
+			get_compatible_mods,
+			get_database_statuses,
 			get_game_ids,
 			get_game,
+			get_last_session_diagnostics,
 			get_local_mods,
+			get_mod_compatibility,
+			get_mod_configs,
+			get_mod_loader_install_preview,
+			get_mod_profiles,
+			get_mod_sources,
+			get_mod_versions,
+			get_modpacks,
 			get_provider_ids,
 			get_remote_mods,
+			get_running_runnables,
+			get_wine_settings,
+			import_mod,
+			import_mod_preset,
 			install_mod,
+			install_modpack,
 			open_game_folder,
 			open_game_mods_folder,
 			open_installed_mod_folder,
@@ -734,17 +1481,33 @@ fn main() {
 			open_mod_folder,
 			open_mod_loader_folder,
 			open_mods_folder,
+			pin_mod_version,
+			preview_mod_import,
 			refresh_game,
 			refresh_games,
 			refresh_mods,
 			refresh_remote_games,
 			remove_game,
+			repair_game_mods,
+			rollback_mod,
 			run_provider_command,
 			run_runnable_without_game,
+			save_mod_profile,
+			save_mod_sources,
+			save_wine_settings,
+			select_bepinex_build,
+			set_mod_config_value,
+			set_mod_enabled,
 			start_game_exe,
 			start_game,
+			stop_runnable,
 			uninstall_all_mods,
 			uninstall_mod,
+			unpin_mod_version,
+			update_all_mods,
+			update_mod,
+			upgrade_bepinex,
+			verify_game_mods,
 		])
 		.events(events::collect_events());
 
@@ -791,6 +1554,15 @@ fn main() {
 		.setup(move |app| {
 			builder.mount_events(app);
 
+			let handle = app.handle().clone();
+			runnable_processes::set_exit_handler(move |exit| {
+				// Crashes still count as exits, so anything waiting for the exit code gets it either way.
+				handle.emit_safe(events::RunnableExited(exit.clone()));
+				if exit.is_crash {
+					handle.emit_safe(events::RunnableCrashed(exit));
+				}
+			});
+
 			if let Some(window) = app.get_webview_window("main") {
 				let mut title = format!("Rai Pal {}", env!("CARGO_PKG_VERSION"));
 				if cfg!(debug_assertions) {
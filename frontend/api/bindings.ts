@@ -5,6 +5,14 @@
 
 
 export const commands = {
+async activateModProfile(gameId: GameId, profileName: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("activate_mod_profile", { gameId, profileName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async addGame(path: string) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("add_game", { path }) };
@@ -13,6 +21,14 @@ async addGame(path: string) : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async cancelModDownload(modId: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_mod_download", { modId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async clearCache() : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("clear_cache") };
@@ -29,6 +45,14 @@ async configureMod(gameId: GameId, modId: string) : Promise<Result<null, Error>>
     else return { status: "error", error: e  as any };
 }
 },
+async deleteModProfile(gameId: GameId, profileName: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_mod_profile", { gameId, profileName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async deleteMod(modId: string) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("delete_mod", { modId }) };
@@ -53,6 +77,14 @@ async downloadMod(modId: string) : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async exportModPreset(gameId: GameId, path: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_mod_preset", { gameId, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async frontendReady() : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("frontend_ready") };
@@ -61,6 +93,30 @@ async frontendReady() : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async getBepinexBuildOptions(gameId: GameId) : Promise<Result<BepInExBuildOptions, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_bepinex_build_options", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCompatibleMods(gameId: GameId) : Promise<Result<CompatibleMods, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_compatible_mods", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDatabaseStatuses() : Promise<Result<DatabaseStatus[], Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_database_statuses") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getGameIds(dataQuery: GamesQuery | null) : Promise<Result<GameIdsResponse, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_game_ids", { dataQuery }) };
@@ -77,6 +133,14 @@ async getGame(id: GameId) : Promise<Result<Game, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async getLastSessionDiagnostics(gameId: GameId) : Promise<Result<SessionDiagnostics, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_last_session_diagnostics", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getLocalMods() : Promise<Result<Partial<{ [key in string]: LocalMod }>, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_local_mods") };
@@ -85,6 +149,62 @@ async getLocalMods() : Promise<Result<Partial<{ [key in string]: LocalMod }>, Er
     else return { status: "error", error: e  as any };
 }
 },
+async getModCompatibility(gameId: GameId, modId: string) : Promise<Result<ModCompatibility, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mod_compatibility", { gameId, modId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModConfigs(gameId: GameId) : Promise<Result<BepInExConfig[], Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mod_configs", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModLoaderInstallPreview(gameId: GameId, modLoaderId: string) : Promise<Result<ArchiveEntry[], Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mod_loader_install_preview", { gameId, modLoaderId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModProfiles(gameId: GameId) : Promise<Result<ModProfiles, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mod_profiles", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModSources() : Promise<Result<ModSources, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mod_sources") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModVersions(modId: string) : Promise<Result<ModVersion[], Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_mod_versions", { modId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModpacks() : Promise<Result<Modpack[], Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_modpacks") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async getProviderIds() : Promise<Result<ProviderId[], Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_provider_ids") };
@@ -101,6 +221,38 @@ async getRemoteMods() : Promise<Result<Partial<{ [key in string]: RemoteMod }>,
     else return { status: "error", error: e  as any };
 }
 },
+async getRunningRunnables() : Promise<Result<RunnableProcess[], Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_running_runnables") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getWineSettings() : Promise<Result<WineSettings, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_wine_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async importMod(options: ModImportOptions) : Promise<Result<LocalMod, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_mod", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async importModPreset(gameId: GameId, path: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_mod_preset", { gameId, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async installMod(gameId: GameId, modId: string) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("install_mod", { gameId, modId }) };
@@ -109,6 +261,14 @@ async installMod(gameId: GameId, modId: string) : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async installModpack(gameId: GameId, modpackId: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("install_modpack", { gameId, modpackId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async openGameFolder(gameId: GameId) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("open_game_folder", { gameId }) };
@@ -165,6 +325,22 @@ async openModsFolder() : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async pinModVersion(gameId: GameId, modId: string, version: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pin_mod_version", { gameId, modId, version }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async previewModImport(sourcePath: string) : Promise<Result<ModImportPreview, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_mod_import", { sourcePath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async refreshGame(gameId: GameId) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("refresh_game", { gameId }) };
@@ -205,6 +381,22 @@ async removeGame(path: string) : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async repairGameMods(gameId: GameId) : Promise<Result<GameIntegrity, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("repair_game_mods", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async rollbackMod(gameId: GameId, modId: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rollback_mod", { gameId, modId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async runProviderCommand(game: Game, commandAction: ProviderCommandAction) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("run_provider_command", { game, commandAction }) };
@@ -221,6 +413,54 @@ async runRunnableWithoutGame(modId: string) : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async saveModProfile(gameId: GameId, profileName: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_mod_profile", { gameId, profileName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async saveModSources(sources: ModSources) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_mod_sources", { sources }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async saveWineSettings(settings: WineSettings) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_wine_settings", { settings }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async selectBepinexBuild(gameId: GameId, build: BepInExBuild | null) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("select_bepinex_build", { gameId, build }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setModConfigValue(gameId: GameId, fileName: string, section: string, key: string, value: string) : Promise<Result<BepInExConfig, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_mod_config_value", { gameId, fileName, section, key, value }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setModEnabled(gameId: GameId, modId: string, enabled: boolean) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_mod_enabled", { gameId, modId, enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async startGameExe(installedGame: InstalledGame) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("start_game_exe", { installedGame }) };
@@ -237,6 +477,14 @@ async startGame(installedGame: InstalledGame) : Promise<Result<null, Error>> {
     else return { status: "error", error: e  as any };
 }
 },
+async stopRunnable(modId: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_runnable", { modId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async uninstallAllMods(gameId: GameId) : Promise<Result<null, Error>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("uninstall_all_mods", { gameId }) };
@@ -252,6 +500,46 @@ async uninstallMod(gameId: GameId, modId: string) : Promise<Result<null, Error>>
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
+},
+async unpinModVersion(gameId: GameId, modId: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unpin_mod_version", { gameId, modId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateAllMods() : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_all_mods") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateMod(gameId: GameId, modId: string) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_mod", { gameId, modId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async upgradeBepinex(gameId: GameId, build: BepInExBuild) : Promise<Result<null, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("upgrade_bepinex", { gameId, build }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async verifyGameMods(gameId: GameId) : Promise<Result<GameIntegrity, Error>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("verify_game_mods", { gameId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
 }
 }
 
@@ -263,18 +551,26 @@ errorRaised: ErrorRaised,
 executedProviderCommand: ExecutedProviderCommand,
 foundGame: FoundGame,
 gamesChanged: GamesChanged,
+modDownloadProgress: ModDownloadProgress,
+runnableCrashed: RunnableCrashed,
+runnableExited: RunnableExited,
 selectInstalledGame: SelectInstalledGame,
 syncLocalMods: SyncLocalMods,
 syncModLoaders: SyncModLoaders,
+syncOutdatedMods: SyncOutdatedMods,
 syncRemoteMods: SyncRemoteMods
 }>({
 errorRaised: "error-raised",
 executedProviderCommand: "executed-provider-command",
 foundGame: "found-game",
 gamesChanged: "games-changed",
+modDownloadProgress: "mod-download-progress",
+runnableCrashed: "runnable-crashed",
+runnableExited: "runnable-exited",
 selectInstalledGame: "select-installed-game",
 syncLocalMods: "sync-local-mods",
 syncModLoaders: "sync-mod-loaders",
+syncOutdatedMods: "sync-outdated-mods",
 syncRemoteMods: "sync-remote-mods"
 })
 
@@ -285,20 +581,36 @@ syncRemoteMods: "sync-remote-mods"
 /** user-defined types **/
 
 export type Architecture = "X64" | "X86"
-export type CommonModData = { id: string; engine: EngineBrand | null; unityBackend: UnityScriptingBackend | null; engineVersionRange: EngineVersionRange | null; loaderId: string }
+export type ArchiveEntry = { path: string; size: bigint; isDir: boolean }
+export type BepInExBuild = "Stable5" | "Stable6" | "BleedingEdge"
+export type BepInExBuildOptions = { selected: BepInExBuild | null; deployed: BepInExBuild | null; available: BepInExBuild[] }
+export type BepInExConfig = { fileName: string; pluginName: string | null; pluginGuid: string | null; entries: BepInExConfigEntry[] }
+export type BepInExConfigEntry = { section: string; key: string; value: string; description: string | null; settingType: string | null; defaultValue: string | null; acceptableValues: string[] | null; acceptableRange: BepInExConfigRange | null; allowsMultipleValues: boolean }
+export type BepInExConfigRange = { from: string; to: string }
+export type CommonModData = { id: string; engine: EngineBrand | null; unityBackend: UnityScriptingBackend | null; engineVersionRange: EngineVersionRange | null; architecture: Architecture | null; loaderId: string }
+export type CompatibilityIssue = "EngineUnknown" | "EngineMismatch" | "EngineVersionUnknown" | "EngineVersionTooOld" | "EngineVersionTooNew" | "UnityBackendUnknown" | "UnityBackendMismatch" | "ArchitectureUnknown" | "ArchitectureMismatch"
+export type CompatibilityVerdict = "Compatible" | "Unknown" | "Incompatible"
+export type CompatibleMod = { modId: string; compatibility: ModCompatibility; isDownloaded: boolean; isInstalled: boolean }
+export type CompatibleMods = { compatible: CompatibleMod[]; excluded: ExcludedMod[] }
+export type DatabaseStatus = { path: string; mirror: string | null; updatedAt: bigint; ageSeconds: bigint; isOffline: boolean }
+export type DownloadProgress = { id: string; downloadedBytes: bigint; totalBytes: bigint | null }
 export type EngineBrand = "Unity" | "Unreal" | "Godot" | "GameMaker"
 export type EngineVersion = { numbers: EngineVersionNumbers; suffix: string | null; display: string }
 export type EngineVersionNumbers = { major: number; minor: number | null; patch: number | null }
 export type EngineVersionRange = { minimum: EngineVersionNumbers | null; maximum: EngineVersionNumbers | null }
 export type Error = "Tauri" | "Core" | "SerdeJson" | { FailedToGetResourcesPath: string } | { FailedToAccessStateData: string }
 export type ErrorRaised = string
+export type ExcludedMod = { modId: string; reason: ModExclusionReason; issues: CompatibilityIssue[] }
 export type ExecutedProviderCommand = null
+export type FileIssue = { path: string; kind: FileIssueKind }
+export type FileIssueKind = "Missing" | "Modified"
 export type FoundGame = GameId
 export type Game = { id: GameId; externalId: string; tags: GameTag[]; installedGame: InstalledGame | null; remoteGame: RemoteGame | null; title: GameTitle; thumbnailUrl: string | null; releaseDate: bigint | null; providerCommands: Partial<{ [key in ProviderCommandAction]: ProviderCommand }>; fromSubscriptions: GameSubscription[] }
 export type GameEngine = { brand: EngineBrand; version: EngineVersion | null }
 export type GameExecutable = { path: string; name: string; engine: GameEngine | null; architecture: Architecture | null; scriptingBackend: UnityScriptingBackend | null }
 export type GameId = { providerId: ProviderId; gameId: string }
 export type GameIdsResponse = { gameIds: GameId[]; totalCount: bigint }
+export type GameIntegrity = { loaders: LoaderIntegrity[]; mods: ModIntegrity[] }
 export type GameSubscription = "UbisoftClassics" | "UbisoftPremium" | "XboxGamePass" | "EaPlay"
 export type GameTag = "VR" | "Demo"
 export type GameTitle = { display: string; normalized: string[] }
@@ -307,25 +619,56 @@ export type GamesFilter = { providers: (ProviderId | null)[]; tags: (GameTag | n
 export type GamesQuery = { filter: GamesFilter; search: string; sortBy: GamesSortBy; sortDescending: boolean }
 export type GamesSortBy = "Title" | "Engine" | "ReleaseDate"
 export type InstallState = "Installed" | "NotInstalled"
-export type InstalledGame = { id: string; executable: GameExecutable; installedModVersions: Partial<{ [key in string]: string }>; discriminator: string | null; startCommand: ProviderCommand | null }
+export type InstalledFile = { path: string; sha256: string }
+export type InstalledGame = { id: string; executable: GameExecutable; installedModVersions: Partial<{ [key in string]: string }>; disabledMods: string[]; pinnedModVersions: Partial<{ [key in string]: string }>; discriminator: string | null; startCommand: ProviderCommand | null; providerId: ProviderId | null; externalId: string | null }
+export type LoaderIntegrity = { loaderId: string; expectedVersion: string | null; installedVersion: string | null; issues: FileIssue[] }
 export type LocalMod = { data: LocalModData; common: CommonModData }
 export type LocalModData = { path: string; manifest: Manifest | null }
-export type Manifest = { title: string | null; version: string; runnable: RunnableModData | null; engine: EngineBrand | null; engineVersionRange: EngineVersionRange | null; unityBackend: UnityScriptingBackend | null }
-export type ModDownload = { id: string; url: string; root: string | null; runnable: RunnableModData | null }
+export type Manifest = { title: string | null; version: string; runnable: RunnableModData | null; engine: EngineBrand | null; engineVersionRange: EngineVersionRange | null; unityBackend: UnityScriptingBackend | null; architecture: Architecture | null; dependencies: ModDependency[] | null; incompatibilities: ModDependency[] | null; bepinexBuilds: BepInExBuild[] | null; configFile: string | null; files: InstalledFile[] | null }
+export type ModCompatibility = { verdict: CompatibilityVerdict; issues: CompatibilityIssue[] }
+export type ModDependency = { id: string; version: string | null }
+export type ModDownload = { id: string; url: string; root: string | null; runnable: RunnableModData | null; sha256: string | null; minisign: ModSignature | null }
+export type ModDownloadProgress = DownloadProgress
+export type ModExclusionReason = "Incompatible" | "Deprecated"
+export type ModGithubInfo = { user: string; repo: string; assetName: string; root: string | null; runnable: RunnableModData | null; sha256: Partial<{ [key in string]: string }> | null; minisign: ModSignature | null }
+export type ModImportLayout = "BepInExFolders" | "LoosePlugins" | "Runnable"
+export type ModImportOptions = { sourcePath: string; id: string | null; title: string | null; version: string | null; layout: ModImportLayout | null; unityBackend: UnityScriptingBackend | null; runnable: RunnableModData | null }
+export type ModImportPreview = { id: string; loaderId: string; layout: ModImportLayout; unityBackend: UnityScriptingBackend | null; executables: string[] }
+export type ModIntegrity = { modId: string; issues: FileIssue[] }
 export type ModKind = "Installable" | "Runnable"
 export type ModLoaderData = { id: string; path: string; kind: ModKind }
+export type ModProfile = { enabledMods: string[] }
+export type ModProfiles = { active: string | null; profiles: Partial<{ [key in string]: ModProfile }> }
+export type ModSignature = { publicKey: string; signatureUrl: string | null }
+export type ModSource = { loaderId: string; url: string; thunderstoreCommunity: string | null; priority: number; label: string }
+export type ModSources = { sources: ModSource[] }
+export type ModVersion = { id: string; isLatest: boolean; isDownloaded: boolean; download: ModDownload | null }
+export type Modpack = { loaderId: string; source: string; entry: ModpackEntry }
+export type ModpackConfigOverride = { fileName: string; section: string; key: string; value: string }
+export type ModpackEntry = { id: string; title: string; author: string; description: string; engine: EngineBrand | null; engineVersionRange: EngineVersionRange | null; unityBackend: UnityScriptingBackend | null; mods: ModpackMod[]; configOverrides: ModpackConfigOverride[] | null }
+export type ModpackMod = { id: string; version: string | null }
+export type OutdatedMod = { gameId: GameId; modId: string; installedVersion: string; latestVersion: string }
 export type ProviderCommand = { String: string } | { Path: [string, string[]] }
 export type ProviderCommandAction = "Install" | "ShowInLibrary" | "ShowInStore" | "Start" | "OpenInBrowser"
 export type ProviderId = "Ea" | "Epic" | "Gog" | "Itch" | "Manual" | "Steam" | "Ubisoft" | "Xbox"
 export type RemoteGame = { title: string | null; engine: GameEngine | null; ids: Partial<{ [key in ProviderId]: string[] }>; subscriptions: GameSubscription[] | null }
 export type RemoteMod = { common: CommonModData; data: RemoteModData }
-export type RemoteModData = { title: string; deprecated: boolean; author: string; sourceCode: string; description: string; latestVersion: ModDownload | null }
+export type RemoteModData = { title: string; deprecated: boolean; author: string; sourceCode: string; description: string; latestVersion: ModDownload | null; dependencies: ModDependency[]; incompatibilities: ModDependency[]; bepinexBuilds: BepInExBuild[]; configFile: string | null; versions: ModDownload[]; github: ModGithubInfo | null; source: string }
+export type RunnableCrashed = RunnableExit
+export type RunnableExit = { process: RunnableProcess; exitCode: number | null; isCrash: boolean }
+export type RunnableExited = RunnableExit
 export type RunnableModData = { path: string; args: string[] }
+export type RunnableProcess = { modId: string; pid: number; logPath: string; startedAt: bigint }
 export type SelectInstalledGame = [ProviderId, string]
+export type SessionDiagnostics = { bepinexLogPath: string | null; playerLogPath: string | null; lastWrittenAt: bigint | null; issues: SessionLogIssue[]; issueCounts: Partial<{ [key in string]: bigint }>; unattributedIssueCount: bigint }
+export type SessionLog = "BepInEx" | "UnityPlayer"
+export type SessionLogIssue = { log: SessionLog; lineNumber: bigint; message: string; details: string[]; source: string | null; modId: string | null }
 export type SyncLocalMods = Partial<{ [key in string]: LocalMod }>
 export type SyncModLoaders = Partial<{ [key in string]: ModLoaderData }>
+export type SyncOutdatedMods = OutdatedMod[]
 export type SyncRemoteMods = Partial<{ [key in string]: RemoteMod }>
 export type UnityScriptingBackend = "Il2Cpp" | "Mono"
+export type WineSettings = { defaultRunner: string | null; defaultPrefix: string | null }
 
 /** tauri-specta globals **/
 